@@ -0,0 +1,208 @@
+use anyhow::Result;
+use serde::Serialize;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::protocol::{
+    ApprovalPatternKind, ApprovalRule, ApprovalRuleAction, Op, SandboxMode, Submission,
+};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AutoApprovedPayload {
+    pub session_id: String,
+    pub approval_id: String,
+    pub command: String,
+    /// Pattern of the allow rule that matched.
+    pub rule: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AutoDeniedPayload {
+    pub session_id: String,
+    pub approval_id: String,
+    pub command: String,
+    pub reason: String,
+}
+
+/// Programs that change files whatever their arguments.
+const MUTATING_PROGRAMS: &[&str] = &[
+    "rm", "rmdir", "mv", "cp", "mkdir", "touch", "chmod", "chown", "chgrp", "ln", "dd", "truncate",
+    "tee", "install", "patch", "shred", "unlink", "rsync",
+];
+
+/// Subcommands, space separated, that write to the working tree or a package store.
+const MUTATING_SUBCOMMANDS: &[(&str, &str)] = &[
+    (
+        "git",
+        "add am apply checkout cherry-pick clean clone commit init merge mv pull push rebase \
+         reset restore revert rm stash switch tag",
+    ),
+    ("npm", "install i ci uninstall update link publish"),
+    ("pnpm", "install i add remove update link publish"),
+    ("yarn", "install add remove upgrade link publish"),
+    ("pip", "install uninstall"),
+    ("pip3", "install uninstall"),
+    ("cargo", "install uninstall add remove fmt fix publish"),
+];
+
+/// Whether one simple command (no shell operators) would modify files.
+fn segment_mutates(words: &[&str]) -> bool {
+    let Some(program) = words.first() else {
+        return false;
+    };
+    let program = program.rsplit('/').next().unwrap_or(program);
+    if MUTATING_PROGRAMS.contains(&program) {
+        return true;
+    }
+    if program == "sed"
+        && words
+            .iter()
+            .any(|w| w.starts_with("-i") || *w == "--in-place")
+    {
+        return true;
+    }
+    MUTATING_SUBCOMMANDS.iter().any(|(name, subcommands)| {
+        *name == program
+            && words[1..]
+                .iter()
+                .find(|w| !w.starts_with('-'))
+                .is_some_and(|sub| subcommands.split_whitespace().any(|s| s == *sub))
+    })
+}
+
+/// Best-effort check for commands that write to disk. Shell scripts passed via
+/// `bash -lc` are split on operators and redirections count as writes, so this
+/// errs towards reporting a write.
+pub fn is_mutating_command(command: &[String]) -> bool {
+    let script = match command {
+        [shell, flag, script, ..]
+            if matches!(shell.rsplit('/').next(), Some("bash" | "sh" | "zsh"))
+                && (flag == "-c" || flag == "-lc") =>
+        {
+            script.clone()
+        }
+        _ => command.join(" "),
+    };
+    // `2>&1` and `>/dev/null` don't touch the workspace
+    let without_harmless = script
+        .replace("2>&1", " ")
+        .replace(">/dev/null", " ")
+        .replace("> /dev/null", " ");
+    if without_harmless.contains('>') {
+        return true;
+    }
+    without_harmless
+        .split(['&', '|', ';', '\n'])
+        .map(|segment| segment.split_whitespace().collect::<Vec<_>>())
+        .any(|words| segment_mutates(&words))
+}
+
+enum Matcher {
+    Glob(glob::Pattern),
+    Regex(regex::Regex),
+}
+
+impl Matcher {
+    fn matches(&self, command: &str) -> bool {
+        match self {
+            Matcher::Glob(pattern) => pattern.matches(command),
+            Matcher::Regex(regex) => regex.is_match(command),
+        }
+    }
+}
+
+struct CompiledRule {
+    pattern: String,
+    action: ApprovalRuleAction,
+    matcher: Matcher,
+}
+
+/// A session's `auto_approve` rules, compiled once, its sandbox, and the stdin
+/// channel used to answer approvals without going through the UI.
+pub struct AutoApprover {
+    rules: Vec<CompiledRule>,
+    sandbox: Option<SandboxMode>,
+    stdin_tx: mpsc::UnboundedSender<String>,
+}
+
+/// Compile `rules`, failing on the first pattern that isn't valid.
+fn compile(rules: &[ApprovalRule]) -> Result<Vec<CompiledRule>, String> {
+    rules
+        .iter()
+        .map(|rule| {
+            let matcher =
+                match rule.kind {
+                    ApprovalPatternKind::Glob => glob::Pattern::new(&rule.pattern)
+                        .map(Matcher::Glob)
+                        .map_err(|e| format!("Invalid glob '{}': {}", rule.pattern, e))?,
+                    ApprovalPatternKind::Regex => regex::Regex::new(&rule.pattern)
+                        .map(Matcher::Regex)
+                        .map_err(|e| format!("Invalid regex '{}': {}", rule.pattern, e))?,
+                };
+            Ok(CompiledRule {
+                pattern: rule.pattern.clone(),
+                action: rule.action,
+                matcher,
+            })
+        })
+        .collect()
+}
+
+/// Check that every pattern in `rules` compiles.
+pub fn validate_rules(rules: &[ApprovalRule]) -> Result<(), String> {
+    compile(rules).map(|_| ())
+}
+
+impl AutoApprover {
+    pub fn new(
+        rules: &[ApprovalRule],
+        sandbox: Option<SandboxMode>,
+        stdin_tx: mpsc::UnboundedSender<String>,
+    ) -> Result<Self, String> {
+        Ok(Self {
+            rules: compile(rules)?,
+            sandbox,
+            stdin_tx,
+        })
+    }
+
+    /// Why `command` must be refused without asking, if it must: a read-only
+    /// sandbox never lets a command write.
+    pub fn denial_reason(&self, command: &[String]) -> Option<String> {
+        (self.sandbox == Some(SandboxMode::ReadOnly) && is_mutating_command(command)).then(|| {
+            "The session's sandbox policy is read-only and this command modifies files".to_string()
+        })
+    }
+
+    /// Pattern of the allow rule that approves `command`, or `None` when the user
+    /// should be asked: nothing allows it, or a deny rule matches as well.
+    pub fn allowing_rule(&self, command: &str) -> Option<&str> {
+        let matching = || self.rules.iter().filter(|r| r.matcher.matches(command));
+        if matching().any(|r| r.action == ApprovalRuleAction::Deny) {
+            return None;
+        }
+        matching()
+            .find(|r| r.action == ApprovalRuleAction::Allow)
+            .map(|r| r.pattern.as_str())
+    }
+
+    pub fn approve_exec(&self, approval_id: &str) -> Result<()> {
+        self.answer_exec(approval_id, "approved")
+    }
+
+    pub fn deny_exec(&self, approval_id: &str) -> Result<()> {
+        self.answer_exec(approval_id, "denied")
+    }
+
+    fn answer_exec(&self, approval_id: &str, decision: &str) -> Result<()> {
+        let submission = Submission {
+            id: Uuid::new_v4().to_string(),
+            op: Op::ExecApproval {
+                id: approval_id.to_string(),
+                decision: decision.to_string(),
+            },
+        };
+        self.stdin_tx.send(serde_json::to_string(&submission)?)?;
+        Ok(())
+    }
+}
@@ -1,23 +1,47 @@
 use anyhow::Result;
 use serde_json;
-use tauri::AppHandle;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
 use uuid::Uuid;
 
-use crate::protocol::{CodexConfig, InputItem, Op, Submission};
+use crate::protocol::{CodexConfig, InputItem, Op, SandboxMode, Submission};
+use crate::services::codex::handle_process_exit;
+use crate::settings::load_settings;
+use crate::state::CodexState;
+use crate::utils::codex_discovery::discover_codex_command;
 
-use super::{CommandBuilder, EventHandler, ProcessManager};
+use super::{
+    AutoApprover, CapturedOutput, CommandBuilder, EventHandler, OutputBuffer, ProcessManager,
+    SharedOutputBuffer, WorkspaceWatch,
+};
+
+/// How long to wait for an exited process to be reaped before giving up on its code.
+const EXIT_WAIT_TIMEOUT: Duration = Duration::from_secs(2);
 
 pub struct CodexClient {
     #[allow(dead_code)]
     app: AppHandle,
     session_id: String,
     process_manager: ProcessManager,
-    #[allow(dead_code)]
     config: CodexConfig,
+    // When set, the stdout handler forwards every protocol line as `raw-codex-event`
+    raw_event_debug: Arc<AtomicBool>,
+    // Recent stdout/stderr lines kept for diagnostics
+    output: SharedOutputBuffer,
+    // Set once the session is closed or dropped, so its process exiting isn't a crash
+    closed: Arc<AtomicBool>,
 }
 
 impl CodexClient {
-    pub async fn new(app: &AppHandle, session_id: String, config: CodexConfig) -> Result<Self> {
+    pub async fn new(app: &AppHandle, session_id: String, mut config: CodexConfig) -> Result<Self> {
+        // Pin the discovered binary so the stored config says exactly what was launched
+        if config.codex_path.is_none() {
+            config.codex_path =
+                discover_codex_command().map(|path| path.to_string_lossy().to_string());
+        }
+
         log::debug!(
             "Creating CodexClient for session and config: {} {:?}",
             session_id,
@@ -25,18 +49,68 @@ impl CodexClient {
         );
 
         // Build the command and environment variables
-        let (cmd, env_vars) = CommandBuilder::build_command(&config).await?;
+        let codex_home = app
+            .state::<CodexState>()
+            .codex_home()
+            .map_err(|e| anyhow::anyhow!(e.to_string()))?;
+        let (cmd, env_vars) = CommandBuilder::build_command(&config, &codex_home).await?;
 
         // Start the process
         let mut process_manager = ProcessManager::start_process(cmd, env_vars, &config).await?;
 
+        let raw_event_debug = Arc::new(AtomicBool::new(false));
+        let closed = Arc::new(AtomicBool::new(false));
+        let settings = load_settings();
+        let output = OutputBuffer::shared(settings.output_buffer_lines);
+
+        let workspace_watch =
+            if settings.track_workspace_changes && !config.working_directory.is_empty() {
+                match WorkspaceWatch::start(std::path::Path::new(&config.working_directory)) {
+                    Ok(watch) => Some(Arc::new(watch)),
+                    Err(e) => {
+                        log::warn!("Failed to watch workspace for {}: {}", session_id, e);
+                        None
+                    }
+                }
+            } else {
+                None
+            };
+
+        let sandbox = config.effective_sandbox();
+        let needs_approver =
+            !config.auto_approve.is_empty() || sandbox == Some(SandboxMode::ReadOnly);
+        let auto_approver = match &process_manager.stdin_tx {
+            Some(stdin_tx) if needs_approver => Some(Arc::new(
+                AutoApprover::new(&config.auto_approve, sandbox, stdin_tx.clone())
+                    .map_err(anyhow::Error::msg)?,
+            )),
+            _ => None,
+        };
+
         // Set up event handlers for stdout and stderr
         if let Some(process) = &mut process_manager.process {
             let stdout = process.stdout.take().expect("Failed to open stdout");
             let stderr = process.stderr.take().expect("Failed to open stderr");
 
-            EventHandler::start_stdout_handler(app.clone(), stdout, session_id.clone());
-            EventHandler::start_stderr_handler(stderr, session_id.clone());
+            let stdout_task = EventHandler::start_stdout_handler(
+                app.clone(),
+                stdout,
+                session_id.clone(),
+                raw_event_debug.clone(),
+                output.clone(),
+                workspace_watch,
+                auto_approver,
+            );
+            EventHandler::start_stderr_handler(stderr, session_id.clone(), output.clone());
+
+            // Stdout reaching EOF means the process is gone; report it unless it was closed
+            let (app, session_id, closed) = (app.clone(), session_id.clone(), closed.clone());
+            tokio::spawn(async move {
+                let _ = stdout_task.await;
+                if !closed.load(Ordering::SeqCst) {
+                    handle_process_exit(app, session_id).await;
+                }
+            });
         }
 
         let client = Self {
@@ -44,6 +118,9 @@ impl CodexClient {
             session_id,
             process_manager,
             config: config.clone(),
+            raw_event_debug,
+            output,
+            closed,
         };
 
         Ok(client)
@@ -67,6 +144,29 @@ impl CodexClient {
         self.send_submission(submission).await
     }
 
+    /// Send one message to be answered by `model`, then switch back to the session's
+    /// model. Codex snapshots the turn context when input arrives, so the restore
+    /// queued right behind it only applies to later turns.
+    pub async fn send_user_input_with_model(&self, message: String, model: String) -> Result<()> {
+        if self.config.model.is_empty() {
+            anyhow::bail!("Session was started without an explicit model to switch back to");
+        }
+
+        self.send_submission(Submission {
+            id: Uuid::new_v4().to_string(),
+            op: Op::OverrideTurnContext { model: Some(model) },
+        })
+        .await?;
+        self.send_user_input(message).await?;
+        self.send_submission(Submission {
+            id: Uuid::new_v4().to_string(),
+            op: Op::OverrideTurnContext {
+                model: Some(self.config.model.clone()),
+            },
+        })
+        .await
+    }
+
     pub async fn send_exec_approval(&self, approval_id: String, approved: bool) -> Result<()> {
         let decision = if approved { "approved" } else { "denied" }.to_string();
 
@@ -130,6 +230,7 @@ impl CodexClient {
 
     pub async fn close_session(&mut self) -> Result<()> {
         log::debug!("Closing session: {}", self.session_id);
+        self.closed.store(true, Ordering::SeqCst);
 
         // Send shutdown command to codex (graceful shutdown)
         let submission = Submission {
@@ -148,6 +249,44 @@ impl CodexClient {
         Ok(())
     }
 
+    pub fn config(&self) -> &CodexConfig {
+        &self.config
+    }
+
+    pub fn set_raw_event_debug(&self, enabled: bool) {
+        self.raw_event_debug.store(enabled, Ordering::Relaxed);
+    }
+
+    /// Exit code of the codex process once it has ended, if it exited normally.
+    pub async fn exit_code(&mut self) -> Option<i32> {
+        self.process_manager.exit_code(EXIT_WAIT_TIMEOUT).await
+    }
+
+    /// The last `count` lines codex wrote to stderr.
+    pub fn stderr_tail(&self, count: usize) -> Vec<String> {
+        self.output
+            .lock()
+            .map(|output| output.stderr_tail(count))
+            .unwrap_or_default()
+    }
+
+    /// Everything still held in the stdout/stderr buffers.
+    pub fn captured_output(&self) -> CapturedOutput {
+        self.output
+            .lock()
+            .map(|output| output.snapshot())
+            .unwrap_or_else(|_| CapturedOutput {
+                stdout: Vec::new(),
+                stderr: Vec::new(),
+            })
+    }
+
+    pub fn clear_output(&self) {
+        if let Ok(mut output) = self.output.lock() {
+            output.clear();
+        }
+    }
+
     #[allow(dead_code)]
     pub async fn shutdown(&mut self) -> Result<()> {
         self.close_session().await
@@ -158,3 +297,10 @@ impl CodexClient {
         self.process_manager.is_active()
     }
 }
+
+// Dropping the client kills codex (kill_on_drop), which is a close, not a crash
+impl Drop for CodexClient {
+    fn drop(&mut self) {
+        self.closed.store(true, Ordering::SeqCst);
+    }
+}
@@ -1,16 +1,21 @@
 use anyhow::Result;
 use std::collections::HashMap;
+use std::path::Path;
 use tokio::process::Command;
 
-use crate::config::{read_model_providers, read_profiles};
+use crate::config::{load_model_providers, load_profiles};
 use crate::protocol::CodexConfig;
 use crate::utils::codex_discovery::discover_codex_command;
 
 pub struct CommandBuilder;
 
 impl CommandBuilder {
-    pub async fn build_command(config: &CodexConfig) -> Result<(Command, HashMap<String, String>)> {
-        log::debug!("Building codex command for config: {:?}", config);
+    /// `codex_home` is passed to codex as `CODEX_HOME` and is where config.toml is read from.
+    pub async fn build_command(
+        config: &CodexConfig,
+        codex_home: &Path,
+    ) -> Result<(Command, HashMap<String, String>)> {
+        log::debug!("Building codex command for config: {:?}", config.redacted());
 
         // Build codex command based on configuration
         let (command, args): (String, Vec<String>) =
@@ -29,10 +34,10 @@ impl CommandBuilder {
         cmd.arg("proto");
 
         // Build environment variables (includes PATH from user's shell)
-        let env_vars = Self::build_env_vars(config).await;
+        let env_vars = Self::build_env_vars(config, codex_home).await;
 
         // Configure provider settings
-        Self::configure_provider(&mut cmd, config).await?;
+        Self::configure_provider(&mut cmd, config, codex_home).await?;
 
         // Configure other settings
         Self::configure_settings(&mut cmd, config);
@@ -43,7 +48,7 @@ impl CommandBuilder {
         Ok((cmd, env_vars))
     }
 
-    async fn build_env_vars(config: &CodexConfig) -> HashMap<String, String> {
+    async fn build_env_vars(config: &CodexConfig, codex_home: &Path) -> HashMap<String, String> {
         let mut env_vars = HashMap::new();
 
         log::debug!(
@@ -57,7 +62,7 @@ impl CommandBuilder {
                 log::debug!("API key provided, length: {}", api_key.len());
 
                 // Try to get the env_key from provider configuration first
-                if let Ok(providers) = read_model_providers().await {
+                if let Ok(providers) = load_model_providers(codex_home) {
                     log::debug!(
                         "Successfully read providers, available: {:?}",
                         providers.keys().collect::<Vec<_>>()
@@ -119,6 +124,18 @@ impl CommandBuilder {
             }
         }
 
+        // Keep codex writing where codexia reads, including after `set_codex_home`
+        env_vars.insert(
+            "CODEX_HOME".to_string(),
+            codex_home.to_string_lossy().to_string(),
+        );
+
+        // Per-session variables go last so they win over everything above
+        if let Some(env) = &config.env {
+            log::debug!("Applying {} per-session environment variables", env.len());
+            env_vars.extend(env.iter().map(|(k, v)| (k.clone(), v.clone())));
+        }
+
         env_vars
     }
 
@@ -179,7 +196,11 @@ impl CommandBuilder {
         }
     }
 
-    async fn configure_provider(cmd: &mut Command, config: &CodexConfig) -> Result<()> {
+    async fn configure_provider(
+        cmd: &mut Command,
+        config: &CodexConfig,
+        codex_home: &Path,
+    ) -> Result<()> {
         // Handle provider configuration
         if !config.provider.is_empty() && config.provider != "openai" {
             // Special case for ollama - use model_provider=oss config instead of --oss flag
@@ -193,8 +214,8 @@ impl CommandBuilder {
                 }
             } else {
                 // For all other providers, try to load from config.toml first
-                if let Ok(providers) = read_model_providers().await {
-                    if let Ok(_profiles) = read_profiles().await {
+                if let Ok(providers) = load_model_providers(codex_home) {
+                    if let Ok(_profiles) = load_profiles(codex_home) {
                         // Check if there's a matching provider in config (try exact match first, then lowercase)
                         let _provider_config = providers
                             .get(&config.provider)
@@ -266,22 +287,17 @@ impl CommandBuilder {
                 .arg(format!("approval_policy={}", config.approval_policy));
         }
 
-        if !config.sandbox_mode.is_empty() {
-            let sandbox_config = match config.sandbox_mode.as_str() {
-                "read-only" => "sandbox_mode=read-only".to_string(),
-                "workspace-write" => "sandbox_mode=workspace-write".to_string(),
-                "danger-full-access" => "sandbox_mode=danger-full-access".to_string(),
-                _ => "sandbox_mode=workspace-write".to_string(),
-            };
-            cmd.arg("-c").arg(sandbox_config);
+        if let Some(sandbox) = config.effective_sandbox() {
+            cmd.arg("-c")
+                .arg(format!("sandbox_mode={}", sandbox.as_str()));
         }
 
         // Add reasoning effort parameter
-        if let Some(reasoning_effort) = &config.reasoning_effort {
-            if !reasoning_effort.is_empty() {
-                cmd.arg("-c")
-                    .arg(format!("model_reasoning_effort={}", reasoning_effort));
-            }
+        if let Some(reasoning_effort) = config.reasoning_effort {
+            cmd.arg("-c").arg(format!(
+                "model_reasoning_effort={}",
+                reasoning_effort.as_str()
+            ));
         }
 
         // Enable streaming by setting show_raw_agent_reasoning=true
@@ -295,6 +311,13 @@ impl CommandBuilder {
             cmd.arg("-c").arg("web_search_request=true");
         }
 
+        // Session-specific instructions; JSON string escaping is also a valid TOML basic string
+        if let Some(instructions) = &config.instructions {
+            if let Ok(quoted) = serde_json::to_string(instructions) {
+                cmd.arg("-c").arg(format!("instructions={}", quoted));
+            }
+        }
+
         // Resume from prior rollout file if provided
         if let Some(resume_path) = &config.resume_path {
             if !resume_path.is_empty() {
@@ -1,17 +1,89 @@
+use serde::Serialize;
 use serde_json;
-use tauri::{AppHandle, Emitter};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter, Manager, UserAttentionType};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::{ChildStderr, ChildStdout};
+use tokio::task::JoinHandle;
 
-use crate::protocol::Event;
+use super::auto_approve::{AutoApprovedPayload, AutoDeniedPayload};
+use super::workspace_watch::WorkspaceFilesModifiedPayload;
+use super::{AutoApprover, SharedOutputBuffer, WorkspaceWatch};
+use crate::protocol::{Event, EventMsg};
+use crate::services::approval_log::{self, ApprovalLogEntry};
+use crate::services::codex::send_next_queued;
+use crate::settings::load_settings;
+use crate::state::{CodexState, PendingApproval, PendingPatch};
+use crate::utils::pricing::estimate_cost_usd;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ApprovalRequestedPayload {
+    pub session_id: String,
+    pub approval_id: String,
+    pub kind: String,
+    pub summary: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct UsageUpdatedPayload {
+    pub session_id: String,
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub estimated_cost_usd: Option<f64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ApprovalTimedOutPayload {
+    pub session_id: String,
+    pub approval_id: String,
+    pub kind: String,
+    pub approved: bool,
+    pub timeout_secs: u64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AssistantDeltaPayload {
+    pub session_id: String,
+    pub turn_id: String,
+    /// Position of this delta within the turn, starting at 0.
+    pub seq: u64,
+    pub delta: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AssistantCompletePayload {
+    pub session_id: String,
+    pub turn_id: String,
+    pub message: String,
+}
+
+/// Assistant text streamed so far in one session's current turn. Each stdout
+/// handler owns its own, so sessions can't mix.
+#[derive(Default)]
+struct AssistantStream {
+    turn_id: String,
+    seq: u64,
+    text: String,
+}
 
 pub struct EventHandler;
 
 impl EventHandler {
-    pub fn start_stdout_handler(app: AppHandle, stdout: ChildStdout, session_id: String) {
+    pub fn start_stdout_handler(
+        app: AppHandle,
+        stdout: ChildStdout,
+        session_id: String,
+        raw_event_debug: Arc<AtomicBool>,
+        output: SharedOutputBuffer,
+        workspace_watch: Option<Arc<WorkspaceWatch>>,
+        auto_approver: Option<Arc<AutoApprover>>,
+    ) -> JoinHandle<()> {
         tokio::spawn(async move {
             let reader = BufReader::new(stdout);
             let mut lines = reader.lines();
+            let mut stream = AssistantStream::default();
 
             log::debug!("Starting stdout reader for session: {}", session_id);
 
@@ -20,6 +92,21 @@ impl EventHandler {
                     continue;
                 }
 
+                if let Ok(mut output) = output.lock() {
+                    output.push_stdout(line.clone());
+                }
+
+                // Opt-in diagnostic stream: forward the protocol line untouched
+                if raw_event_debug.load(Ordering::Relaxed) {
+                    let raw_event = serde_json::json!({
+                        "session_id": session_id,
+                        "line": line
+                    });
+                    if let Err(e) = app.emit("raw-codex-event", &raw_event) {
+                        log::error!("Failed to emit raw debug event: {}", e);
+                    }
+                }
+
                 // log::debug!("📥 Received line from codex: {}", line);
 
                 // Try to parse as the structured Event format first
@@ -31,6 +118,19 @@ impl EventHandler {
                         log::debug!("Event for session: {}", event_session_id);
                     }
 
+                    let auto_approved = match &auto_approver {
+                        Some(approver) => Self::auto_approve(&app, &session_id, &event, approver),
+                        None => false,
+                    };
+                    if !auto_approved {
+                        Self::notify_approval_request(&app, &session_id, &event).await;
+                    }
+                    Self::track_turn(&app, &session_id, &event).await;
+                    Self::forward_assistant_stream(&app, &session_id, &event, &mut stream);
+                    if let Some(watch) = &workspace_watch {
+                        Self::track_workspace_changes(&app, &session_id, &event, watch);
+                    }
+
                     // Emit structured event with attached session_id for routing in the UI
                     let wrapped = serde_json::json!({
                         "id": event.id,
@@ -79,10 +179,14 @@ impl EventHandler {
                 }
             }
             log::debug!("Stdout reader terminated for session: {}", session_id);
-        });
+        })
     }
 
-    pub fn start_stderr_handler(stderr: ChildStderr, session_id: String) {
+    pub fn start_stderr_handler(
+        stderr: ChildStderr,
+        session_id: String,
+        output: SharedOutputBuffer,
+    ) {
         tokio::spawn(async move {
             let reader = BufReader::new(stderr);
             let mut lines = reader.lines();
@@ -94,6 +198,10 @@ impl EventHandler {
                     continue;
                 }
 
+                if let Ok(mut output) = output.lock() {
+                    output.push_stderr(line.clone());
+                }
+
                 // Check if this is an informational log from codex (not an actual error)
                 if Self::is_informational_log(&line) {
                     log::debug!("📋 Codex info [{}]: {}", session_id, line);
@@ -119,6 +227,434 @@ impl EventHandler {
         line.starts_with("20") && line.contains("Z  TRACE")
     }
 
+    /// Keep the session's activity time, turn counters and token totals in
+    /// `CodexState` in step with events, emitting `usage-updated` as each turn ends
+    /// and then sending the next queued message, if any. An aborted turn drains
+    /// the queue too, unless the session was paused.
+    async fn track_turn(app: &AppHandle, session_id: &str, event: &Event) {
+        let Some(state) = app.try_state::<CodexState>() else {
+            return;
+        };
+        let mut runtimes = state.runtimes.lock().await;
+        let Some(runtime) = runtimes.get_mut(session_id) else {
+            return;
+        };
+        runtime.last_activity_at = chrono::Utc::now().timestamp_millis();
+        let mut turn_ended = false;
+
+        match &event.msg {
+            EventMsg::TaskStarted => {
+                runtime.turn_started_at = Some(chrono::Utc::now().timestamp_millis());
+                runtime.paused = false;
+                runtime.awaiting_turn = false;
+            }
+            // Both can arrive for the same turn; only count it once
+            EventMsg::TaskComplete { .. } | EventMsg::TurnComplete { .. }
+                if runtime.turn_started_at.is_some() =>
+            {
+                runtime.turn_started_at = None;
+                runtime.awaiting_turn = false;
+                runtime.turns_completed += 1;
+                turn_ended = true;
+
+                let usage = runtime.usage;
+                let payload = UsageUpdatedPayload {
+                    session_id: session_id.to_string(),
+                    input_tokens: usage.input_tokens,
+                    cached_input_tokens: usage.cached_input_tokens,
+                    output_tokens: usage.output_tokens,
+                    estimated_cost_usd: estimate_cost_usd(
+                        &runtime.model,
+                        usage.input_tokens,
+                        usage.cached_input_tokens,
+                        usage.output_tokens,
+                    ),
+                };
+                if let Err(e) = app.emit("usage-updated", &payload) {
+                    log::error!("Failed to emit usage-updated event: {}", e);
+                }
+            }
+            // An interrupted turn ends without completing; it isn't counted
+            EventMsg::TurnAborted { .. } => {
+                runtime.turn_started_at = None;
+                runtime.awaiting_turn = false;
+                turn_ended = true;
+            }
+            // A failed submission may never start a turn; don't hold the queue for it
+            EventMsg::Error { .. } => {
+                runtime.awaiting_turn = false;
+            }
+            EventMsg::TokenCount {
+                input_tokens,
+                output_tokens,
+                cached_input_tokens,
+                reasoning_output_tokens,
+                ..
+            } => {
+                let usage = &mut runtime.usage;
+                usage.input_tokens += input_tokens.unwrap_or(0);
+                usage.cached_input_tokens += cached_input_tokens.unwrap_or(0);
+                usage.output_tokens += output_tokens.unwrap_or(0);
+                usage.reasoning_output_tokens += reasoning_output_tokens.unwrap_or(0);
+            }
+            _ => {}
+        }
+
+        drop(runtimes);
+        // `send_next_queued` holds the queue of a paused session
+        if turn_ended {
+            send_next_queued(app, session_id).await;
+        }
+    }
+
+    /// Emit `assistant-delta` for each chunk of assistant text and `assistant-complete`
+    /// with the whole message once it's done, so the UI can render while it streams
+    /// and then reconcile against the final text.
+    fn forward_assistant_stream(
+        app: &AppHandle,
+        session_id: &str,
+        event: &Event,
+        stream: &mut AssistantStream,
+    ) {
+        if stream.turn_id != event.id {
+            *stream = AssistantStream {
+                turn_id: event.id.clone(),
+                ..Default::default()
+            };
+        }
+
+        let message = match &event.msg {
+            EventMsg::AgentMessageDelta { delta } => {
+                stream.text.push_str(delta);
+                let payload = AssistantDeltaPayload {
+                    session_id: session_id.to_string(),
+                    turn_id: event.id.clone(),
+                    seq: stream.seq,
+                    delta: delta.clone(),
+                };
+                stream.seq += 1;
+                if let Err(e) = app.emit("assistant-delta", &payload) {
+                    log::error!("Failed to emit assistant-delta event: {}", e);
+                }
+                return;
+            }
+            EventMsg::AgentMessage {
+                message,
+                last_agent_message,
+            } => message
+                .clone()
+                .or_else(|| last_agent_message.clone())
+                .unwrap_or_else(|| std::mem::take(&mut stream.text)),
+            // A turn that ends mid-stream still needs closing off
+            EventMsg::TaskComplete { .. }
+            | EventMsg::TurnComplete { .. }
+            | EventMsg::TurnAborted { .. }
+                if !stream.text.is_empty() =>
+            {
+                std::mem::take(&mut stream.text)
+            }
+            _ => return,
+        };
+        stream.text.clear();
+
+        let payload = AssistantCompletePayload {
+            session_id: session_id.to_string(),
+            turn_id: event.id.clone(),
+            message,
+        };
+        if let Err(e) = app.emit("assistant-complete", &payload) {
+            log::error!("Failed to emit assistant-complete event: {}", e);
+        }
+    }
+
+    /// Emit `workspace-file-modified` at the end of each turn that changed files in the workspace.
+    fn track_workspace_changes(
+        app: &AppHandle,
+        session_id: &str,
+        event: &Event,
+        watch: &WorkspaceWatch,
+    ) {
+        match &event.msg {
+            EventMsg::TaskStarted => watch.begin_turn(),
+            EventMsg::PatchApplyBegin { changes, .. } => watch.record_patch(changes),
+            EventMsg::TaskComplete { .. }
+            | EventMsg::TurnComplete { .. }
+            | EventMsg::TurnAborted { .. } => {
+                let files = watch.end_turn();
+                if files.is_empty() {
+                    return;
+                }
+                let unapproved_count = files.iter().filter(|f| !f.approved).count();
+                if unapproved_count > 0 {
+                    log::warn!(
+                        "Session {} changed {} file(s) without an approved patch",
+                        session_id,
+                        unapproved_count
+                    );
+                }
+                let payload = WorkspaceFilesModifiedPayload {
+                    session_id: session_id.to_string(),
+                    files,
+                    unapproved_count,
+                };
+                if let Err(e) = app.emit("workspace-file-modified", &payload) {
+                    log::error!("Failed to emit workspace-file-modified event: {}", e);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn log_auto_decision(
+        session_id: &str,
+        approval_id: &str,
+        kind: &str,
+        decision: &str,
+        summary: &str,
+    ) {
+        let entry = ApprovalLogEntry {
+            timestamp: chrono::Utc::now().to_rfc3339(),
+            session_id: session_id.to_string(),
+            approval_id: approval_id.to_string(),
+            kind: kind.to_string(),
+            decision: decision.to_string(),
+            auto: true,
+            summary: summary.to_string(),
+        };
+        if let Err(e) = approval_log::append_entry(&entry) {
+            log::warn!("Failed to record approval decision: {}", e);
+        }
+    }
+
+    /// Answer an exec approval request without asking: deny writes under a
+    /// read-only sandbox (emitting `auto-denied`), or approve what an allow rule
+    /// covers (emitting `auto-approved`). Returns false when the user still has to be asked.
+    fn auto_approve(
+        app: &AppHandle,
+        session_id: &str,
+        event: &Event,
+        approver: &AutoApprover,
+    ) -> bool {
+        let EventMsg::ExecApprovalRequest { command: argv, .. } = &event.msg else {
+            return false;
+        };
+        // Read-only mode refuses approvals, so leave it to the manual path
+        if app
+            .try_state::<CodexState>()
+            .is_some_and(|state| state.is_read_only())
+        {
+            return false;
+        }
+        let command = argv.join(" ");
+
+        if let Some(reason) = approver.denial_reason(argv) {
+            if let Err(e) = approver.deny_exec(&event.id) {
+                log::error!("Failed to auto-deny '{}': {}", command, e);
+                return false;
+            }
+            log::info!(
+                "Auto-denied '{}' in session {}: {}",
+                command,
+                session_id,
+                reason
+            );
+            Self::log_auto_decision(session_id, &event.id, "exec", "denied", &command);
+
+            let payload = AutoDeniedPayload {
+                session_id: session_id.to_string(),
+                approval_id: event.id.clone(),
+                command,
+                reason,
+            };
+            if let Err(e) = app.emit("auto-denied", &payload) {
+                log::error!("Failed to emit auto-denied event: {}", e);
+            }
+            return true;
+        }
+
+        let Some(rule) = approver.allowing_rule(&command) else {
+            return false;
+        };
+        if let Err(e) = approver.approve_exec(&event.id) {
+            log::error!("Failed to auto-approve '{}': {}", command, e);
+            return false;
+        }
+        log::info!(
+            "Auto-approved '{}' in session {} (rule '{}')",
+            command,
+            session_id,
+            rule
+        );
+        Self::log_auto_decision(session_id, &event.id, "exec", "approved", &command);
+
+        let payload = AutoApprovedPayload {
+            session_id: session_id.to_string(),
+            approval_id: event.id.clone(),
+            command,
+            rule: rule.to_string(),
+        };
+        if let Err(e) = app.emit("auto-approved", &payload) {
+            log::error!("Failed to emit auto-approved event: {}", e);
+        }
+        true
+    }
+
+    /// Emit `approval-requested` for approval events and, if enabled in settings,
+    /// ask the OS to draw attention to the window so a backgrounded app isn't missed.
+    async fn notify_approval_request(app: &AppHandle, session_id: &str, event: &Event) {
+        let mut patch = None;
+        let (kind, summary) = match &event.msg {
+            EventMsg::ExecApprovalRequest { command, .. } => ("exec", command.join(" ")),
+            EventMsg::PatchApprovalRequest { patch: text, files } => {
+                patch = Some(PendingPatch::Text {
+                    patch: text.clone(),
+                    files: files.clone(),
+                });
+                ("patch", files.join(", "))
+            }
+            EventMsg::ApplyPatchApprovalRequest {
+                changes, reason, ..
+            } => {
+                let files = changes
+                    .as_object()
+                    .map(|map| map.keys().cloned().collect::<Vec<_>>().join(", "))
+                    .unwrap_or_default();
+                let summary = match reason {
+                    Some(reason) => format!("{} ({})", reason, files),
+                    None => files,
+                };
+                patch = Some(PendingPatch::Changes(changes.clone()));
+                ("patch", summary)
+            }
+            _ => return,
+        };
+
+        // Remembered so the decision can be written to the approval log with its summary
+        if let Some(state) = app.try_state::<CodexState>() {
+            let timeout_config = state.sessions.lock().await.get(session_id).and_then(|c| {
+                let config = c.config();
+                config
+                    .approval_timeout_secs
+                    .filter(|secs| *secs > 0)
+                    .map(|secs| (secs, config.default_on_timeout))
+            });
+            let timeout = timeout_config.map(|(secs, approve)| {
+                tokio::spawn(Self::expire_approval(
+                    app.clone(),
+                    session_id.to_string(),
+                    event.id.clone(),
+                    secs,
+                    approve,
+                ))
+                .abort_handle()
+            });
+            state.pending_approvals.lock().await.insert(
+                event.id.clone(),
+                PendingApproval {
+                    session_id: session_id.to_string(),
+                    kind: kind.to_string(),
+                    summary: summary.clone(),
+                    requested_at: chrono::Utc::now().timestamp_millis(),
+                    timeout,
+                    patch,
+                },
+            );
+        }
+
+        let payload = ApprovalRequestedPayload {
+            session_id: session_id.to_string(),
+            approval_id: event.id.clone(),
+            kind: kind.to_string(),
+            summary,
+        };
+        if let Err(e) = app.emit("approval-requested", &payload) {
+            log::error!("Failed to emit approval-requested event: {}", e);
+        }
+
+        if load_settings().focus_on_approval {
+            if let Some(window) = app.get_webview_window("main") {
+                if let Err(e) =
+                    window.request_user_attention(Some(UserAttentionType::Informational))
+                {
+                    log::warn!("Failed to request window attention: {}", e);
+                }
+            }
+        }
+    }
+
+    /// Answer `approval_id` with the session's `default_on_timeout` once `secs`
+    /// pass, unless it was answered first (which aborts this task), and emit
+    /// `approval-timed-out`.
+    async fn expire_approval(
+        app: AppHandle,
+        session_id: String,
+        approval_id: String,
+        secs: u64,
+        approve: bool,
+    ) {
+        tokio::time::sleep(std::time::Duration::from_secs(secs)).await;
+        let Some(state) = app.try_state::<CodexState>() else {
+            return;
+        };
+        // Read-only mode refuses approvals, so leave it waiting for the user
+        if state.is_read_only() {
+            return;
+        }
+        let Some(mut pending) = state.pending_approvals.lock().await.remove(&approval_id) else {
+            return;
+        };
+        // This task is the timeout; dropping the entry must not abort it
+        pending.timeout.take();
+
+        let result = {
+            let sessions = state.sessions.lock().await;
+            match sessions.get(&session_id) {
+                Some(client) if pending.kind == "exec" => {
+                    client
+                        .send_exec_approval(approval_id.clone(), approve)
+                        .await
+                }
+                Some(client) => {
+                    client
+                        .send_apply_patch_approval(approval_id.clone(), approve)
+                        .await
+                }
+                None => return,
+            }
+        };
+        if let Err(e) = result {
+            log::error!("Failed to answer timed-out approval {}: {}", approval_id, e);
+            return;
+        }
+
+        let decision = if approve { "approved" } else { "denied" };
+        log::info!(
+            "Approval {} in session {} timed out after {}s, {}",
+            approval_id,
+            session_id,
+            secs,
+            decision
+        );
+        Self::log_auto_decision(
+            &session_id,
+            &approval_id,
+            &pending.kind,
+            decision,
+            &pending.summary,
+        );
+
+        let payload = ApprovalTimedOutPayload {
+            session_id,
+            approval_id,
+            kind: pending.kind.clone(),
+            approved: approve,
+            timeout_secs: secs,
+        };
+        if let Err(e) = app.emit("approval-timed-out", &payload) {
+            log::error!("Failed to emit approval-timed-out event: {}", e);
+        }
+    }
+
     fn get_session_id_from_event(event: &Event) -> Option<String> {
         match &event.msg {
             crate::protocol::EventMsg::SessionConfigured { session_id, .. } => {
@@ -1,9 +1,15 @@
+pub mod auto_approve;
 pub mod client;
 pub mod command_builder;
 pub mod event_handler;
+pub mod output_buffer;
 pub mod process_manager;
+pub mod workspace_watch;
 
+pub use auto_approve::AutoApprover;
 pub use client::CodexClient;
 pub use command_builder::CommandBuilder;
 pub use event_handler::EventHandler;
+pub use output_buffer::{CapturedOutput, OutputBuffer, SharedOutputBuffer};
 pub use process_manager::ProcessManager;
+pub use workspace_watch::WorkspaceWatch;
@@ -0,0 +1,67 @@
+use serde::Serialize;
+use std::collections::VecDeque;
+use std::sync::{Arc, Mutex};
+
+/// Bounded capture of a codex process's stdout/stderr lines, oldest dropped first.
+#[derive(Debug)]
+pub struct OutputBuffer {
+    capacity: usize,
+    stdout: VecDeque<String>,
+    stderr: VecDeque<String>,
+}
+
+pub type SharedOutputBuffer = Arc<Mutex<OutputBuffer>>;
+
+/// Copy of the captured lines, oldest first, as returned by `get_session_output`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CapturedOutput {
+    pub stdout: Vec<String>,
+    pub stderr: Vec<String>,
+}
+
+impl OutputBuffer {
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            stdout: VecDeque::new(),
+            stderr: VecDeque::new(),
+        }
+    }
+
+    pub fn shared(capacity: usize) -> SharedOutputBuffer {
+        Arc::new(Mutex::new(Self::new(capacity)))
+    }
+
+    pub fn push_stdout(&mut self, line: String) {
+        Self::push(&mut self.stdout, self.capacity, line);
+    }
+
+    pub fn push_stderr(&mut self, line: String) {
+        Self::push(&mut self.stderr, self.capacity, line);
+    }
+
+    /// The last `count` stderr lines, oldest first.
+    pub fn stderr_tail(&self, count: usize) -> Vec<String> {
+        let skip = self.stderr.len().saturating_sub(count);
+        self.stderr.iter().skip(skip).cloned().collect()
+    }
+
+    pub fn snapshot(&self) -> CapturedOutput {
+        CapturedOutput {
+            stdout: self.stdout.iter().cloned().collect(),
+            stderr: self.stderr.iter().cloned().collect(),
+        }
+    }
+
+    pub fn clear(&mut self) {
+        self.stdout.clear();
+        self.stderr.clear();
+    }
+
+    fn push(lines: &mut VecDeque<String>, capacity: usize, line: String) {
+        if lines.len() >= capacity {
+            lines.pop_front();
+        }
+        lines.push_back(line);
+    }
+}
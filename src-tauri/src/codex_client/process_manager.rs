@@ -1,6 +1,7 @@
 use anyhow::Result;
 use std::collections::HashMap;
 use std::process::Stdio;
+use std::time::Duration;
 use tokio::io::AsyncWriteExt;
 use tokio::process::{Child, Command};
 use tokio::sync::mpsc;
@@ -29,6 +30,8 @@ impl ProcessManager {
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .current_dir(&config.working_directory)
+            // Never leave codex running if the handle is dropped without terminate()
+            .kill_on_drop(true)
             .spawn()?;
 
         // Give the process a moment to start up and check if it's still running
@@ -113,6 +116,20 @@ impl ProcessManager {
         Ok(())
     }
 
+    /// Exit code of a process that has ended, waiting up to `timeout` for it to be
+    /// reaped. `None` if it is still running or was killed by a signal.
+    pub async fn exit_code(&mut self, timeout: Duration) -> Option<i32> {
+        let process = self.process.as_mut()?;
+        match tokio::time::timeout(timeout, process.wait()).await {
+            Ok(Ok(status)) => status.code(),
+            Ok(Err(e)) => {
+                log::error!("Error checking process status: {}", e);
+                None
+            }
+            Err(_) => None,
+        }
+    }
+
     pub fn is_active(&self) -> bool {
         self.process.is_some() && self.stdin_tx.is_some()
     }
@@ -0,0 +1,109 @@
+use notify::{Event, EventKind, RecursiveMode};
+use serde::Serialize;
+use std::collections::HashSet;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, Mutex};
+
+use crate::utils::watcher::{create_watcher, BoxedWatcher};
+
+#[derive(Serialize, Debug, Clone)]
+pub struct WorkspaceFileChange {
+    pub path: String,
+    /// Covered by a patch codex applied this turn; `false` means the file changed some other way.
+    pub approved: bool,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct WorkspaceFilesModifiedPayload {
+    pub session_id: String,
+    pub files: Vec<WorkspaceFileChange>,
+    pub unapproved_count: usize,
+}
+
+/// Records which files under a session's working directory change during a turn,
+/// and which of those were announced through `patch_apply_begin`.
+pub struct WorkspaceWatch {
+    _watcher: BoxedWatcher,
+    root: PathBuf,
+    changed: Arc<Mutex<HashSet<PathBuf>>>,
+    patched: Mutex<HashSet<PathBuf>>,
+}
+
+fn is_git_internal(path: &Path) -> bool {
+    path.components()
+        .any(|c| matches!(c, Component::Normal(name) if name == ".git"))
+}
+
+impl WorkspaceWatch {
+    pub fn start(root: &Path) -> notify::Result<Self> {
+        let root = root.canonicalize().unwrap_or_else(|_| root.to_path_buf());
+        let changed: Arc<Mutex<HashSet<PathBuf>>> = Arc::new(Mutex::new(HashSet::new()));
+
+        let changed_for_cb = changed.clone();
+        let mut watcher = create_watcher(move |res: Result<Event, notify::Error>| {
+            let Ok(event) = res else {
+                return;
+            };
+            if !matches!(
+                event.kind,
+                EventKind::Create(_) | EventKind::Modify(_) | EventKind::Remove(_)
+            ) {
+                return;
+            }
+            if let Ok(mut changed) = changed_for_cb.lock() {
+                changed.extend(event.paths.into_iter().filter(|p| !is_git_internal(p)));
+            }
+        })?;
+        watcher.watch(&root, RecursiveMode::Recursive)?;
+
+        Ok(Self {
+            _watcher: watcher,
+            root,
+            changed,
+            patched: Mutex::new(HashSet::new()),
+        })
+    }
+
+    /// Forget anything recorded before the turn that is starting now.
+    pub fn begin_turn(&self) {
+        if let Ok(mut changed) = self.changed.lock() {
+            changed.clear();
+        }
+        if let Ok(mut patched) = self.patched.lock() {
+            patched.clear();
+        }
+    }
+
+    /// Note the files a patch touches; `changes` is keyed by path, relative or absolute.
+    pub fn record_patch(&self, changes: &serde_json::Value) {
+        let Some(files) = changes.as_object() else {
+            return;
+        };
+        if let Ok(mut patched) = self.patched.lock() {
+            patched.extend(files.keys().map(|file| self.root.join(file)));
+        }
+    }
+
+    /// Drain the files changed during the turn, sorted by path.
+    pub fn end_turn(&self) -> Vec<WorkspaceFileChange> {
+        let changed: Vec<PathBuf> = match self.changed.lock() {
+            Ok(mut changed) => changed.drain().collect(),
+            Err(_) => return Vec::new(),
+        };
+        let patched = self
+            .patched
+            .lock()
+            .map(|mut p| std::mem::take(&mut *p))
+            .unwrap_or_default();
+
+        let mut files: Vec<WorkspaceFileChange> = changed
+            .into_iter()
+            .map(|path| WorkspaceFileChange {
+                approved: patched.contains(&path),
+                path: path.to_string_lossy().to_string(),
+            })
+            .collect();
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        files
+    }
+}
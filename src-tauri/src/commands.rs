@@ -1,16 +1,300 @@
+use crate::codex_client::CapturedOutput;
+use crate::filesystem::file_io::{
+    resolve_allowed_write_target, resolve_readable_file, resolve_writable_text_file,
+};
+use crate::filesystem::watch::repoint_sessions_watch;
 use crate::protocol::CodexConfig;
-use crate::services::{codex, session};
-use crate::state::CodexState;
-use crate::utils::file::{get_sessions_path, scan_jsonl_files};
-use std::fs;
+use crate::services::{
+    approval_log, archive, auth, codex, diagnostics, export, history, models, preflight, search,
+    session, session_meta, shell, stats,
+};
+use crate::settings::load_settings;
+use crate::state::{CodexState, SessionStatus};
+use crate::utils::codex_discovery::{self, locate_codex_candidates};
+use crate::utils::file::{probe_writable, read_to_string_with_retry, user_home};
+use std::sync::atomic::Ordering;
 use tauri::{AppHandle, State};
 
 // Re-export types for external use
-pub use crate::services::session::Conversation;
+pub use crate::services::approval_log::ApprovalLogEntry;
+pub use crate::services::archive::{ArchiveSummary, ArchivedSession};
+pub use crate::services::auth::CodexAuthStatus;
+pub use crate::services::codex::{
+    CodexBinaryInfo, CodexError, PendingApprovalInfo, SessionRuntimeInfo, SessionUsage,
+};
+pub use crate::services::diagnostics::DiagnosticsReport;
+pub use crate::services::export::MarkdownExportOptions;
+pub use crate::services::history::HistoryEntry;
+pub use crate::services::models::AvailableModels;
+pub use crate::services::patch::PatchPreview;
+pub use crate::services::preflight::PreflightReport;
+pub use crate::services::search::{
+    MessageMatch, SearchOptions, SessionMatch, SessionSearchOptions,
+};
+pub use crate::services::session::{
+    BulkDeleteOptions, BulkDeleteResult, Conversation, DuplicateSessionGroup, ParsedSession,
+    SessionMetadata, SessionPage, SessionSort, SessionValidationReport, TruncateResult,
+};
+pub use crate::services::session_meta::MetadataRepairReport;
+pub use crate::services::stats::{GlobalStats, SessionStats};
+pub use crate::utils::codex_discovery::CodexCandidate;
 
+/// Toggle read-only mode. While on, starting sessions, sending messages,
+/// approvals and file or session-store writes are refused.
 #[tauri::command]
-pub async fn load_sessions_from_disk() -> Result<Vec<Conversation>, String> {
-    session::load_sessions_from_disk().await
+pub async fn set_read_only(state: State<'_, CodexState>, enabled: bool) -> Result<(), String> {
+    state.read_only.store(enabled, Ordering::SeqCst);
+    log::info!(
+        "Read-only mode {}",
+        if enabled { "enabled" } else { "disabled" }
+    );
+    Ok(())
+}
+
+/// Cap how many codex processes run at once; `None` or 0 lifts the cap. Over
+/// the cap, `start_codex_session` fails with `TooManySessions`, or with `queue`
+/// waits until a running session ends. Sessions already running are kept.
+#[tauri::command]
+pub async fn set_session_limit(
+    state: State<'_, CodexState>,
+    max_sessions: Option<usize>,
+    queue: bool,
+) -> Result<(), String> {
+    state
+        .max_concurrent_sessions
+        .store(max_sessions.unwrap_or(0), Ordering::SeqCst);
+    state.queue_over_limit.store(queue, Ordering::SeqCst);
+    // A raised limit may have room for starts already waiting
+    state.release_session_slot();
+    log::info!(
+        "Session limit set to {:?} ({})",
+        max_sessions,
+        if queue { "queueing" } else { "rejecting" }
+    );
+    Ok(())
+}
+
+/// Use `path` as the codex home (sessions, history, config) instead of
+/// `CODEX_HOME` or `~/.codex`, until restart; `None` or empty goes back to the
+/// default. The directory must exist and be writable. Sessions started
+/// afterwards get it as `CODEX_HOME`; running sessions keep their home.
+/// Codexia's own settings, session metadata, index and approval log stay in
+/// the default home. Returns the codex home now in effect.
+#[tauri::command]
+pub async fn set_codex_home(
+    state: State<'_, CodexState>,
+    path: Option<String>,
+) -> Result<String, String> {
+    let Some(path) = path.filter(|p| !p.trim().is_empty()) else {
+        state.set_codex_home_override(None);
+        log::info!("Codex home override cleared");
+        return Ok(state.codex_home()?.to_string_lossy().to_string());
+    };
+
+    let expanded = match path.strip_prefix("~/") {
+        Some(rest) => user_home()?.join(rest),
+        None => std::path::PathBuf::from(&path),
+    };
+    let resolved = match std::fs::canonicalize(&expanded) {
+        Ok(resolved) if resolved.is_dir() => resolved,
+        _ => {
+            return Err(format!(
+                "Codex home '{}' is not an existing directory",
+                path
+            ))
+        }
+    };
+    probe_writable(&resolved)
+        .map_err(|e| format!("Codex home '{}' is not writable: {}", path, e))?;
+
+    state.set_codex_home_override(Some(resolved.clone()));
+    log::info!("Codex home set to {}", resolved.display());
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+/// Confine `read_file`, `write_file` and friends to `roots` (and everything below
+/// them). An empty list lifts the restriction. Returns the canonical roots.
+#[tauri::command]
+pub async fn set_allowed_roots(
+    state: State<'_, CodexState>,
+    roots: Vec<String>,
+) -> Result<Vec<String>, String> {
+    let mut resolved = Vec::with_capacity(roots.len());
+    for root in &roots {
+        let path = match root.strip_prefix("~/") {
+            Some(rest) => user_home()?.join(rest),
+            None => std::path::PathBuf::from(root),
+        };
+        match std::fs::canonicalize(&path) {
+            Ok(path) if path.is_dir() => resolved.push(path),
+            _ => {
+                return Err(format!(
+                    "Allowed root '{}' is not an existing directory",
+                    root
+                ))
+            }
+        }
+    }
+
+    log::info!("Allowed roots set to {:?}", resolved);
+    let canonical = resolved
+        .iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect();
+    *state
+        .allowed_roots
+        .write()
+        .map_err(|_| "Allowed roots lock poisoned".to_string())? = resolved;
+    Ok(canonical)
+}
+
+#[tauri::command]
+pub async fn load_sessions_from_disk(
+    state: State<'_, CodexState>,
+    sort_by: Option<SessionSort>,
+    request_id: Option<String>,
+) -> Result<Vec<Conversation>, String> {
+    session::load_sessions_from_disk(&state.codex_home()?, sort_by, request_id).await
+}
+
+/// A session with its messages; list entries are summaries without them.
+#[tauri::command]
+pub async fn load_session(
+    state: State<'_, CodexState>,
+    file_path: String,
+) -> Result<Conversation, String> {
+    session::load_session(&state.sessions_path()?, file_path).await
+}
+
+/// Drop the cached session index and parse every rollout again. Returns how
+/// many sessions were indexed.
+#[tauri::command]
+pub async fn rebuild_session_index(
+    state: State<'_, CodexState>,
+    request_id: Option<String>,
+) -> Result<usize, String> {
+    session::rebuild_session_index(&state.codex_home()?, request_id).await
+}
+
+/// One page of the session list plus the total number of rollout files.
+#[tauri::command]
+pub async fn load_sessions_page(
+    state: State<'_, CodexState>,
+    offset: usize,
+    limit: usize,
+    sort_by: Option<SessionSort>,
+    request_id: Option<String>,
+) -> Result<SessionPage, String> {
+    session::load_sessions_page(&state.codex_home()?, offset, limit, sort_by, request_id).await
+}
+
+/// Cancel the session scans started with `request_id`; they fail with
+/// "Session load cancelled". Returns whether any were still running.
+#[tauri::command]
+pub async fn cancel_session_load(request_id: String) -> Result<bool, String> {
+    Ok(session::cancel_session_load(&request_id))
+}
+
+#[tauri::command]
+pub async fn relocate_sessions_store(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    new_base: String,
+) -> Result<usize, String> {
+    state.ensure_writable()?;
+    if !state.sessions.lock().await.is_empty() {
+        return Err("Close all running sessions before relocating the sessions store".to_string());
+    }
+
+    let codex_home = state.codex_home()?;
+    let moved = tokio::task::spawn_blocking(move || {
+        session::relocate_sessions_store(&codex_home, &new_base)
+    })
+    .await
+    .map_err(|e| format!("Relocation failed: {}", e))??;
+    repoint_sessions_watch(app, &state).await?;
+    Ok(moved)
+}
+
+/// Messages containing `query` across every rollout, newest sessions first.
+#[tauri::command]
+pub async fn search_sessions(
+    state: State<'_, CodexState>,
+    query: String,
+    options: Option<SearchOptions>,
+) -> Result<Vec<SessionMatch>, String> {
+    search::search_sessions(&state.sessions_path()?, query, options.unwrap_or_default()).await
+}
+
+/// Hits of `query` in one session's messages, with character offsets for highlighting.
+#[tauri::command]
+pub async fn search_in_session(
+    file_path: String,
+    query: String,
+    options: Option<SessionSearchOptions>,
+) -> Result<Vec<MessageMatch>, String> {
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || search::search_in_session(&file_path, &query, &options))
+        .await
+        .map_err(|e| format!("Search failed: {}", e))?
+}
+
+/// Message, command, patch and token counts plus duration for one rollout.
+#[tauri::command]
+pub async fn get_session_stats(file_path: String) -> Result<SessionStats, String> {
+    let path = resolve_readable_file(&file_path)?;
+    tokio::task::spawn_blocking(move || stats::compute_session_stats(&path))
+        .await
+        .map_err(|e| format!("Stats task failed: {}", e))?
+}
+
+/// `get_session_stats` summed over every session, with per-day totals.
+#[tauri::command]
+pub async fn get_global_stats(state: State<'_, CodexState>) -> Result<GlobalStats, String> {
+    let sessions_path = state.sessions_path()?;
+    tokio::task::spawn_blocking(move || stats::compute_global_stats(&sessions_path))
+        .await
+        .map_err(|e| format!("Stats task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn list_sessions_for_cwd(
+    state: State<'_, CodexState>,
+    cwd: String,
+    request_id: Option<String>,
+) -> Result<Vec<Conversation>, String> {
+    session::list_sessions_for_cwd(&state.codex_home()?, cwd, request_id).await
+}
+
+#[tauri::command]
+pub async fn set_session_tags(
+    state: State<'_, CodexState>,
+    session_id: String,
+    tags: Vec<String>,
+) -> Result<Vec<String>, String> {
+    state.ensure_writable()?;
+    session_meta::set_session_tags(&session_id, tags)
+}
+
+/// Set a custom title for the session in `file_path` (a rollout path or session id).
+/// Stored beside the rollouts, so the JSONL itself is never rewritten.
+#[tauri::command]
+pub async fn rename_session(
+    state: State<'_, CodexState>,
+    file_path: String,
+    new_title: String,
+) -> Result<Option<String>, String> {
+    state.ensure_writable()?;
+    session_meta::set_session_title(&file_path, &new_title)
+}
+
+/// Sessions carrying any of `tags`.
+#[tauri::command]
+pub async fn list_sessions_by_tag(
+    state: State<'_, CodexState>,
+    tags: Vec<String>,
+) -> Result<Vec<Conversation>, String> {
+    session::list_sessions_by_tag(&state.sessions_path()?, tags).await
 }
 
 #[tauri::command]
@@ -19,18 +303,74 @@ pub async fn start_codex_session(
     state: State<'_, CodexState>,
     session_id: String,
     config: CodexConfig,
-) -> Result<(), String> {
+) -> Result<(), CodexError> {
     log::info!("Starting codex session: {}", session_id);
     codex::start_codex_session(app, state, session_id, config).await
 }
 
+/// Continue an existing rollout in a new codex process and return the session id to use.
+#[tauri::command]
+pub async fn resume_session(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    file_path: String,
+    config: CodexConfig,
+) -> Result<String, CodexError> {
+    codex::resume_session(app, state, file_path, config).await
+}
+
+/// Check the codex binary, `~/.codex`, history and sessions store, each with a
+/// status and a hint for fixing it.
+#[tauri::command]
+pub async fn diagnose(state: State<'_, CodexState>) -> Result<DiagnosticsReport, String> {
+    let codex_home = state.codex_home().ok();
+    tokio::task::spawn_blocking(move || diagnostics::diagnose(codex_home))
+        .await
+        .map_err(|e| format!("Diagnostics failed: {}", e))
+}
+
+#[tauri::command]
+pub async fn preview_start_session(
+    state: State<'_, CodexState>,
+    config: CodexConfig,
+) -> Result<PreflightReport, String> {
+    preflight::preview_start_session(&state.codex_home()?, config).await
+}
+
 #[tauri::command]
 pub async fn send_message(
+    app: AppHandle,
     state: State<'_, CodexState>,
     session_id: String,
     message: String,
-) -> Result<(), String> {
-    codex::send_message(state, session_id, message).await
+) -> Result<(), CodexError> {
+    codex::send_message(app, state, session_id, message).await
+}
+
+#[tauri::command]
+pub async fn get_queued_messages(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<Vec<String>, CodexError> {
+    codex::get_queued_messages(state, session_id).await
+}
+
+#[tauri::command]
+pub async fn clear_message_queue(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<usize, CodexError> {
+    codex::clear_message_queue(state, session_id).await
+}
+
+#[tauri::command]
+pub async fn send_message_with_model(
+    state: State<'_, CodexState>,
+    session_id: String,
+    message: String,
+    model: String,
+) -> Result<(), CodexError> {
+    codex::send_message_with_model(state, session_id, message, model).await
 }
 
 #[tauri::command]
@@ -39,7 +379,7 @@ pub async fn approve_execution(
     session_id: String,
     approval_id: String,
     approved: bool,
-) -> Result<(), String> {
+) -> Result<(), CodexError> {
     codex::approve_execution(state, session_id, approval_id, approved).await
 }
 
@@ -49,20 +389,208 @@ pub async fn approve_patch(
     session_id: String,
     approval_id: String,
     approved: bool,
-) -> Result<(), String> {
+) -> Result<(), CodexError> {
     codex::approve_patch(state, session_id, approval_id, approved).await
 }
 
 #[tauri::command]
-pub async fn pause_session(state: State<'_, CodexState>, session_id: String) -> Result<(), String> {
+pub async fn approve_patch_files(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    session_id: String,
+    approval_id: String,
+    approved_paths: Vec<String>,
+) -> Result<(), CodexError> {
+    codex::approve_patch_files(app, state, session_id, approval_id, approved_paths).await
+}
+
+#[tauri::command]
+pub async fn approve_all_pending(
+    state: State<'_, CodexState>,
+    session_id: String,
+    approved: bool,
+) -> Result<usize, CodexError> {
+    codex::approve_all_pending(state, session_id, approved).await
+}
+
+#[tauri::command]
+pub async fn pause_session(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), CodexError> {
     codex::pause_session(state, session_id).await
 }
 
 #[tauri::command]
-pub async fn close_session(state: State<'_, CodexState>, session_id: String) -> Result<(), String> {
+pub async fn interrupt_turn(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), CodexError> {
+    codex::interrupt_turn(app, state, session_id).await
+}
+
+#[tauri::command]
+pub async fn retry_last_turn(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    session_id: String,
+    edited_message: Option<String>,
+) -> Result<(), CodexError> {
+    codex::retry_last_turn(app, state, session_id, edited_message).await
+}
+
+#[tauri::command]
+pub async fn close_session(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), CodexError> {
     codex::close_session(state, session_id).await
 }
 
+#[tauri::command]
+pub async fn list_all_pending_approvals(
+    state: State<'_, CodexState>,
+) -> Result<Vec<PendingApprovalInfo>, String> {
+    codex::list_all_pending_approvals(state).await
+}
+
+#[tauri::command]
+pub async fn get_pending_patch(
+    state: State<'_, CodexState>,
+    session_id: String,
+    approval_id: String,
+) -> Result<PatchPreview, CodexError> {
+    codex::get_pending_patch(state, session_id, approval_id).await
+}
+
+#[tauri::command]
+pub async fn read_approval_log(
+    session_id: Option<String>,
+) -> Result<Vec<ApprovalLogEntry>, String> {
+    approval_log::read_entries(session_id.as_deref())
+}
+
+#[tauri::command]
+pub async fn export_approval_log_csv(
+    state: State<'_, CodexState>,
+    dest_path: String,
+    session_id: Option<String>,
+) -> Result<usize, String> {
+    state.ensure_writable()?;
+    resolve_allowed_write_target(&state, &dest_path).map_err(|e| e.to_string())?;
+    let dest_path = resolve_writable_text_file(&dest_path)?;
+    approval_log::export_csv(&dest_path, session_id.as_deref())
+}
+
+/// Write a rollout as Markdown. Pass `options.compact` to clean up noisy tool output.
+#[tauri::command]
+pub async fn export_session_markdown(
+    state: State<'_, CodexState>,
+    file_path: String,
+    out_path: String,
+    options: Option<MarkdownExportOptions>,
+) -> Result<(), String> {
+    state.ensure_writable()?;
+    let options = options.unwrap_or_default();
+    tokio::task::spawn_blocking(move || {
+        export::export_session_markdown(&file_path, &out_path, &options)
+    })
+    .await
+    .map_err(|e| format!("Export failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn get_effective_config(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<CodexConfig, CodexError> {
+    codex::get_effective_config(state, session_id).await
+}
+
+#[tauri::command]
+pub async fn get_session_output(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<CapturedOutput, CodexError> {
+    codex::get_session_output(state, session_id).await
+}
+
+#[tauri::command]
+pub async fn clear_session_output(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), String> {
+    codex::clear_session_output(state, session_id).await
+}
+
+#[tauri::command]
+pub async fn get_session_status(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<SessionStatus, CodexError> {
+    codex::get_session_status(state, session_id).await
+}
+
+#[tauri::command]
+pub async fn get_session_runtime(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<SessionRuntimeInfo, CodexError> {
+    codex::get_session_runtime(state, session_id).await
+}
+
+#[tauri::command]
+pub async fn get_session_usage(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<SessionUsage, CodexError> {
+    codex::get_session_usage(state, session_id).await
+}
+
+/// Close every running session, killing any that don't exit in time.
+#[tauri::command]
+pub async fn shutdown_all_sessions(state: State<'_, CodexState>) -> Result<usize, String> {
+    Ok(codex::shutdown_all_sessions(&state).await)
+}
+
+#[tauri::command]
+pub async fn reset_all_sessions(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+) -> Result<(), String> {
+    codex::reset_all_sessions(app, state).await
+}
+
+#[tauri::command]
+pub async fn enable_raw_event_debug(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), CodexError> {
+    codex::set_raw_event_debug(state, session_id, true).await
+}
+
+#[tauri::command]
+pub async fn disable_raw_event_debug(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), CodexError> {
+    codex::set_raw_event_debug(state, session_id, false).await
+}
+
+#[tauri::command]
+pub async fn run_command(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    session_id: String,
+    program: String,
+    args: Vec<String>,
+    timeout_secs: Option<u64>,
+) -> Result<i32, CodexError> {
+    CodexError::ensure_writable(&state)?;
+    shell::run_command(app, state, session_id, program, args, timeout_secs).await
+}
+
 #[tauri::command]
 pub async fn get_running_sessions(state: State<'_, CodexState>) -> Result<Vec<String>, String> {
     codex::get_running_sessions(state).await
@@ -73,65 +601,250 @@ pub async fn check_codex_version() -> Result<String, String> {
     codex::check_codex_version().await
 }
 
+/// Check that `path` (or the discovered codex) actually runs and report its version.
 #[tauri::command]
-pub async fn delete_session_file(file_path: String) -> Result<(), String> {
-    session::delete_session_file(file_path).await
+pub async fn validate_codex_binary(path: Option<String>) -> Result<CodexBinaryInfo, String> {
+    codex::validate_codex_binary(path).await
 }
 
 #[tauri::command]
-pub async fn get_latest_session_id() -> Result<Option<String>, String> {
-    session::get_latest_session_id().await
+pub async fn list_codex_binaries() -> Result<Vec<CodexBinaryInfo>, String> {
+    codex::list_codex_binaries().await
 }
 
 #[tauri::command]
-pub async fn get_session_files() -> Result<Vec<String>, String> {
-    let sessions_dir = get_sessions_path()?;
+pub async fn locate_codex() -> Result<Vec<CodexCandidate>, String> {
+    tokio::task::spawn_blocking(locate_codex_candidates)
+        .await
+        .map_err(|e| format!("Failed to locate codex: {}", e))
+}
 
-    if !sessions_dir.exists() {
-        return Ok(vec![]);
-    }
-    let session_files = scan_jsonl_files(&sessions_dir)
-        .map(|entry| entry.path().to_string_lossy().to_string())
-        .collect::<Vec<_>>();
+/// Rediscover the codex binary on next use, e.g. after installing it while the app is open.
+#[tauri::command]
+pub async fn invalidate_codex_path_cache() -> Result<(), String> {
+    codex_discovery::invalidate_codex_path_cache();
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn list_available_models(
+    state: State<'_, CodexState>,
+    config: CodexConfig,
+) -> Result<AvailableModels, String> {
+    models::list_available_models(&state.codex_home()?, config).await
+}
 
-    Ok(session_files)
+#[tauri::command]
+pub async fn check_codex_auth(state: State<'_, CodexState>) -> Result<CodexAuthStatus, String> {
+    auth::check_codex_auth(&state.codex_home()?).await
+}
+
+#[tauri::command]
+pub async fn delete_session_file(
+    state: State<'_, CodexState>,
+    file_path: String,
+) -> Result<(), String> {
+    state.ensure_writable()?;
+    session::delete_session_file(&state.sessions_path()?, file_path).await
+}
+
+/// Compress rollouts untouched for more than `days` days into the codex home's `archive`.
+#[tauri::command]
+pub async fn archive_sessions_older_than(
+    state: State<'_, CodexState>,
+    days: u64,
+) -> Result<ArchiveSummary, String> {
+    state.ensure_writable()?;
+    let codex_home = state.codex_home()?;
+    tokio::task::spawn_blocking(move || archive::archive_sessions_older_than(&codex_home, days))
+        .await
+        .map_err(|e| format!("Archive task failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn list_archived_sessions(
+    state: State<'_, CodexState>,
+) -> Result<Vec<ArchivedSession>, String> {
+    let codex_home = state.codex_home()?;
+    tokio::task::spawn_blocking(move || archive::list_archived_sessions(&codex_home))
+        .await
+        .map_err(|e| format!("Archive listing failed: {}", e))?
+}
+
+/// Move an archived rollout back into the sessions tree and return its new path.
+#[tauri::command]
+pub async fn restore_archived_session(
+    state: State<'_, CodexState>,
+    file_path: String,
+) -> Result<String, String> {
+    state.ensure_writable()?;
+    archive::restore_archived_session(&state.codex_home()?, &file_path)
+}
+
+/// Delete or archive many rollouts at once; see `BulkDeleteOptions` for the confirmation token.
+#[tauri::command]
+pub async fn delete_sessions(
+    state: State<'_, CodexState>,
+    file_paths: Vec<String>,
+    options: Option<BulkDeleteOptions>,
+) -> Result<BulkDeleteResult, String> {
+    state.ensure_writable()?;
+    session::delete_sessions(&state.codex_home()?, file_paths, options).await
+}
+
+#[tauri::command]
+pub async fn repair_session_metadata(
+    state: State<'_, CodexState>,
+) -> Result<MetadataRepairReport, String> {
+    state.ensure_writable()?;
+    let sessions_path = state.sessions_path()?;
+    tokio::task::spawn_blocking(move || session_meta::repair_session_metadata(&sessions_path))
+        .await
+        .map_err(|e| format!("Metadata repair failed: {}", e))?
+}
+
+#[tauri::command]
+pub async fn find_duplicate_sessions(
+    state: State<'_, CodexState>,
+) -> Result<Vec<DuplicateSessionGroup>, String> {
+    session::find_duplicate_sessions(&state.sessions_path()?).await
+}
+
+#[tauri::command]
+pub async fn truncate_session(
+    state: State<'_, CodexState>,
+    session_id: String,
+    keep_last_n: usize,
+) -> Result<TruncateResult, String> {
+    state.ensure_writable()?;
+    session::truncate_session(&state.sessions_path()?, session_id, keep_last_n).await
+}
+
+/// Combine two rollouts into a new session and return its id; the originals stay as they are.
+#[tauri::command]
+pub async fn merge_sessions(
+    state: State<'_, CodexState>,
+    first_id: String,
+    second_id: String,
+) -> Result<String, String> {
+    state.ensure_writable()?;
+    session::merge_sessions(&state.sessions_path()?, first_id, second_id).await
+}
+
+/// Copy a rollout under a new id to branch the conversation; nothing is started for it.
+#[tauri::command]
+pub async fn fork_session(
+    state: State<'_, CodexState>,
+    file_path: String,
+) -> Result<Conversation, String> {
+    state.ensure_writable()?;
+    session::fork_session(&state.sessions_path()?, file_path).await
+}
+
+#[tauri::command]
+pub async fn validate_session_file(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<SessionValidationReport, String> {
+    session::validate_session_file(&state.sessions_path()?, session_id).await
+}
+
+/// Messages that parsed plus a report of the lines that didn't, for recovering damaged rollouts.
+#[tauri::command]
+pub async fn read_session_parsed(
+    state: State<'_, CodexState>,
+    file_path: String,
+) -> Result<ParsedSession, String> {
+    session::read_session_parsed(&state.sessions_path()?, file_path).await
+}
+
+#[tauri::command]
+pub async fn read_session_metadata(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<SessionMetadata, String> {
+    session::read_session_metadata(&state.sessions_path()?, session_id).await
+}
+
+#[tauri::command]
+pub async fn get_latest_session_id(state: State<'_, CodexState>) -> Result<Option<String>, String> {
+    session::get_latest_session_id(&state.sessions_path()?).await
+}
+
+/// Every rollout file under the sessions directory, at whatever depth codex nests them.
+#[tauri::command]
+pub async fn get_session_files(state: State<'_, CodexState>) -> Result<Vec<String>, String> {
+    let sessions_dir = state.sessions_path()?;
+    // Walking day directories in parallel keeps slow (e.g. network) homes off the async runtime
+    let workers = load_settings().scan_worker_count();
+    let session_files =
+        tokio::task::spawn_blocking(move || session::list_session_files(&sessions_dir, workers))
+            .await
+            .map_err(|e| format!("Session file scan failed: {}", e))?;
+
+    Ok(session_files
+        .into_iter()
+        .map(|path| path.to_string_lossy().to_string())
+        .collect())
 }
 
 #[tauri::command]
 pub async fn read_session_file(file_path: String) -> Result<String, String> {
-    fs::read_to_string(&file_path).map_err(|e| format!("Failed to read session file: {}", e))
+    read_to_string_with_retry(&file_path)
+        .await
+        .map_err(|e| format!("Failed to read session file: {}", e))
 }
 
 #[tauri::command]
-pub async fn read_history_file() -> Result<String, String> {
-    let home = dirs::home_dir().ok_or("Could not find home directory")?;
-    let history_path = home.join(".codex").join("history.jsonl");
+pub async fn read_history_file(state: State<'_, CodexState>) -> Result<String, String> {
+    let history_path = history::get_history_path(&state.codex_home()?);
 
     if !history_path.exists() {
         return Ok(String::new());
     }
 
-    fs::read_to_string(&history_path).map_err(|e| format!("Failed to read history file: {}", e))
+    read_to_string_with_retry(&history_path)
+        .await
+        .map_err(|e| format!("Failed to read history file: {}", e))
 }
 
+/// Past prompts containing `query`, newest first, with back-to-back repeats collapsed.
 #[tauri::command]
-pub async fn find_rollout_path_for_session(session_uuid: String) -> Result<Option<String>, String> {
-    let sessions_dir = get_sessions_path()?;
-    if !sessions_dir.exists() {
-        return Ok(None);
-    }
+pub async fn search_history(
+    state: State<'_, CodexState>,
+    query: String,
+    limit: usize,
+) -> Result<Vec<HistoryEntry>, String> {
+    let codex_home = state.codex_home()?;
+    tokio::task::spawn_blocking(move || history::search_history(&codex_home, &query, limit))
+        .await
+        .map_err(|e| format!("History search failed: {}", e))?
+}
 
-    // Walk recursively year/month/day and find file ending with -<uuid>.jsonl
-    let needle = format!("-{}.jsonl", session_uuid);
-    let rollout_path = scan_jsonl_files(&sessions_dir)
-        .find_map(|entry| {
-            let file_name = entry.file_name();
-            if file_name.to_string_lossy().ends_with(&needle) {
-                Some(entry.path().to_string_lossy().to_string())
-            } else {
-                None
-            }
-        });
+/// Empty `history.jsonl` after moving its contents to a timestamped backup,
+/// whose path is returned (`None` when there was no history).
+#[tauri::command]
+pub async fn clear_history(state: State<'_, CodexState>) -> Result<Option<String>, String> {
+    state.ensure_writable()?;
+    let codex_home = state.codex_home()?;
+    tokio::task::spawn_blocking(move || history::clear_history(&codex_home))
+        .await
+        .map_err(|e| format!("Clearing history failed: {}", e))?
+}
 
-    Ok(rollout_path)
+/// Canonical session UUID from a bare id, `codex-event-<uuid>`, a rollout file name or path.
+#[tauri::command]
+pub async fn normalize_session_id(input: String) -> Result<String, String> {
+    session::normalize_session_id(&input)
+}
+
+#[tauri::command]
+pub async fn find_rollout_path_for_session(
+    state: State<'_, CodexState>,
+    session_uuid: String,
+) -> Result<Option<String>, String> {
+    Ok(
+        session::find_rollout_path_for_session(&state.sessions_path()?, &session_uuid)?
+            .map(|path| path.to_string_lossy().to_string()),
+    )
 }
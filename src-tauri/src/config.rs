@@ -1,10 +1,11 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use std::path::PathBuf;
-use tauri::command;
+use std::path::{Path, PathBuf};
+use tauri::{command, State};
 
 use crate::mcp::McpServerConfig;
+use crate::state::CodexState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ProjectConfig {
@@ -42,14 +43,13 @@ pub struct Project {
     pub trust_level: String,
 }
 
-pub fn get_config_path() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    Ok(home_dir.join(".codex").join("config.toml"))
+pub fn get_config_path(codex_home: &Path) -> PathBuf {
+    codex_home.join("config.toml")
 }
 
 #[command]
-pub async fn read_codex_config() -> Result<Vec<Project>, String> {
-    let config_path = get_config_path()?;
+pub async fn read_codex_config(state: State<'_, CodexState>) -> Result<Vec<Project>, String> {
+    let config_path = get_config_path(&state.codex_home()?);
 
     if !config_path.exists() {
         return Ok(Vec::new());
@@ -83,8 +83,12 @@ pub async fn is_version_controlled(path: String) -> Result<bool, String> {
 
 /// Set or update a project's trust level in `~/.codex/config.toml`.
 #[command]
-pub async fn set_project_trust(path: String, trust_level: String) -> Result<(), String> {
-    let config_path = get_config_path()?;
+pub async fn set_project_trust(
+    state: State<'_, CodexState>,
+    path: String,
+    trust_level: String,
+) -> Result<(), String> {
+    let config_path = get_config_path(&state.codex_home()?);
 
     // Read existing config or initialize a default one
     let mut codex_config: CodexConfig = if config_path.exists() {
@@ -130,9 +134,9 @@ pub async fn get_project_name(path: String) -> Result<String, String> {
     Ok(name)
 }
 
-#[command]
-pub async fn read_model_providers() -> Result<HashMap<String, ModelProvider>, String> {
-    let config_path = get_config_path()?;
+/// Model providers declared in `<codex_home>/config.toml`.
+pub fn load_model_providers(codex_home: &Path) -> Result<HashMap<String, ModelProvider>, String> {
+    let config_path = get_config_path(codex_home);
 
     if !config_path.exists() {
         return Ok(HashMap::new());
@@ -148,8 +152,15 @@ pub async fn read_model_providers() -> Result<HashMap<String, ModelProvider>, St
 }
 
 #[command]
-pub async fn read_profiles() -> Result<HashMap<String, Profile>, String> {
-    let config_path = get_config_path()?;
+pub async fn read_model_providers(
+    state: State<'_, CodexState>,
+) -> Result<HashMap<String, ModelProvider>, String> {
+    load_model_providers(&state.codex_home()?)
+}
+
+/// Profiles declared in `<codex_home>/config.toml`.
+pub fn load_profiles(codex_home: &Path) -> Result<HashMap<String, Profile>, String> {
+    let config_path = get_config_path(codex_home);
 
     if !config_path.exists() {
         return Ok(HashMap::new());
@@ -164,12 +175,21 @@ pub async fn read_profiles() -> Result<HashMap<String, Profile>, String> {
     Ok(config.profiles)
 }
 
+#[command]
+pub async fn read_profiles(
+    state: State<'_, CodexState>,
+) -> Result<HashMap<String, Profile>, String> {
+    load_profiles(&state.codex_home()?)
+}
+
 #[command]
 pub async fn get_provider_config(
+    state: State<'_, CodexState>,
     provider_name: String,
 ) -> Result<Option<(ModelProvider, Option<Profile>)>, String> {
-    let providers = read_model_providers().await?;
-    let profiles = read_profiles().await?;
+    let codex_home = state.codex_home()?;
+    let providers = load_model_providers(&codex_home)?;
+    let profiles = load_profiles(&codex_home)?;
 
     if let Some(provider) = providers.get(&provider_name) {
         let profile = profiles.get(&provider_name).cloned();
@@ -180,14 +200,21 @@ pub async fn get_provider_config(
 }
 
 #[command]
-pub async fn get_profile_config(profile_name: String) -> Result<Option<Profile>, String> {
-    let profiles = read_profiles().await?;
+pub async fn get_profile_config(
+    state: State<'_, CodexState>,
+    profile_name: String,
+) -> Result<Option<Profile>, String> {
+    let profiles = load_profiles(&state.codex_home()?)?;
     Ok(profiles.get(&profile_name).cloned())
 }
 
 #[command]
-pub async fn update_profile_model(profile_name: String, new_model: String) -> Result<(), String> {
-    let config_path = get_config_path()?;
+pub async fn update_profile_model(
+    state: State<'_, CodexState>,
+    profile_name: String,
+    new_model: String,
+) -> Result<(), String> {
+    let config_path = get_config_path(&state.codex_home()?);
 
     let mut codex_config: CodexConfig = if config_path.exists() {
         let content = fs::read_to_string(&config_path)
@@ -213,8 +240,12 @@ pub async fn update_profile_model(profile_name: String, new_model: String) -> Re
 }
 
 #[command]
-pub async fn add_or_update_profile(profile_name: String, profile: Profile) -> Result<(), String> {
-    let config_path = get_config_path()?;
+pub async fn add_or_update_profile(
+    state: State<'_, CodexState>,
+    profile_name: String,
+    profile: Profile,
+) -> Result<(), String> {
+    let config_path = get_config_path(&state.codex_home()?);
 
     let mut codex_config: CodexConfig = if config_path.exists() {
         let content = fs::read_to_string(&config_path)
@@ -246,8 +277,11 @@ pub async fn add_or_update_profile(profile_name: String, profile: Profile) -> Re
 }
 
 #[command]
-pub async fn delete_profile(profile_name: String) -> Result<(), String> {
-    let config_path = get_config_path()?;
+pub async fn delete_profile(
+    state: State<'_, CodexState>,
+    profile_name: String,
+) -> Result<(), String> {
+    let config_path = get_config_path(&state.codex_home()?);
 
     if !config_path.exists() {
         return Err("Config file does not exist".to_string());
@@ -274,10 +308,11 @@ pub async fn delete_profile(profile_name: String) -> Result<(), String> {
 
 #[command]
 pub async fn add_or_update_model_provider(
+    state: State<'_, CodexState>,
     provider_name: String,
     provider: ModelProvider,
 ) -> Result<(), String> {
-    let config_path = get_config_path()?;
+    let config_path = get_config_path(&state.codex_home()?);
 
     let mut codex_config: CodexConfig = if config_path.exists() {
         let content = fs::read_to_string(&config_path)
@@ -309,8 +344,14 @@ pub async fn add_or_update_model_provider(
 }
 
 #[command]
-pub async fn ensure_default_providers() -> Result<(), String> {
-    let config_path = get_config_path()?;
+pub async fn ensure_default_providers(state: State<'_, CodexState>) -> Result<(), String> {
+    add_default_providers(&state.codex_home()?)
+}
+
+/// Declare the Google and OpenRouter providers in `<codex_home>/config.toml`
+/// unless the user already has entries for them.
+pub fn add_default_providers(codex_home: &Path) -> Result<(), String> {
+    let config_path = get_config_path(codex_home);
 
     let mut codex_config: CodexConfig = if config_path.exists() {
         let content = fs::read_to_string(&config_path)
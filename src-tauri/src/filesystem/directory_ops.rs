@@ -1,12 +1,128 @@
-use super::file_types::FileEntry;
+use super::file_io::ensure_within_roots;
+use super::file_types::{CodexHomeUsage, DirectoryStats, FileEntry, ListDirectoryOptions};
+use crate::settings::load_settings;
+use crate::state::CodexState;
+use crate::utils::disk_space::available_space;
+use crate::utils::file::user_home;
+use crate::utils::gitignore::git_ignored_paths;
+use crate::utils::parallel::parallel_map;
+use glob::Pattern;
+use std::collections::HashSet;
 use std::fs;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tauri::State;
 use walkdir::{DirEntry, WalkDir};
 
+/// Largest number of entries `list_directory` returns in one call.
+const MAX_LIST_ENTRIES: usize = 20_000;
+
+/// Refuse `dir` unless it resolves under one of the allowed roots.
+fn ensure_dir_allowed(state: &CodexState, dir: &Path) -> Result<(), String> {
+    let resolved = fs::canonicalize(dir).map_err(|_| "Directory does not exist".to_string())?;
+    ensure_within_roots(&resolved, &state.allowed_roots()).map_err(|e| e.to_string())
+}
+
+fn file_entry(path: &Path, name: String, is_directory: bool) -> FileEntry {
+    let metadata = fs::metadata(path).ok();
+    let size = if is_directory {
+        None
+    } else {
+        metadata.as_ref().map(|m| m.len())
+    };
+    let extension = if is_directory {
+        None
+    } else {
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .map(|s| s.to_string())
+    };
+    let modified = metadata
+        .and_then(|m| m.modified().ok())
+        .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+        .map(|d| d.as_millis() as i64);
+
+    FileEntry {
+        name,
+        path: path.to_string_lossy().to_string(),
+        is_directory,
+        size,
+        extension,
+        modified,
+    }
+}
+
+/// List a directory for a file browser: directories first, then files, by name.
+/// Only the directory itself is listed unless `options.recursive` is set; symlinked
+/// directories are never followed, so a listing can't escape `dir_path`.
+#[tauri::command]
+pub async fn list_directory(
+    state: State<'_, CodexState>,
+    dir_path: String,
+    options: Option<ListDirectoryOptions>,
+) -> Result<Vec<FileEntry>, String> {
+    let options = options.unwrap_or_default();
+    let expanded_path = match dir_path.strip_prefix("~/") {
+        Some(rest) => user_home()?.join(rest),
+        None => PathBuf::from(&dir_path),
+    };
+    let root = fs::canonicalize(&expanded_path)
+        .map_err(|e| format!("Directory '{}' does not exist: {}", dir_path, e))?;
+    if !root.is_dir() {
+        return Err(format!("'{}' is not a directory", dir_path));
+    }
+    ensure_within_roots(&root, &state.allowed_roots()).map_err(|e| e.to_string())?;
+
+    let max_depth = if options.recursive {
+        options.max_depth.unwrap_or(usize::MAX).max(1)
+    } else {
+        1
+    };
+
+    tokio::task::spawn_blocking(move || {
+        let walker = WalkDir::new(&root)
+            .min_depth(1)
+            .max_depth(max_depth)
+            .follow_links(false)
+            .into_iter()
+            .filter_entry(|e| {
+                !(options.skip_hidden && e.file_name().to_string_lossy().starts_with('.'))
+            });
+
+        let mut entries = Vec::new();
+        for entry in walker.filter_map(Result::ok) {
+            if entries.len() >= MAX_LIST_ENTRIES {
+                return Err(format!(
+                    "Directory has more than {} entries; list it with a smaller max_depth",
+                    MAX_LIST_ENTRIES
+                ));
+            }
+            let name = entry.file_name().to_string_lossy().to_string();
+            entries.push(file_entry(entry.path(), name, entry.file_type().is_dir()));
+        }
+
+        // Directories first within each parent, then case-insensitive by name
+        entries.sort_by(|a, b| {
+            let parent_a = Path::new(&a.path).parent();
+            let parent_b = Path::new(&b.path).parent();
+            parent_a
+                .cmp(&parent_b)
+                .then_with(|| b.is_directory.cmp(&a.is_directory))
+                .then_with(|| a.name.to_lowercase().cmp(&b.name.to_lowercase()))
+        });
+        Ok(entries)
+    })
+    .await
+    .map_err(|e| format!("Failed to list directory: {}", e))?
+}
+
 #[tauri::command]
-pub async fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
+pub async fn read_directory(
+    state: State<'_, CodexState>,
+    path: String,
+) -> Result<Vec<FileEntry>, String> {
     let expanded_path = if path.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
+        let home = user_home()?;
         home.join(&path[2..])
     } else {
         Path::new(&path).to_path_buf()
@@ -15,6 +131,7 @@ pub async fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
     if !expanded_path.exists() || !expanded_path.is_dir() {
         return Err("Directory does not exist".to_string());
     }
+    ensure_dir_allowed(&state, &expanded_path)?;
 
     let mut entries = Vec::new();
 
@@ -31,27 +148,7 @@ pub async fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
                             .to_string();
 
                         let is_directory = path.is_dir();
-                        let size = if is_directory {
-                            None
-                        } else {
-                            fs::metadata(&path).ok().map(|m| m.len())
-                        };
-
-                        let extension = if is_directory {
-                            None
-                        } else {
-                            path.extension()
-                                .and_then(|ext| ext.to_str())
-                                .map(|s| s.to_string())
-                        };
-
-                        entries.push(FileEntry {
-                            name,
-                            path: path.to_string_lossy().to_string(),
-                            is_directory,
-                            size,
-                            extension,
-                        });
+                        entries.push(file_entry(&path, name, is_directory));
                     }
                     Err(_) => continue,
                 }
@@ -72,7 +169,7 @@ pub async fn read_directory(path: String) -> Result<Vec<FileEntry>, String> {
 
 #[tauri::command]
 pub async fn get_default_directories() -> Result<Vec<String>, String> {
-    let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
+    let home = user_home()?;
 
     let default_dirs = vec![
         home.to_string_lossy().to_string(),
@@ -96,16 +193,44 @@ fn should_skip_dir(entry: &DirEntry, exclude_folders: &[String]) -> bool {
     }
 }
 
+/// Glob excludes match either the entry name (`dist`, `*.egg-info`) or, for
+/// patterns containing a slash, the path relative to the search root (`packages/*/build`).
+fn matches_exclude_glob(entry: &DirEntry, root: &Path, patterns: &[Pattern]) -> bool {
+    let name = entry.file_name().to_string_lossy();
+    let relative = entry
+        .path()
+        .strip_prefix(root)
+        .map(|p| p.to_string_lossy().replace('\\', "/"))
+        .unwrap_or_default();
+
+    patterns.iter().any(|pattern| {
+        if pattern.as_str().contains('/') {
+            pattern.matches(&relative)
+        } else {
+            pattern.matches(&name)
+        }
+    })
+}
+
 #[tauri::command]
 pub async fn search_files(
+    state: State<'_, CodexState>,
     root: String,
     query: String,
     exclude_folders: Vec<String>,
     // Optional cap to avoid returning an extremely large result set
     max_results: Option<usize>,
+    // Optional directory names/globs pruned from the walk (e.g. "dist", "vendor", "*.egg-info")
+    exclude: Option<Vec<String>>,
 ) -> Result<Vec<FileEntry>, String> {
+    let exclude_patterns = exclude
+        .unwrap_or_default()
+        .iter()
+        .map(|p| Pattern::new(p).map_err(|e| format!("Invalid exclude pattern '{}': {}", p, e)))
+        .collect::<Result<Vec<_>, _>>()?;
+
     let expanded_root: PathBuf = if root.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
+        let home = user_home()?;
         home.join(&root[2..])
     } else {
         Path::new(&root).to_path_buf()
@@ -114,64 +239,239 @@ pub async fn search_files(
     if !expanded_root.exists() || !expanded_root.is_dir() {
         return Err("Directory does not exist".to_string());
     }
+    ensure_dir_allowed(&state, &expanded_root)?;
 
     let lc_query = query.to_lowercase();
     // Reasonable default limit
     let limit = max_results.unwrap_or(2000);
+    let workers = load_settings().scan_worker_count();
 
-    let mut results: Vec<FileEntry> = Vec::new();
+    // Each top-level entry is walked as its own subtree so the work can be spread
+    // over the configured number of workers; results keep the walk order.
+    let results = tokio::task::spawn_blocking(move || {
+        let mut results: Vec<FileEntry> = WalkDir::new(&expanded_root)
+            .max_depth(0)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter_map(|e| matching_entry(&e, &lc_query))
+            .collect();
+        let found = AtomicUsize::new(results.len());
 
-    let walker = WalkDir::new(&expanded_root)
-        .into_iter()
-        .filter_entry(|e| !e.path_is_symlink() && !should_skip_dir(e, &exclude_folders));
-
-    for entry in walker {
-        let entry = match entry {
-            Ok(e) => e,
-            Err(_e) => continue,
+        let is_excluded = |e: &DirEntry| {
+            e.path_is_symlink()
+                || should_skip_dir(e, &exclude_folders)
+                || matches_exclude_glob(e, &expanded_root, &exclude_patterns)
         };
 
-        let path = entry.path();
-        let file_name = match path.file_name().and_then(|n| n.to_str()) {
-            Some(n) => n,
-            None => continue,
+        let top_level: Vec<DirEntry> = WalkDir::new(&expanded_root)
+            .min_depth(1)
+            .max_depth(1)
+            .into_iter()
+            .filter_entry(|e| !is_excluded(e))
+            .filter_map(|e| e.ok())
+            .collect();
+
+        let per_subtree = parallel_map(&top_level, workers, |top| {
+            let mut matches = Vec::new();
+            let walker = WalkDir::new(top.path())
+                .into_iter()
+                .filter_entry(|e| !is_excluded(e));
+
+            for entry in walker {
+                if found.load(Ordering::Relaxed) >= limit {
+                    break;
+                }
+                let entry = match entry {
+                    Ok(e) => e,
+                    Err(_e) => continue,
+                };
+                if let Some(file_entry) = matching_entry(&entry, &lc_query) {
+                    found.fetch_add(1, Ordering::Relaxed);
+                    matches.push(file_entry);
+                }
+            }
+            matches
+        });
+
+        results.extend(per_subtree.into_iter().flatten());
+        results.truncate(limit);
+        results
+    })
+    .await
+    .map_err(|e| format!("File search failed: {}", e))?;
+
+    Ok(results)
+}
+
+/// Build a `FileEntry` when the entry's name matches the (lowercased) query.
+fn matching_entry(entry: &DirEntry, lc_query: &str) -> Option<FileEntry> {
+    let path = entry.path();
+    let file_name = path.file_name().and_then(|n| n.to_str())?;
+
+    // Match folders and files by name (case-insensitive)
+    if !file_name.to_lowercase().contains(lc_query) {
+        return None;
+    }
+
+    Some(file_entry(
+        path,
+        file_name.to_string(),
+        entry.file_type().is_dir(),
+    ))
+}
+
+/// Total file sizes and counts under `root` using metadata only, walking the
+/// top-level entries in parallel. Directories deeper than `max_depth` aren't entered.
+pub(crate) fn compute_directory_stats(
+    root: &Path,
+    respect_gitignore: bool,
+    max_depth: usize,
+    workers: usize,
+) -> DirectoryStats {
+    let ignored: HashSet<PathBuf> = if respect_gitignore {
+        git_ignored_paths(root).unwrap_or_default()
+    } else {
+        HashSet::new()
+    };
+    let is_ignored = |e: &DirEntry| ignored.contains(e.path());
+
+    let top_level: Vec<DirEntry> = WalkDir::new(root)
+        .min_depth(1)
+        .max_depth(1)
+        .into_iter()
+        .filter_entry(|e| !is_ignored(e))
+        .filter_map(|e| e.ok())
+        .collect();
+
+    if max_depth == 0 {
+        return DirectoryStats {
+            depth_limited: !top_level.is_empty(),
+            ..Default::default()
         };
+    }
 
-        // Match folders and files by name (case-insensitive)
-        if file_name.to_lowercase().contains(&lc_query) {
-            let is_directory = entry.file_type().is_dir();
-            let size = if is_directory {
-                None
-            } else {
-                fs::metadata(path).ok().map(|m| m.len())
-            };
-            let extension = if is_directory {
-                None
-            } else {
-                path.extension().and_then(|ext| ext.to_str()).map(|s| s.to_string())
-            };
-
-            results.push(FileEntry {
-                name: file_name.to_string(),
-                path: path.to_string_lossy().to_string(),
-                is_directory,
-                size,
-                extension,
-            });
+    // Each subtree is rooted at depth 1 relative to `root`
+    let per_subtree = parallel_map(&top_level, workers, |top| {
+        let mut stats = DirectoryStats::default();
+        let walker = WalkDir::new(top.path())
+            .max_depth(max_depth - 1)
+            .into_iter()
+            .filter_entry(|e| !is_ignored(e));
 
-            if results.len() >= limit {
-                break;
+        for entry in walker.filter_map(|e| e.ok()) {
+            let file_type = entry.file_type();
+            if file_type.is_dir() {
+                stats.dir_count += 1;
+                if entry.depth() + 1 == max_depth
+                    && fs::read_dir(entry.path()).is_ok_and(|mut d| d.next().is_some())
+                {
+                    stats.depth_limited = true;
+                }
+            } else if file_type.is_file() {
+                stats.file_count += 1;
+                stats.total_bytes += entry.metadata().map(|m| m.len()).unwrap_or(0);
             }
         }
+        stats
+    });
+
+    per_subtree
+        .into_iter()
+        .fold(DirectoryStats::default(), |mut total, stats| {
+            total.total_bytes += stats.total_bytes;
+            total.file_count += stats.file_count;
+            total.dir_count += stats.dir_count;
+            total.depth_limited |= stats.depth_limited;
+            total
+        })
+}
+
+/// Recursively total file sizes and counts under `dir_path` using metadata only.
+/// Directories deeper than `max_depth` (default 32) below `dir_path` aren't entered.
+#[tauri::command]
+pub async fn directory_stats(
+    state: State<'_, CodexState>,
+    dir_path: String,
+    respect_gitignore: Option<bool>,
+    max_depth: Option<usize>,
+) -> Result<DirectoryStats, String> {
+    let expanded_root: PathBuf = if let Some(rest) = dir_path.strip_prefix("~/") {
+        user_home()?.join(rest)
+    } else {
+        Path::new(&dir_path).to_path_buf()
+    };
+
+    if !expanded_root.exists() || !expanded_root.is_dir() {
+        return Err("Directory does not exist".to_string());
     }
+    ensure_dir_allowed(&state, &expanded_root)?;
 
-    Ok(results)
+    let max_depth = max_depth.unwrap_or(32);
+    let workers = load_settings().scan_worker_count();
+
+    tokio::task::spawn_blocking(move || {
+        compute_directory_stats(
+            &expanded_root,
+            respect_gitignore.unwrap_or(false),
+            max_depth,
+            workers,
+        )
+    })
+    .await
+    .map_err(|e| format!("Directory scan failed: {}", e))
+}
+
+/// Size of a file or directory tree; missing paths count as zero.
+fn path_size(path: &Path, workers: usize) -> u64 {
+    match fs::metadata(path) {
+        Ok(meta) if meta.is_dir() => compute_directory_stats(path, false, 64, workers).total_bytes,
+        Ok(meta) => meta.len(),
+        Err(_) => 0,
+    }
+}
+
+/// Free bytes on the filesystem containing `path`. The path doesn't have to
+/// exist yet; its nearest existing parent is used.
+#[tauri::command]
+pub async fn available_disk_space(path: String) -> Result<u64, String> {
+    let expanded_path = match path.strip_prefix("~/") {
+        Some(rest) => user_home()?.join(rest),
+        None => PathBuf::from(&path),
+    };
+    available_space(&expanded_path)
+}
+
+/// Storage used by the codex home, split into the parts users usually ask about.
+#[tauri::command]
+pub async fn codex_home_disk_usage(state: State<'_, CodexState>) -> Result<CodexHomeUsage, String> {
+    let home = state.codex_home()?;
+    let workers = load_settings().scan_worker_count();
+
+    tokio::task::spawn_blocking(move || {
+        let total_bytes = path_size(&home, workers);
+        let sessions_bytes = path_size(&home.join("sessions"), workers);
+        let history_bytes = path_size(&home.join("history.jsonl"), workers);
+        let logs_bytes = path_size(&home.join("log"), workers);
+        let auth_bytes = path_size(&home.join("auth.json"), workers);
+
+        CodexHomeUsage {
+            total_bytes,
+            sessions_bytes,
+            history_bytes,
+            logs_bytes,
+            auth_bytes,
+            other_bytes: total_bytes
+                .saturating_sub(sessions_bytes + history_bytes + logs_bytes + auth_bytes),
+        }
+    })
+    .await
+    .map_err(|e| format!("Disk usage scan failed: {}", e))
 }
 
 #[tauri::command]
 pub async fn canonicalize_path(path: String) -> Result<String, String> {
     let expanded = if path.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
+        let home = user_home()?;
         home.join(&path[2..])
     } else {
         Path::new(&path).to_path_buf()
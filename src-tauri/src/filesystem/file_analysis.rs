@@ -1,3 +1,4 @@
+use serde::Serialize;
 use std::fs;
 use std::path::Path;
 
@@ -66,3 +67,116 @@ pub async fn calculate_file_tokens(file_path: String) -> Result<Option<u32>, Str
         Err(_) => Ok(None),
     }
 }
+
+#[derive(Debug, Serialize)]
+pub struct TokenCount {
+    pub tokens: usize,
+    /// Tokenizer family the count was calibrated for, e.g. "o200k_base".
+    pub encoding: String,
+    /// Always true for now: counts come from a calibrated heuristic, not a BPE tokenizer.
+    pub estimated: bool,
+}
+
+/// Tokenizer family for a model name, with the average characters per token
+/// for English words in that family.
+fn token_family(model: &str) -> (&'static str, f64) {
+    let model = model.to_lowercase();
+    if model.starts_with("gpt-4o")
+        || model.starts_with("gpt-4.1")
+        || model.starts_with("gpt-5")
+        || model.starts_with("o1")
+        || model.starts_with("o3")
+        || model.starts_with("o4")
+        || model.contains("codex")
+    {
+        ("o200k_base", 4.2)
+    } else if model.starts_with("gpt-4") || model.starts_with("gpt-3.5") {
+        ("cl100k_base", 4.0)
+    } else if model.contains("claude") {
+        ("claude", 3.5)
+    } else {
+        ("unknown", 4.0)
+    }
+}
+
+#[derive(PartialEq)]
+enum Run {
+    None,
+    Letters,
+    Digits,
+}
+
+/// Estimate BPE tokens: letter runs are split by the family's characters per
+/// token, digits go in groups of three, punctuation, newlines and non-ASCII
+/// characters count one each, and other whitespace merges into the next word.
+fn estimate_tokens(text: &str, chars_per_token: f64) -> usize {
+    let run_tokens = |run: &Run, len: usize| match run {
+        Run::Letters => ((len as f64) / chars_per_token).ceil() as usize,
+        Run::Digits => len.div_ceil(3),
+        Run::None => 0,
+    };
+
+    let mut tokens = 0;
+    let mut run = Run::None;
+    let mut run_len = 0;
+    for c in text.chars() {
+        let kind = if c.is_ascii_alphabetic() {
+            Run::Letters
+        } else if c.is_ascii_digit() {
+            Run::Digits
+        } else {
+            Run::None
+        };
+
+        if kind != run {
+            tokens += run_tokens(&run, run_len);
+            run_len = 0;
+        }
+        if kind == Run::None && (c == '\n' || !c.is_whitespace()) {
+            tokens += 1;
+        }
+        run = kind;
+        run_len += 1;
+    }
+    tokens + run_tokens(&run, run_len)
+}
+
+/// Estimate how many tokens `text` costs for `model`, without a running session.
+#[tauri::command]
+pub async fn count_tokens(text: String, model: Option<String>) -> Result<TokenCount, String> {
+    let (encoding, chars_per_token) = token_family(model.as_deref().unwrap_or_default());
+    Ok(TokenCount {
+        tokens: estimate_tokens(&text, chars_per_token),
+        encoding: encoding.to_string(),
+        estimated: true,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn estimate_tokens_empty_input() {
+        assert_eq!(estimate_tokens("", 4.0), 0);
+    }
+
+    #[test]
+    fn estimate_tokens_ascii_prose() {
+        // The(1) quick(2) brown(2) fox(1) .(1); spaces merge into the next word
+        assert_eq!(estimate_tokens("The quick brown fox.", 4.0), 7);
+        assert_eq!(estimate_tokens("line one\nline two", 4.0), 5);
+    }
+
+    #[test]
+    fn estimate_tokens_digits_group_in_threes() {
+        assert_eq!(estimate_tokens("1234567", 4.0), 3);
+        assert_eq!(estimate_tokens("2026-10-15", 4.0), 6);
+    }
+
+    #[test]
+    fn estimate_tokens_cjk_counts_each_character() {
+        assert_eq!(estimate_tokens("你好世界", 4.0), 4);
+        assert_eq!(estimate_tokens("你好，世界", 4.0), 5);
+    }
+}
@@ -1,42 +1,407 @@
+use super::file_types::{
+    ContentDiff, DecodedFileContent, FileAccessError, FileChunk, FileContent, FileLines,
+    GlobReadResult, SkippedFile,
+};
+use crate::settings::load_settings;
+use crate::state::CodexState;
+use crate::utils::diff::unified_diff;
+use crate::utils::disk_space::ensure_space;
+use crate::utils::file::user_home;
+use encoding_rs::{EncoderResult, Encoding, UTF_16BE, UTF_16LE, UTF_8, WINDOWS_1252};
+use std::collections::BTreeMap;
 use std::fs;
-use std::path::Path;
+use std::io::{BufRead, BufReader, Read, Seek, SeekFrom, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+use tauri::State;
 
-#[tauri::command]
-pub async fn read_file(file_path: String) -> Result<String, String> {
-    let expanded_path = if file_path.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
-        home.join(&file_path[2..])
-    } else {
-        Path::new(&file_path).to_path_buf()
+/// Largest single file the read commands will return.
+const MAX_READ_BYTES: u64 = 1024 * 1024;
+/// Upper bound for `read_file`'s `max_bytes`; bigger files should be read with `read_file_range`.
+const MAX_READ_BYTES_CEILING: u64 = 16 * 1024 * 1024;
+const DEFAULT_GLOB_MAX_FILES: usize = 50;
+const DEFAULT_GLOB_MAX_TOTAL_BYTES: u64 = 4 * 1024 * 1024;
+const DEFAULT_STREAM_MAX_LINES: usize = 1000;
+const MAX_STREAM_LINES: usize = 10_000;
+
+/// Lines between the byte offsets recorded in a `LineIndex`.
+const LINE_INDEX_STRIDE: usize = 1000;
+/// Files whose `LineIndex` is kept; the least recently used one is dropped first.
+const MAX_LINE_INDEXES: usize = 16;
+
+/// Line count of a file plus the byte offset of every `LINE_INDEX_STRIDE`th
+/// line, valid while the file's mtime and size are unchanged.
+struct LineIndex {
+    modified: SystemTime,
+    len: u64,
+    total_lines: usize,
+    /// `checkpoints[i]` is where line `i * LINE_INDEX_STRIDE` starts.
+    checkpoints: Vec<u64>,
+}
+
+/// Most recently used last.
+static LINE_INDEXES: Mutex<Vec<(PathBuf, Arc<LineIndex>)>> = Mutex::new(Vec::new());
+
+/// Look up an encoding by WHATWG label ("latin1", "windows-1252", "shift_jis", ...).
+fn encoding_for_label(label: &str) -> Result<&'static Encoding, String> {
+    Encoding::for_label(label.trim().as_bytes())
+        .ok_or_else(|| format!("Unsupported encoding: {}", label))
+}
+
+/// Expand `~/` and resolve symlinks and `..`, requiring the result to be a regular file.
+pub fn resolve_readable_file(file_path: &str) -> Result<PathBuf, String> {
+    let expanded_path = match file_path.strip_prefix("~/") {
+        Some(rest) => user_home()?.join(rest),
+        None => PathBuf::from(file_path),
     };
+    match fs::canonicalize(&expanded_path) {
+        Ok(resolved) if resolved.is_file() => Ok(resolved),
+        _ => Err("File does not exist or is a directory".to_string()),
+    }
+}
 
-    if !expanded_path.exists() || expanded_path.is_dir() {
-        return Err("File does not exist or is a directory".to_string());
+/// Err unless canonical `resolved` lies under one of `roots`. No roots means no restriction.
+pub(crate) fn ensure_within_roots(
+    resolved: &Path,
+    roots: &[PathBuf],
+) -> Result<(), FileAccessError> {
+    if roots.is_empty() || roots.iter().any(|root| resolved.starts_with(root)) {
+        return Ok(());
     }
+    log::warn!(
+        "Refused access outside allowed roots: {}",
+        resolved.display()
+    );
+    Err(FileAccessError::OutsideAllowedRoots {
+        path: resolved.to_string_lossy().to_string(),
+        message: format!("{} is outside the allowed directories", resolved.display()),
+    })
+}
+
+fn expand_home(file_path: &str) -> Result<PathBuf, String> {
+    Ok(match file_path.strip_prefix("~/") {
+        Some(rest) => user_home()?.join(rest),
+        None => PathBuf::from(file_path),
+    })
+}
+
+/// Resolve an existing file for reading, symlinks included, against the allowed roots.
+fn resolve_allowed_file(state: &CodexState, file_path: &str) -> Result<PathBuf, FileAccessError> {
+    let resolved =
+        fs::canonicalize(expand_home(file_path)?).map_err(|_| FileAccessError::NotFound {
+            path: file_path.to_string(),
+            message: "File does not exist or is a directory".to_string(),
+        })?;
+    ensure_within_roots(&resolved, &state.allowed_roots())?;
+    Ok(resolved)
+}
+
+/// Resolve a file about to be written against the allowed roots. A new file is
+/// judged by its canonical parent directory, an existing one (or the target of
+/// a symlink) by its own canonical path.
+pub(crate) fn resolve_allowed_write_target(
+    state: &CodexState,
+    file_path: &str,
+) -> Result<PathBuf, FileAccessError> {
+    let expanded = expand_home(file_path)?;
+    let not_found = || FileAccessError::NotFound {
+        path: file_path.to_string(),
+        message: "Parent directory does not exist".to_string(),
+    };
+    let resolved = match fs::canonicalize(&expanded) {
+        Ok(resolved) => resolved,
+        Err(_) => {
+            let parent = expanded
+                .parent()
+                .filter(|p| !p.as_os_str().is_empty())
+                .unwrap_or(Path::new("."));
+            let file_name = expanded.file_name().ok_or_else(not_found)?;
+            fs::canonicalize(parent)
+                .map_err(|_| not_found())?
+                .join(file_name)
+        }
+    };
+    ensure_within_roots(&resolved, &state.allowed_roots())?;
+    Ok(resolved)
+}
+
+/// `resolve_readable_file`, also refusing files over `max_bytes` (default 1MB, at most 16MB).
+fn resolve_capped_file(file_path: &str, max_bytes: Option<u64>) -> Result<PathBuf, String> {
+    let expanded_path = resolve_readable_file(file_path)?;
 
     // Check file size to prevent reading very large files
+    let max_bytes = max_bytes
+        .unwrap_or(MAX_READ_BYTES)
+        .clamp(1, MAX_READ_BYTES_CEILING);
     if let Ok(metadata) = fs::metadata(&expanded_path) {
-        if metadata.len() > 1024 * 1024 {
-            // 1MB limit
+        if metadata.len() > max_bytes {
             return Err("File is too large to display".to_string());
         }
     }
+    Ok(expanded_path)
+}
+
+/// Guess the encoding of `bytes`: a BOM wins, then valid UTF-8, then BOM-less
+/// UTF-16 recognised by NUL bytes in every other position, and finally Latin-1
+/// (as windows-1252, which decodes any byte sequence).
+fn detect_encoding(bytes: &[u8]) -> &'static Encoding {
+    if let Some((encoding, _)) = Encoding::for_bom(bytes) {
+        return encoding;
+    }
+    if std::str::from_utf8(bytes).is_ok() {
+        return UTF_8;
+    }
+
+    let sample = &bytes[..bytes.len().min(4096) & !1];
+    if !sample.is_empty() {
+        let pairs = sample.len() / 2;
+        let even_nuls = sample.iter().step_by(2).filter(|b| **b == 0).count();
+        let odd_nuls = sample
+            .iter()
+            .skip(1)
+            .step_by(2)
+            .filter(|b| **b == 0)
+            .count();
+        // Mostly-ASCII UTF-16 text has a NUL in the high byte of nearly every unit
+        if odd_nuls * 10 >= pairs * 3 && even_nuls * 10 < pairs {
+            return UTF_16LE;
+        }
+        if even_nuls * 10 >= pairs * 3 && odd_nuls * 10 < pairs {
+            return UTF_16BE;
+        }
+    }
+    WINDOWS_1252
+}
+
+/// Read a text file in whatever encoding it's in. The encoding is detected (see
+/// `detect_encoding`) and `lossy` is set when some bytes couldn't be decoded, in
+/// which case saving the text back would change the file.
+#[tauri::command]
+pub async fn read_file_lossy(
+    state: State<'_, CodexState>,
+    file_path: String,
+    max_bytes: Option<u64>,
+) -> Result<DecodedFileContent, FileAccessError> {
+    resolve_allowed_file(&state, &file_path)?;
+    let expanded_path = resolve_capped_file(&file_path, max_bytes)?;
+    let bytes = fs::read(&expanded_path).map_err(|e| format!("Failed to read file: {}", e))?;
+
+    let has_bom = Encoding::for_bom(&bytes).is_some();
+    let (content, used_encoding, lossy) = detect_encoding(&bytes).decode(&bytes);
+    Ok(DecodedFileContent {
+        content: content.into_owned(),
+        encoding: used_encoding.name().to_string(),
+        lossy,
+        has_bom,
+    })
+}
+
+/// Read a text file. Without `encoding` the file must be valid UTF-8; with it,
+/// the bytes are decoded from that encoding (a BOM, if present, takes precedence).
+/// `max_bytes` overrides the 1MB size limit, up to 16MB.
+#[tauri::command]
+pub async fn read_file(
+    state: State<'_, CodexState>,
+    file_path: String,
+    encoding: Option<String>,
+    max_bytes: Option<u64>,
+) -> Result<FileContent, FileAccessError> {
+    resolve_allowed_file(&state, &file_path)?;
+    let expanded_path = resolve_capped_file(&file_path, max_bytes)?;
+
+    let Some(label) = encoding else {
+        return match fs::read_to_string(&expanded_path) {
+            Ok(content) => Ok(FileContent {
+                content,
+                encoding: UTF_8.name().to_string(),
+            }),
+            Err(e) => Err(format!("Failed to read file: {}", e).into()),
+        };
+    };
+
+    let encoding = encoding_for_label(&label)?;
+    let bytes = fs::read(&expanded_path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let (content, used_encoding, _) = encoding.decode(&bytes);
+
+    Ok(FileContent {
+        content: content.into_owned(),
+        encoding: used_encoding.name().to_string(),
+    })
+}
+
+/// Read every text file matching `pattern` (relative to `base_dir`) in one call.
+/// Matches that resolve outside `base_dir`, e.g. via `..` or symlinks, are ignored.
+///
+/// By default files that can't be returned are listed in `skipped` and the rest
+/// are still read. With `stop_on_error`, the first such file ends the read: files
+/// before it are in `files`, it is the last entry of `skipped`, and later matches
+/// are not looked at.
+#[tauri::command]
+pub async fn read_files_glob(
+    state: State<'_, CodexState>,
+    base_dir: String,
+    pattern: String,
+    max_files: Option<usize>,
+    max_total_bytes: Option<u64>,
+    stop_on_error: Option<bool>,
+) -> Result<GlobReadResult, String> {
+    let stop_on_error = stop_on_error.unwrap_or(false);
+    let expanded_base = if let Some(rest) = base_dir.strip_prefix("~/") {
+        user_home()?.join(rest)
+    } else {
+        Path::new(&base_dir).to_path_buf()
+    };
+    let base = fs::canonicalize(&expanded_base)
+        .map_err(|e| format!("Failed to resolve base directory: {}", e))?;
+    if !base.is_dir() {
+        return Err("Base directory does not exist".to_string());
+    }
+    ensure_within_roots(&base, &state.allowed_roots()).map_err(|e| e.to_string())?;
+    if Path::new(&pattern).is_absolute() {
+        return Err("Glob pattern must be relative to the base directory".to_string());
+    }
 
-    match fs::read_to_string(&expanded_path) {
-        Ok(content) => Ok(content),
-        Err(e) => Err(format!("Failed to read file: {}", e)),
+    let full_pattern = base.join(&pattern).to_string_lossy().to_string();
+    let paths = glob::glob(&full_pattern).map_err(|e| format!("Invalid glob pattern: {}", e))?;
+
+    let max_files = max_files.unwrap_or(DEFAULT_GLOB_MAX_FILES);
+    let max_total_bytes = max_total_bytes.unwrap_or(DEFAULT_GLOB_MAX_TOTAL_BYTES);
+    let mut result = GlobReadResult {
+        files: BTreeMap::new(),
+        skipped: Vec::new(),
+        truncated: false,
+        aborted: false,
+    };
+    let mut total_bytes: u64 = 0;
+
+    for path in paths.filter_map(|p| p.ok()) {
+        let Ok(resolved) = fs::canonicalize(&path) else {
+            continue;
+        };
+        if !resolved.starts_with(&base) || !resolved.is_file() {
+            continue;
+        }
+        if result.files.len() >= max_files {
+            result.truncated = true;
+            break;
+        }
+
+        let display_path = path.to_string_lossy().to_string();
+        let size = fs::metadata(&resolved).map(|m| m.len()).unwrap_or(0);
+
+        let outcome = if size > MAX_READ_BYTES {
+            Err("too_large")
+        } else if total_bytes + size > max_total_bytes {
+            Err("total_limit")
+        } else {
+            match fs::read(&resolved) {
+                // Treat NUL bytes or invalid UTF-8 as binary
+                Ok(bytes) => match String::from_utf8(bytes) {
+                    Ok(content) if !content.contains('\0') => Ok(content),
+                    _ => Err("binary"),
+                },
+                Err(_) => Err("unreadable"),
+            }
+        };
+
+        match outcome {
+            Ok(content) => {
+                total_bytes += size;
+                result.files.insert(display_path, content);
+            }
+            Err(reason) => {
+                result.skipped.push(SkippedFile {
+                    path: display_path,
+                    reason: reason.to_string(),
+                });
+                if stop_on_error {
+                    result.aborted = true;
+                    break;
+                }
+            }
+        }
     }
+
+    Ok(result)
 }
 
+/// Unified diff between the file on disk and `new_content`, without writing anything.
+/// A missing file diffs as entirely added.
 #[tauri::command]
-pub async fn write_file(file_path: String, content: String) -> Result<(), String> {
-    let expanded_path = if file_path.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
-        home.join(&file_path[2..])
+pub async fn diff_against_file(
+    state: State<'_, CodexState>,
+    file_path: String,
+    new_content: String,
+) -> Result<ContentDiff, String> {
+    let expanded_path = if let Some(rest) = file_path.strip_prefix("~/") {
+        user_home()?.join(rest)
     } else {
         Path::new(&file_path).to_path_buf()
     };
 
+    let is_new_file = !expanded_path.exists();
+    let current_content = if is_new_file {
+        String::new()
+    } else {
+        read_file(state, file_path.clone(), None, None)
+            .await
+            .map_err(|e| e.to_string())?
+            .content
+    };
+
+    let old_label = if is_new_file { "/dev/null" } else { &file_path };
+    let diff = unified_diff(&current_content, &new_content, old_label, &file_path, 3);
+
+    Ok(ContentDiff {
+        has_changes: !diff.is_empty(),
+        unified_diff: diff,
+        is_new_file,
+    })
+}
+
+/// Encode `content` without replacement, failing on the first character the
+/// target encoding can't represent.
+fn encode_strict(content: &str, encoding: &'static Encoding) -> Result<Vec<u8>, String> {
+    if encoding.output_encoding() != encoding {
+        // encoding_rs only decodes UTF-16 and replacement; encoding to them falls back to UTF-8
+        return Err(format!("Writing {} is not supported", encoding.name()));
+    }
+
+    let mut encoder = encoding.new_encoder();
+    let mut bytes = Vec::with_capacity(
+        encoder
+            .max_buffer_length_from_utf8_without_replacement(content.len())
+            .unwrap_or(content.len()),
+    );
+    let mut remaining = content;
+    loop {
+        let (result, read) =
+            encoder.encode_from_utf8_to_vec_without_replacement(remaining, &mut bytes, true);
+        remaining = &remaining[read..];
+        match result {
+            EncoderResult::InputEmpty => return Ok(bytes),
+            EncoderResult::OutputFull => bytes.reserve(remaining.len().max(16)),
+            EncoderResult::Unmappable(c) => {
+                return Err(format!(
+                    "Character '{}' (U+{:04X}) cannot be represented in {}",
+                    c,
+                    c as u32,
+                    encoding.name()
+                ))
+            }
+        }
+    }
+}
+
+/// Expand `~/` in a path that is about to be written and check that it names a
+/// text file, the only kind the app writes.
+pub fn resolve_writable_text_file(file_path: &str) -> Result<PathBuf, String> {
+    let expanded_path = match file_path.strip_prefix("~/") {
+        Some(rest) => user_home()?.join(rest),
+        None => PathBuf::from(file_path),
+    };
+
     // Basic safety check: only allow writing to text files
     let extension = expanded_path
         .extension()
@@ -47,16 +412,255 @@ pub async fn write_file(file_path: String, content: String) -> Result<(), String
         Some("txt") | Some("md") | Some("json") | Some("xml") | Some("yaml") | Some("yml")
         | Some("js") | Some("jsx") | Some("ts") | Some("tsx") | Some("rs") | Some("py")
         | Some("java") | Some("cpp") | Some("c") | Some("h") | Some("css") | Some("html")
-        | Some("toml") | Some("cfg") | Some("ini") | Some("sh") | Some("log") => true,
+        | Some("toml") | Some("cfg") | Some("ini") | Some("sh") | Some("log") | Some("csv") => true,
         _ => false,
     };
 
     if !is_text_file {
         return Err("Only text files can be edited".to_string());
     }
+    Ok(expanded_path)
+}
+
+/// Write a text file, encoded as `encoding` when given (UTF-8 otherwise).
+#[tauri::command]
+pub async fn write_file(
+    state: State<'_, CodexState>,
+    file_path: String,
+    content: String,
+    encoding: Option<String>,
+) -> Result<(), FileAccessError> {
+    state.ensure_writable()?;
+    resolve_allowed_write_target(&state, &file_path)?;
+    let expanded_path = resolve_writable_text_file(&file_path)?;
+
+    let bytes = match encoding {
+        Some(label) => encode_strict(&content, encoding_for_label(&label)?)?,
+        None => content.into_bytes(),
+    };
+
+    let max_bytes = load_settings().write_limit();
+    if bytes.len() as u64 > max_bytes {
+        return Err(format!(
+            "Content is too large to write ({} bytes, limit is {} bytes)",
+            bytes.len(),
+            max_bytes
+        )
+        .into());
+    }
+    ensure_space(&expanded_path, bytes.len() as u64)?;
+
+    Ok(write_atomically(&expanded_path, &bytes)?)
+}
+
+/// Write `bytes` to a temporary file beside `path`, flush it to disk and rename it
+/// over `path`, so a crash mid-save leaves either the old or the new content.
+fn write_atomically(path: &Path, bytes: &[u8]) -> Result<(), String> {
+    // Replace the file a symlink points at rather than the link itself
+    let path = &fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+    let file_name = path
+        .file_name()
+        .ok_or_else(|| format!("Invalid file path: {}", path.display()))?;
+    let tmp_path = path.with_file_name(format!(
+        ".{}.{}.tmp",
+        file_name.to_string_lossy(),
+        std::process::id()
+    ));
+
+    let result = (|| {
+        let mut file =
+            fs::File::create(&tmp_path).map_err(|e| format!("Failed to write file: {}", e))?;
+        file.write_all(bytes)
+            .and_then(|_| file.sync_all())
+            .map_err(|e| format!("Failed to write file: {}", e))?;
+        // Keep the mode of the file being replaced, e.g. an executable script
+        if let Ok(metadata) = fs::metadata(path) {
+            let _ = fs::set_permissions(&tmp_path, metadata.permissions());
+        }
+        fs::rename(&tmp_path, path).map_err(|e| format!("Failed to replace file: {}", e))
+    })();
+
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp_path);
+    }
+    result
+}
+
+/// Append text to a file, creating it if needed. Same text-file and size rules as `write_file`.
+#[tauri::command]
+pub async fn append_file(
+    state: State<'_, CodexState>,
+    file_path: String,
+    content: String,
+) -> Result<(), FileAccessError> {
+    state.ensure_writable()?;
+    resolve_allowed_write_target(&state, &file_path)?;
+    let expanded_path = resolve_writable_text_file(&file_path)?;
+
+    let max_bytes = load_settings().write_limit();
+    if content.len() as u64 > max_bytes {
+        return Err(format!(
+            "Content is too large to write ({} bytes, limit is {} bytes)",
+            content.len(),
+            max_bytes
+        )
+        .into());
+    }
+    ensure_space(&expanded_path, content.len() as u64)?;
+
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&expanded_path)
+        .map_err(|e| format!("Failed to open file: {}", e))?;
+    file.write_all(content.as_bytes())
+        .map_err(|e| format!("Failed to append to file: {}", e).into())
+}
+
+/// Count lines by scanning for newlines in fixed-size chunks, recording where
+/// every `LINE_INDEX_STRIDE`th line starts. A final line without a trailing
+/// newline still counts.
+fn build_line_index(path: &Path, modified: SystemTime, len: u64) -> Result<LineIndex, String> {
+    let mut file = fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let mut buf = vec![0u8; 64 * 1024];
+    let mut count = 0;
+    let mut offset = 0u64;
+    let mut checkpoints = vec![0];
+    let mut last_byte = None;
+    loop {
+        let n = file
+            .read(&mut buf)
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        if n == 0 {
+            break;
+        }
+        for (i, _) in buf[..n].iter().enumerate().filter(|(_, &b)| b == b'\n') {
+            count += 1;
+            if count % LINE_INDEX_STRIDE == 0 {
+                checkpoints.push(offset + i as u64 + 1);
+            }
+        }
+        offset += n as u64;
+        last_byte = Some(buf[n - 1]);
+    }
+    if last_byte.is_some_and(|b| b != b'\n') {
+        count += 1;
+    }
+    Ok(LineIndex {
+        modified,
+        len,
+        total_lines: count,
+        checkpoints,
+    })
+}
+
+fn cached_line_index(path: &Path, metadata: &fs::Metadata) -> Result<Arc<LineIndex>, String> {
+    let modified = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+    let len = metadata.len();
+
+    if let Ok(mut cache) = LINE_INDEXES.lock() {
+        if let Some(pos) = cache.iter().position(|(p, _)| p == path) {
+            let (cached_path, index) = cache.remove(pos);
+            if index.modified == modified && index.len == len {
+                cache.push((cached_path, index.clone()));
+                return Ok(index);
+            }
+        }
+    }
+
+    let index = Arc::new(build_line_index(path, modified, len)?);
+    if let Ok(mut cache) = LINE_INDEXES.lock() {
+        cache.retain(|(p, _)| p != path);
+        if cache.len() >= MAX_LINE_INDEXES {
+            cache.remove(0);
+        }
+        cache.push((path.to_path_buf(), index.clone()));
+    }
+    Ok(index)
+}
+
+/// Read the window by seeking to the nearest indexed line at or before
+/// `from_line`, so later pages cost no more than the first.
+fn read_line_window(path: &Path, from_line: usize, max_lines: usize) -> Result<FileLines, String> {
+    let metadata = fs::metadata(path).map_err(|e| format!("Failed to read file: {}", e))?;
+    let index = cached_line_index(path, &metadata)?;
 
-    match fs::write(&expanded_path, content) {
-        Ok(()) => Ok(()),
-        Err(e) => Err(format!("Failed to write file: {}", e)),
+    let mut lines = Vec::new();
+    if from_line < index.total_lines {
+        let checkpoint = (from_line / LINE_INDEX_STRIDE).min(index.checkpoints.len() - 1);
+        let mut file = fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+        file.seek(SeekFrom::Start(index.checkpoints[checkpoint]))
+            .map_err(|e| format!("Failed to read file: {}", e))?;
+        let mut reader = BufReader::new(file);
+        let mut buf = Vec::new();
+        let mut line_no = checkpoint * LINE_INDEX_STRIDE;
+        while lines.len() < max_lines {
+            buf.clear();
+            let n = reader
+                .read_until(b'\n', &mut buf)
+                .map_err(|e| format!("Failed to read file: {}", e))?;
+            if n == 0 {
+                break;
+            }
+            if line_no >= from_line {
+                let line = buf.strip_suffix(b"\n").unwrap_or(&buf);
+                let line = line.strip_suffix(b"\r").unwrap_or(line);
+                lines.push(String::from_utf8_lossy(line).into_owned());
+            }
+            line_no += 1;
+        }
     }
+
+    Ok(FileLines {
+        lines,
+        from_line,
+        total_lines: index.total_lines,
+    })
+}
+
+/// Read up to `max_lines` lines starting at zero-based `from_line`, without the
+/// size limit of `read_file`. `total_lines` is cached until the file changes.
+#[tauri::command]
+pub async fn stream_file_lines(
+    state: State<'_, CodexState>,
+    file_path: String,
+    from_line: usize,
+    max_lines: Option<usize>,
+) -> Result<FileLines, String> {
+    let expanded_path = resolve_readable_file(&file_path)?;
+    ensure_within_roots(&expanded_path, &state.allowed_roots()).map_err(|e| e.to_string())?;
+
+    let max_lines = max_lines
+        .unwrap_or(DEFAULT_STREAM_MAX_LINES)
+        .clamp(1, MAX_STREAM_LINES);
+
+    tokio::task::spawn_blocking(move || read_line_window(&expanded_path, from_line, max_lines))
+        .await
+        .map_err(|e| format!("Failed to read file: {}", e))?
+}
+
+/// Read `line_count` lines from zero-based `start_line` for virtual scrolling of
+/// files too big for `read_file`. Same window as `stream_file_lines`, but the
+/// count is required and capped at 10,000 lines.
+#[tauri::command]
+pub async fn read_file_range(
+    state: State<'_, CodexState>,
+    file_path: String,
+    start_line: usize,
+    line_count: usize,
+) -> Result<FileChunk, String> {
+    let expanded_path = resolve_readable_file(&file_path)?;
+    ensure_within_roots(&expanded_path, &state.allowed_roots()).map_err(|e| e.to_string())?;
+    let line_count = line_count.clamp(1, MAX_STREAM_LINES);
+
+    let window = tokio::task::spawn_blocking(move || {
+        read_line_window(&expanded_path, start_line, line_count)
+    })
+    .await
+    .map_err(|e| format!("Failed to read file: {}", e))??;
+    Ok(FileChunk {
+        lines: window.lines,
+        start_line: window.from_line,
+        total_lines: window.total_lines,
+    })
 }
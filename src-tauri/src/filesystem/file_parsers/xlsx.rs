@@ -1,10 +1,11 @@
+use crate::utils::file::user_home;
 use calamine::{open_workbook, Data, Reader as CalamineReader, Xlsx};
 use std::path::Path;
 
 #[tauri::command]
 pub async fn read_xlsx_content(file_path: String) -> Result<String, String> {
     let expanded_path = if file_path.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
+        let home = user_home()?;
         home.join(&file_path[2..])
     } else {
         Path::new(&file_path).to_path_buf()
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileEntry {
@@ -7,6 +8,65 @@ pub struct FileEntry {
     pub is_directory: bool,
     pub size: Option<u64>,
     pub extension: Option<String>,
+    /// Last modification time in Unix millis, when the platform reports one.
+    #[serde(default)]
+    pub modified: Option<i64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ListDirectoryOptions {
+    /// Leave out entries whose name starts with a dot.
+    #[serde(default)]
+    pub skip_hidden: bool,
+    /// Also list the contents of subdirectories, down to `max_depth` levels.
+    #[serde(default)]
+    pub recursive: bool,
+    #[serde(default)]
+    pub max_depth: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileContent {
+    pub content: String,
+    /// WHATWG name of the encoding the file was decoded with, e.g. "UTF-8" or "windows-1252".
+    pub encoding: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DecodedFileContent {
+    pub content: String,
+    /// WHATWG name of the detected encoding.
+    pub encoding: String,
+    /// Some bytes were invalid in that encoding and replaced, so re-saving won't round-trip.
+    pub lossy: bool,
+    pub has_bom: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct DirectoryStats {
+    pub total_bytes: u64,
+    pub file_count: u64,
+    pub dir_count: u64,
+    /// Some directories sat at the depth cap and their contents weren't counted.
+    pub depth_limited: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CodexHomeUsage {
+    pub total_bytes: u64,
+    pub sessions_bytes: u64,
+    pub history_bytes: u64,
+    pub logs_bytes: u64,
+    pub auth_bytes: u64,
+    /// Everything else under `~/.codex` (config, prompts, caches, ...).
+    pub other_bytes: u64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ContentDiff {
+    pub unified_diff: String,
+    pub has_changes: bool,
+    pub is_new_file: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -15,3 +75,73 @@ pub struct GitDiff {
     pub current_content: String,
     pub has_changes: bool,
 }
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SkippedFile {
+    pub path: String,
+    /// One of "too_large", "binary", "total_limit" or "unreadable".
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GlobReadResult {
+    pub files: BTreeMap<String, String>,
+    pub skipped: Vec<SkippedFile>,
+    /// More files matched than `max_files` allowed.
+    pub truncated: bool,
+    /// `stop_on_error` was set and the read stopped at the last `skipped` entry.
+    pub aborted: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileLines {
+    pub lines: Vec<String>,
+    /// Zero-based index of the first entry in `lines`.
+    pub from_line: usize,
+    pub total_lines: usize,
+}
+
+/// Lines returned by `read_file_range`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FileChunk {
+    pub lines: Vec<String>,
+    /// Zero-based index of the first entry in `lines`.
+    pub start_line: usize,
+    pub total_lines: usize,
+}
+
+/// Error returned by the file read and write commands. Serialized as
+/// `{ kind, message, ... }` so the frontend can tell a path refused by the
+/// allowed-roots list from one that doesn't exist.
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum FileAccessError {
+    NotFound {
+        path: String,
+        message: String,
+    },
+    /// The canonical path lies outside every configured allowed root.
+    OutsideAllowedRoots {
+        path: String,
+        message: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl std::fmt::Display for FileAccessError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FileAccessError::NotFound { message, .. }
+            | FileAccessError::OutsideAllowedRoots { message, .. }
+            | FileAccessError::Failed { message } => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for FileAccessError {
+    fn from(message: String) -> Self {
+        FileAccessError::Failed { message }
+    }
+}
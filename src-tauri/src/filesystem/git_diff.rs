@@ -1,4 +1,5 @@
 use super::file_types::GitDiff;
+use crate::utils::file::user_home;
 use std::fs;
 use std::path::Path;
 use std::process::Command;
@@ -6,7 +7,7 @@ use std::process::Command;
 #[tauri::command]
 pub async fn get_git_file_diff(file_path: String) -> Result<GitDiff, String> {
     let expanded_path = if file_path.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
+        let home = user_home()?;
         home.join(&file_path[2..])
     } else {
         Path::new(&file_path).to_path_buf()
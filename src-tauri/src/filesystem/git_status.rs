@@ -1,3 +1,4 @@
+use crate::utils::file::user_home;
 use serde::{Deserialize, Serialize};
 use std::path::Path;
 use std::process::Command;
@@ -15,7 +16,7 @@ pub struct GitStatus {
 #[tauri::command]
 pub async fn get_git_status(directory: String) -> Result<GitStatus, String> {
     let expanded_path = if directory.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
+        let home = user_home()?;
         home.join(&directory[2..])
     } else {
         Path::new(&directory).to_path_buf()
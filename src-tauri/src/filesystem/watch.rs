@@ -1,18 +1,35 @@
-use crate::state::CodexState;
-use notify::{recommended_watcher, Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use crate::services::session::normalize_session_id;
+use crate::state::{CodexState, SessionFileWatch, SessionsWatch};
+use crate::utils::file::user_home;
+use crate::utils::watcher::{create_watcher, BoxedWatcher};
+use notify::{Event, EventKind, RecursiveMode};
 use serde::Serialize;
+use std::io::{Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
 use tauri::{AppHandle, Emitter, State};
 
+/// Writes landing within this window are reported as one `session-file-appended` event.
+const APPEND_DEBOUNCE: Duration = Duration::from_millis(250);
+
 #[derive(Serialize, Debug, Clone)]
 pub struct FsChangePayload {
     pub path: String,
     pub kind: String,
 }
 
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionFileAppendedPayload {
+    pub file_path: String,
+    pub session_id: Option<String>,
+    /// Complete JSONL lines written since the last event.
+    pub lines: Vec<String>,
+}
+
 fn expand_path(input: &str) -> Result<PathBuf, String> {
     if input.starts_with("~/") {
-        let home = dirs::home_dir().ok_or_else(|| "Cannot find home directory".to_string())?;
+        let home = user_home()?;
         Ok(home.join(&input[2..]))
     } else {
         Ok(Path::new(input).to_path_buf())
@@ -61,7 +78,7 @@ pub async fn start_watch_directory(
 
     let app_for_cb = app.clone();
     // Create a new watcher with a callback that emits tauri event
-    let mut watcher: RecommendedWatcher = recommended_watcher(move |res: Result<Event, notify::Error>| {
+    let mut watcher = create_watcher(move |res: Result<Event, notify::Error>| {
         if let Ok(event) = res {
             // Send one event per affected path
             for p in event.paths.iter() {
@@ -108,3 +125,232 @@ pub async fn stop_watch_directory(
     }
     Ok(())
 }
+
+fn create_sessions_watcher(
+    app: AppHandle,
+    sessions_path: &Path,
+    sessions_watch: &SessionsWatch,
+) -> Result<BoxedWatcher, String> {
+    std::fs::create_dir_all(sessions_path)
+        .map_err(|e| format!("Failed to create sessions directory: {}", e))?;
+
+    let paused = sessions_watch.paused.clone();
+    let missed_events = sessions_watch.missed_events.clone();
+    let mut watcher = create_watcher(move |res: Result<Event, notify::Error>| {
+        if let Ok(event) = res {
+            if paused.load(Ordering::SeqCst) {
+                missed_events.store(true, Ordering::SeqCst);
+                return;
+            }
+            for p in event.paths.iter() {
+                let payload = FsChangePayload {
+                    path: p.to_string_lossy().to_string(),
+                    kind: kind_to_string(&event.kind),
+                };
+                let _ = app.emit("sessions-changed", &payload);
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+
+    watcher
+        .watch(sessions_path, RecursiveMode::Recursive)
+        .map_err(|e| format!("Failed to start watcher: {}", e))?;
+
+    Ok(watcher)
+}
+
+/// Watch the codex sessions directory and emit `sessions-changed` for each change.
+#[tauri::command]
+pub async fn start_sessions_watch(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+) -> Result<(), String> {
+    let mut sessions_watch = state.sessions_watch.lock().await;
+    if sessions_watch.watcher.is_some() {
+        return Ok(());
+    }
+
+    let watcher = create_sessions_watcher(app, &state.sessions_path()?, &sessions_watch)?;
+    sessions_watch.watcher = Some(watcher);
+    Ok(())
+}
+
+/// Move an active sessions watch to the current sessions path, e.g. after relocation.
+pub async fn repoint_sessions_watch(app: AppHandle, state: &CodexState) -> Result<(), String> {
+    let mut sessions_watch = state.sessions_watch.lock().await;
+    if sessions_watch.watcher.is_none() {
+        return Ok(());
+    }
+
+    sessions_watch.watcher = None;
+    let watcher = create_sessions_watcher(app, &state.sessions_path()?, &sessions_watch)?;
+    sessions_watch.watcher = Some(watcher);
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn stop_sessions_watch(state: State<'_, CodexState>) -> Result<(), String> {
+    let mut sessions_watch = state.sessions_watch.lock().await;
+    sessions_watch.watcher = None;
+    sessions_watch.paused.store(false, Ordering::SeqCst);
+    sessions_watch.missed_events.store(false, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Stop emitting `sessions-changed` (e.g. during bulk imports) without dropping the watch.
+#[tauri::command]
+pub async fn pause_sessions_watch(state: State<'_, CodexState>) -> Result<(), String> {
+    let sessions_watch = state.sessions_watch.lock().await;
+    sessions_watch.paused.store(true, Ordering::SeqCst);
+    Ok(())
+}
+
+/// Resume emitting events. Anything that changed while paused is reported as a
+/// single `sessions-changed` event of kind "refresh" for the sessions directory.
+#[tauri::command]
+pub async fn resume_sessions_watch(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+) -> Result<(), String> {
+    let sessions_watch = state.sessions_watch.lock().await;
+    sessions_watch.paused.store(false, Ordering::SeqCst);
+
+    if sessions_watch.missed_events.swap(false, Ordering::SeqCst) {
+        let payload = FsChangePayload {
+            path: state.sessions_path()?.to_string_lossy().to_string(),
+            kind: "refresh".into(),
+        };
+        app.emit("sessions-changed", &payload)
+            .map_err(|e| format!("Failed to emit sessions-changed: {}", e))?;
+    }
+    Ok(())
+}
+
+/// Complete lines appended to `path` past `offset`, advancing `offset` to the end of
+/// the last full line. A file that shrank (rewritten or truncated) is read from the start.
+fn read_appended_lines(path: &Path, offset: &mut u64) -> Result<Vec<String>, String> {
+    let mut file = std::fs::File::open(path).map_err(|e| format!("Failed to open file: {}", e))?;
+    let len = file
+        .metadata()
+        .map_err(|e| format!("Failed to read file: {}", e))?
+        .len();
+    if len < *offset {
+        *offset = 0;
+    }
+    if len == *offset {
+        return Ok(Vec::new());
+    }
+
+    let mut buf = Vec::new();
+    file.seek(SeekFrom::Start(*offset))
+        .and_then(|_| file.read_to_end(&mut buf))
+        .map_err(|e| format!("Failed to read file: {}", e))?;
+    // A line still being written is picked up on the next change
+    let Some(end) = buf.iter().rposition(|&b| b == b'\n') else {
+        return Ok(Vec::new());
+    };
+    *offset += end as u64 + 1;
+
+    Ok(String::from_utf8_lossy(&buf[..end])
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(|line| line.to_string())
+        .collect())
+}
+
+/// Follow a rollout file that may still be written by codex and emit
+/// `session-file-appended` with the new lines. Only lines added after this call are reported.
+#[tauri::command]
+pub async fn watch_session_file(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    file_path: String,
+) -> Result<(), String> {
+    let path = std::fs::canonicalize(expand_path(&file_path)?)
+        .map_err(|e| format!("Session file does not exist: {}", e))?;
+    if !path.is_file() {
+        return Err("Session file does not exist or is a directory".to_string());
+    }
+    let key = path.to_string_lossy().to_string();
+
+    let mut watchers = state.session_file_watchers.lock().await;
+    if watchers.contains_key(&key) {
+        return Ok(());
+    }
+
+    let (tx, mut rx) = tokio::sync::mpsc::unbounded_channel::<()>();
+    let mut watcher = create_watcher(move |res: Result<Event, notify::Error>| {
+        if let Ok(event) = res {
+            if matches!(event.kind, EventKind::Modify(_) | EventKind::Create(_)) {
+                let _ = tx.send(());
+            }
+        }
+    })
+    .map_err(|e| format!("Failed to create watcher: {}", e))?;
+    watcher
+        .watch(&path, RecursiveMode::NonRecursive)
+        .map_err(|e| format!("Failed to start watcher: {}", e))?;
+
+    let session_id = normalize_session_id(&key).ok();
+    let mut offset = std::fs::metadata(&path).map(|m| m.len()).unwrap_or(0);
+    let payload_session_id = session_id.clone();
+    let tail_path = path.clone();
+    // Ends once the watcher, and with it the sender, is dropped
+    tokio::spawn(async move {
+        while rx.recv().await.is_some() {
+            tokio::time::sleep(APPEND_DEBOUNCE).await;
+            while rx.try_recv().is_ok() {}
+
+            match read_appended_lines(&tail_path, &mut offset) {
+                Ok(lines) if !lines.is_empty() => {
+                    let payload = SessionFileAppendedPayload {
+                        file_path: tail_path.to_string_lossy().to_string(),
+                        session_id: payload_session_id.clone(),
+                        lines,
+                    };
+                    if let Err(e) = app.emit("session-file-appended", &payload) {
+                        log::error!("Failed to emit session-file-appended: {}", e);
+                    }
+                }
+                Ok(_) => {}
+                Err(e) => log::warn!("Failed to tail {}: {}", tail_path.display(), e),
+            }
+        }
+    });
+
+    watchers.insert(
+        key,
+        SessionFileWatch {
+            _watcher: watcher,
+            session_id,
+        },
+    );
+    Ok(())
+}
+
+#[tauri::command]
+pub async fn unwatch_session_file(
+    state: State<'_, CodexState>,
+    file_path: String,
+) -> Result<(), String> {
+    let abs = expand_path(&file_path)?;
+    let key = std::fs::canonicalize(&abs)
+        .unwrap_or(abs)
+        .to_string_lossy()
+        .to_string();
+    state.session_file_watchers.lock().await.remove(&key);
+    Ok(())
+}
+
+/// Stop tailing the rollout files of `session_id`, e.g. when the session closes.
+pub async fn unwatch_session_files_for(state: &CodexState, session_id: &str) {
+    let Ok(id) = normalize_session_id(session_id) else {
+        return;
+    };
+    state
+        .session_file_watchers
+        .lock()
+        .await
+        .retain(|_, watch| watch.session_id.as_deref() != Some(id.as_str()));
+}
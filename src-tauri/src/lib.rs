@@ -5,14 +5,30 @@ mod filesystem;
 mod mcp;
 mod protocol;
 mod services;
+mod settings;
 mod state;
 mod utils;
 
 use commands::{
-    approve_execution, approve_patch, check_codex_version, close_session, delete_session_file,
-    find_rollout_path_for_session, get_latest_session_id, get_running_sessions, get_session_files,
-    load_sessions_from_disk, pause_session, read_history_file, read_session_file, send_message,
-    start_codex_session,
+    approve_all_pending, approve_execution, approve_patch, approve_patch_files,
+    archive_sessions_older_than, cancel_session_load, check_codex_auth, check_codex_version,
+    clear_history, clear_message_queue, clear_session_output, close_session, delete_session_file,
+    delete_sessions, diagnose, disable_raw_event_debug, enable_raw_event_debug,
+    export_approval_log_csv, export_session_markdown, find_duplicate_sessions,
+    find_rollout_path_for_session, fork_session, get_effective_config, get_global_stats,
+    get_latest_session_id, get_pending_patch, get_queued_messages, get_running_sessions,
+    get_session_files, get_session_output, get_session_runtime, get_session_stats,
+    get_session_status, get_session_usage, interrupt_turn, invalidate_codex_path_cache,
+    list_all_pending_approvals, list_archived_sessions, list_available_models, list_codex_binaries,
+    list_sessions_by_tag, list_sessions_for_cwd, load_session, load_sessions_from_disk,
+    load_sessions_page, locate_codex, merge_sessions, normalize_session_id, pause_session,
+    preview_start_session, read_approval_log, read_history_file, read_session_file,
+    read_session_metadata, read_session_parsed, rebuild_session_index, relocate_sessions_store,
+    rename_session, repair_session_metadata, reset_all_sessions, restore_archived_session,
+    resume_session, retry_last_turn, run_command, search_history, search_in_session,
+    search_sessions, send_message, send_message_with_model, set_allowed_roots, set_codex_home,
+    set_read_only, set_session_limit, set_session_tags, shutdown_all_sessions, start_codex_session,
+    truncate_session, validate_codex_binary, validate_session_file,
 };
 use config::{
     add_or_update_model_provider, add_or_update_profile, delete_profile, ensure_default_providers,
@@ -21,16 +37,30 @@ use config::{
     update_profile_model,
 };
 use filesystem::{
-    directory_ops::{get_default_directories, read_directory, search_files, canonicalize_path},
-    file_analysis::calculate_file_tokens,
-    file_io::{read_file, write_file},
+    directory_ops::{
+        available_disk_space, canonicalize_path, codex_home_disk_usage, directory_stats,
+        get_default_directories, list_directory, read_directory, search_files,
+    },
+    file_analysis::{calculate_file_tokens, count_tokens},
+    file_io::{
+        append_file, diff_against_file, read_file, read_file_lossy, read_file_range,
+        read_files_glob, stream_file_lines, write_file,
+    },
     file_parsers::{csv::read_csv_content, pdf::read_pdf_content, xlsx::read_xlsx_content},
     git_diff::get_git_file_diff,
     git_status::get_git_status,
-    watch::{start_watch_directory, stop_watch_directory},
+    watch::{
+        pause_sessions_watch, resume_sessions_watch, start_sessions_watch, start_watch_directory,
+        stop_sessions_watch, stop_watch_directory, unwatch_session_file, watch_session_file,
+    },
 };
 use mcp::{add_mcp_server, delete_mcp_server, read_mcp_servers};
+use settings::{
+    get_default_approval_policy, read_app_settings, set_default_approval_policy,
+    update_app_settings,
+};
 use state::CodexState;
+use tauri::Manager;
 
 #[cfg_attr(mobile, tauri::mobile_entry_point)]
 pub fn run() {
@@ -59,27 +89,102 @@ pub fn run() {
         .manage(CodexState::new())
         .invoke_handler(tauri::generate_handler![
             start_codex_session,
+            resume_session,
+            preview_start_session,
+            diagnose,
             send_message,
+            get_queued_messages,
+            clear_message_queue,
+            send_message_with_model,
             approve_execution,
             approve_patch,
+            approve_patch_files,
+            approve_all_pending,
+            list_all_pending_approvals,
+            get_pending_patch,
+            read_approval_log,
+            export_approval_log_csv,
+            export_session_markdown,
             pause_session,
+            interrupt_turn,
+            retry_last_turn,
             close_session,
+            reset_all_sessions,
+            shutdown_all_sessions,
+            enable_raw_event_debug,
+            disable_raw_event_debug,
+            get_session_output,
+            clear_session_output,
+            run_command,
             get_running_sessions,
+            get_session_runtime,
+            get_session_status,
+            get_session_usage,
+            get_effective_config,
+            set_read_only,
+            set_allowed_roots,
+            set_codex_home,
+            set_session_limit,
             load_sessions_from_disk,
+            load_session,
+            rebuild_session_index,
+            load_sessions_page,
+            cancel_session_load,
+            relocate_sessions_store,
+            list_sessions_for_cwd,
+            set_session_tags,
+            rename_session,
+            list_sessions_by_tag,
+            search_sessions,
+            search_in_session,
+            get_session_stats,
+            get_global_stats,
             delete_session_file,
+            delete_sessions,
+            archive_sessions_older_than,
+            list_archived_sessions,
+            restore_archived_session,
+            find_duplicate_sessions,
+            repair_session_metadata,
+            truncate_session,
+            merge_sessions,
+            fork_session,
+            validate_session_file,
+            read_session_metadata,
+            read_session_parsed,
             get_latest_session_id,
             get_session_files,
             read_session_file,
             read_history_file,
+            search_history,
+            clear_history,
             find_rollout_path_for_session,
+            normalize_session_id,
             check_codex_version,
+            validate_codex_binary,
+            list_codex_binaries,
+            locate_codex,
+            invalidate_codex_path_cache,
+            check_codex_auth,
+            list_available_models,
             read_directory,
+            list_directory,
             get_default_directories,
             search_files,
             canonicalize_path,
+            directory_stats,
+            codex_home_disk_usage,
+            available_disk_space,
             calculate_file_tokens,
+            count_tokens,
             read_file,
+            read_file_lossy,
+            read_files_glob,
+            stream_file_lines,
+            read_file_range,
             write_file,
+            append_file,
+            diff_against_file,
             read_pdf_content,
             read_csv_content,
             read_xlsx_content,
@@ -87,6 +192,12 @@ pub fn run() {
             get_git_status,
             start_watch_directory,
             stop_watch_directory,
+            watch_session_file,
+            unwatch_session_file,
+            start_sessions_watch,
+            stop_sessions_watch,
+            pause_sessions_watch,
+            resume_sessions_watch,
             read_codex_config,
             get_project_name,
             is_version_controlled,
@@ -103,13 +214,27 @@ pub fn run() {
             delete_profile,
             add_or_update_model_provider,
             ensure_default_providers,
+            read_app_settings,
+            update_app_settings,
+            get_default_approval_policy,
+            set_default_approval_policy,
         ])
-        .setup(|_app| {
-            tauri::async_runtime::spawn(async {
-                let _ = ensure_default_providers().await;
+        .setup(|app| {
+            let codex_home = app.state::<CodexState>().codex_home();
+            tauri::async_runtime::spawn(async move {
+                if let Ok(codex_home) = codex_home {
+                    let _ = config::add_default_providers(&codex_home);
+                }
             });
             Ok(())
         })
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while building tauri application")
+        .run(|app, event| {
+            // Don't leave codex processes behind when the app quits
+            if let tauri::RunEvent::Exit = event {
+                let state = app.state::<CodexState>();
+                tauri::async_runtime::block_on(services::codex::shutdown_all_sessions(&state));
+            }
+        });
 }
@@ -1,9 +1,10 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
-use tauri::command;
+use tauri::{command, State};
 
 use crate::config::{get_config_path, CodexConfig};
+use crate::state::CodexState;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type")]
@@ -20,8 +21,10 @@ pub enum McpServerConfig {
 }
 
 #[command]
-pub async fn read_mcp_servers() -> Result<HashMap<String, McpServerConfig>, String> {
-    let config_path = get_config_path()?;
+pub async fn read_mcp_servers(
+    state: State<'_, CodexState>,
+) -> Result<HashMap<String, McpServerConfig>, String> {
+    let config_path = get_config_path(&state.codex_home()?);
 
     if !config_path.exists() {
         return Ok(HashMap::new());
@@ -37,8 +40,12 @@ pub async fn read_mcp_servers() -> Result<HashMap<String, McpServerConfig>, Stri
 }
 
 #[command]
-pub async fn add_mcp_server(name: String, config: McpServerConfig) -> Result<(), String> {
-    let config_path = get_config_path()?;
+pub async fn add_mcp_server(
+    state: State<'_, CodexState>,
+    name: String,
+    config: McpServerConfig,
+) -> Result<(), String> {
+    let config_path = get_config_path(&state.codex_home()?);
 
     let mut codex_config: CodexConfig = if config_path.exists() {
         let content = fs::read_to_string(&config_path)
@@ -70,8 +77,8 @@ pub async fn add_mcp_server(name: String, config: McpServerConfig) -> Result<(),
 }
 
 #[command]
-pub async fn delete_mcp_server(name: String) -> Result<(), String> {
-    let config_path = get_config_path()?;
+pub async fn delete_mcp_server(state: State<'_, CodexState>, name: String) -> Result<(), String> {
+    let config_path = get_config_path(&state.codex_home()?);
 
     if !config_path.exists() {
         return Err("Config file does not exist".to_string());
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::path::PathBuf;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -26,6 +27,11 @@ pub enum Op {
     UserInput {
         items: Vec<InputItem>,
     },
+    /// Change the context used for later turns; omitted fields keep their value.
+    OverrideTurnContext {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        model: Option<String>,
+    },
     Interrupt,
     ExecApproval {
         id: String,
@@ -225,12 +231,157 @@ pub struct CodexConfig {
     pub provider: String,
     pub use_oss: bool,
     pub custom_args: Option<Vec<String>>, 
+    /// Empty falls back to the default approval policy in settings.
+    #[serde(default)]
     pub approval_policy: String,
     pub sandbox_mode: String,
+    /// Typed sandbox choice; takes precedence over `sandbox_mode` when set.
+    #[serde(default)]
+    pub sandbox_policy: Option<SandboxMode>,
+    /// Binary to launch instead of the discovered one, e.g. a nightly build.
+    /// Also accepted as `binary_path`.
+    #[serde(default, alias = "binary_path")]
     pub codex_path: Option<String>,
     pub api_key: Option<String>,
-    pub reasoning_effort: Option<String>,
+    /// Reasoning effort for models that support it; `None` leaves codex's default.
+    pub reasoning_effort: Option<ReasoningEffort>,
     pub resume_path: Option<String>,
     #[serde(default)]
     pub tools_web_search: Option<bool>,
+    /// Extra instructions passed to codex for this session only.
+    #[serde(default)]
+    pub instructions: Option<String>,
+    /// Project root for this session, overriding `working_directory`. `~/` is
+    /// expanded and the path must be an existing directory.
+    #[serde(default)]
+    pub cwd: Option<String>,
+    /// Rules for answering exec approval requests without prompting.
+    #[serde(default)]
+    pub auto_approve: Vec<ApprovalRule>,
+    /// Answer an approval request nobody responded to after this many seconds.
+    #[serde(default)]
+    pub approval_timeout_secs: Option<u64>,
+    /// Decision sent when `approval_timeout_secs` runs out: approve when true, deny otherwise.
+    #[serde(default)]
+    pub default_on_timeout: bool,
+    /// Respawn codex and resume the rollout when the process exits without being closed.
+    #[serde(default)]
+    pub auto_restart: bool,
+    /// Extra environment variables for the codex process. They are applied last,
+    /// so a key that is also inherited, or set for the API key or `PATH`, takes
+    /// the value given here.
+    #[serde(default)]
+    pub env: Option<HashMap<String, String>>,
+}
+
+/// Sandbox codex runs commands under, passed as `-c sandbox_mode=...`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SandboxMode {
+    /// Commands may read anything but write nothing; mutating commands are denied.
+    ReadOnly,
+    WorkspaceWrite,
+    DangerFullAccess,
+}
+
+impl SandboxMode {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SandboxMode::ReadOnly => "read-only",
+            SandboxMode::WorkspaceWrite => "workspace-write",
+            SandboxMode::DangerFullAccess => "danger-full-access",
+        }
+    }
+
+    pub fn parse(mode: &str) -> Option<Self> {
+        match mode {
+            "read-only" => Some(SandboxMode::ReadOnly),
+            "workspace-write" => Some(SandboxMode::WorkspaceWrite),
+            "danger-full-access" => Some(SandboxMode::DangerFullAccess),
+            _ => None,
+        }
+    }
+}
+
+/// How long a reasoning model thinks before answering, passed as `-c model_reasoning_effort=...`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ReasoningEffort {
+    /// Only the gpt-5 family accepts this one.
+    Minimal,
+    Low,
+    Medium,
+    High,
+}
+
+impl ReasoningEffort {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ReasoningEffort::Minimal => "minimal",
+            ReasoningEffort::Low => "low",
+            ReasoningEffort::Medium => "medium",
+            ReasoningEffort::High => "high",
+        }
+    }
+}
+
+impl CodexConfig {
+    /// Directory codex runs in: the per-session `cwd` override, else `working_directory`.
+    pub fn effective_cwd(&self) -> &str {
+        self.cwd
+            .as_deref()
+            .filter(|c| !c.trim().is_empty())
+            .unwrap_or(&self.working_directory)
+    }
+
+    /// `sandbox_policy`, else `sandbox_mode` with unknown values falling back to
+    /// workspace-write. `None` leaves the choice to codex.
+    pub fn effective_sandbox(&self) -> Option<SandboxMode> {
+        if let Some(policy) = self.sandbox_policy {
+            return Some(policy);
+        }
+        if self.sandbox_mode.is_empty() {
+            return None;
+        }
+        Some(SandboxMode::parse(&self.sandbox_mode).unwrap_or(SandboxMode::WorkspaceWrite))
+    }
+
+    /// Copy safe to log, with the API key and `env` values masked.
+    pub fn redacted(&self) -> Self {
+        let mut config = self.clone();
+        config.api_key = config.api_key.map(|_| "***".to_string());
+        if let Some(env) = &mut config.env {
+            env.values_mut()
+                .for_each(|value| *value = "***".to_string());
+        }
+        config
+    }
+}
+
+/// How an `ApprovalRule` pattern is interpreted.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalPatternKind {
+    /// Shell-style wildcards matched against the whole command line, e.g. `cargo build*`.
+    #[default]
+    Glob,
+    /// Regular expression searched in the command line; anchor it to match the whole line.
+    Regex,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalRuleAction {
+    /// Approve matching commands automatically.
+    Allow,
+    /// Always ask for matching commands, even when an allow rule matches too.
+    Deny,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalRule {
+    pub pattern: String,
+    #[serde(default)]
+    pub kind: ApprovalPatternKind,
+    pub action: ApprovalRuleAction,
 }
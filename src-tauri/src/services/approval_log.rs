@@ -0,0 +1,126 @@
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+
+use crate::utils::file::default_codex_home;
+
+/// One approval decision. Stored as a JSON line in `~/.codex/codexia_approvals.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalLogEntry {
+    /// RFC 3339 time the decision was sent.
+    pub timestamp: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "approvalId")]
+    pub approval_id: String,
+    /// "exec" or "patch".
+    pub kind: String,
+    /// "approved" or "denied".
+    pub decision: String,
+    /// Decided without the user being asked.
+    pub auto: bool,
+    /// Command line for exec approvals, affected files for patches.
+    pub summary: String,
+}
+
+pub fn get_approval_log_path() -> Result<PathBuf, String> {
+    Ok(default_codex_home()?.join("codexia_approvals.jsonl"))
+}
+
+pub fn append_entry(entry: &ApprovalLogEntry) -> Result<(), String> {
+    let log_path = get_approval_log_path()?;
+    if let Some(parent) = log_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create approval log directory: {}", e))?;
+    }
+
+    let line = serde_json::to_string(entry)
+        .map_err(|e| format!("Failed to serialize approval log entry: {}", e))?;
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&log_path)
+        .map_err(|e| format!("Failed to open approval log: {}", e))?;
+    writeln!(file, "{}", line).map_err(|e| format!("Failed to write approval log: {}", e))
+}
+
+/// Entries in the order they were recorded, optionally for a single session.
+/// Lines that fail to parse are skipped.
+pub fn read_entries(session_id: Option<&str>) -> Result<Vec<ApprovalLogEntry>, String> {
+    let log_path = get_approval_log_path()?;
+    if !log_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file =
+        fs::File::open(&log_path).map_err(|e| format!("Failed to open approval log: {}", e))?;
+    let entries = BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<ApprovalLogEntry>(&line).ok())
+        .filter(|entry| session_id.is_none_or(|id| entry.session_id == id))
+        .collect();
+
+    Ok(entries)
+}
+
+/// Prefix `value` with `'` when it starts with a character spreadsheets read as
+/// a formula, so an exported command line can't run when the CSV is opened.
+fn csv_cell(value: &str) -> Cow<'_, str> {
+    if value.starts_with(['=', '+', '-', '@']) {
+        Cow::Owned(format!("'{}", value))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+/// Write the log as CSV to `dest_path`, returning the number of rows written.
+pub fn export_csv(dest_path: &Path, session_id: Option<&str>) -> Result<usize, String> {
+    let entries = read_entries(session_id)?;
+
+    let mut writer = csv::Writer::from_path(dest_path)
+        .map_err(|e| format!("Failed to create CSV file: {}", e))?;
+    writer
+        .write_record([
+            "timestamp",
+            "session",
+            "decision",
+            "auto",
+            "kind",
+            "summary",
+        ])
+        .map_err(|e| format!("Failed to write CSV header: {}", e))?;
+    for entry in &entries {
+        writer
+            .write_record([
+                csv_cell(&entry.timestamp).as_ref(),
+                csv_cell(&entry.session_id).as_ref(),
+                csv_cell(&entry.decision).as_ref(),
+                if entry.auto { "true" } else { "false" },
+                csv_cell(&entry.kind).as_ref(),
+                csv_cell(&entry.summary).as_ref(),
+            ])
+            .map_err(|e| format!("Failed to write CSV row: {}", e))?;
+    }
+    writer
+        .flush()
+        .map_err(|e| format!("Failed to write CSV file: {}", e))?;
+
+    Ok(entries.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn csv_cell_escapes_formula_prefixes() {
+        for value in ["=HYPERLINK(\"x\")", "+1", "-rf /", "@SUM(A1)"] {
+            assert_eq!(csv_cell(value), format!("'{}", value));
+        }
+        assert_eq!(csv_cell("git status"), "git status");
+        assert_eq!(csv_cell(""), "");
+    }
+}
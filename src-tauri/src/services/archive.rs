@@ -0,0 +1,250 @@
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use serde::Serialize;
+use std::fs;
+use std::io::{self, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+use walkdir::WalkDir;
+
+use crate::services::session::{canonical_sessions_root, normalize_session_id};
+use crate::utils::file::{
+    get_file_modification_time, is_compressed_rollout, scan_jsonl_files, sessions_path_in,
+};
+
+const SECONDS_PER_DAY: u64 = 24 * 60 * 60;
+
+#[derive(Debug, Serialize)]
+pub struct ArchiveSummary {
+    pub moved: usize,
+    /// Bytes freed in the sessions tree minus what the compressed copies take.
+    #[serde(rename = "bytesReclaimed")]
+    pub bytes_reclaimed: u64,
+    /// Files left in place, with the reason.
+    pub failed: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ArchivedSession {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    #[serde(rename = "sessionId")]
+    pub session_id: Option<String>,
+    #[serde(rename = "sizeBytes")]
+    pub size_bytes: u64,
+    /// Unix millis of the archived file's modification time.
+    #[serde(rename = "modifiedAt")]
+    pub modified_at: Option<i64>,
+    pub compressed: bool,
+}
+
+/// `<codex_home>/archive`, mirroring the `YYYY/MM/DD` layout of the sessions tree.
+pub fn archive_root(codex_home: &Path) -> PathBuf {
+    codex_home.join("archive")
+}
+
+fn is_archived_rollout(path: &Path) -> bool {
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy())
+        .unwrap_or_default();
+    name.ends_with(".jsonl") || name.ends_with(".jsonl.gz")
+}
+
+/// Write through `<dest>.tmp` and rename, so `dest` is either absent or complete.
+fn write_then_rename(
+    dest: &Path,
+    write: impl FnOnce(&mut fs::File) -> io::Result<()>,
+) -> Result<(), String> {
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent).map_err(|e| format!("Failed to create directory: {}", e))?;
+    }
+    let mut tmp_name = dest.as_os_str().to_owned();
+    tmp_name.push(".tmp");
+    let tmp = PathBuf::from(tmp_name);
+
+    let result = fs::File::create(&tmp).and_then(|mut file| {
+        write(&mut file)?;
+        file.sync_all()
+    });
+    if let Err(e) = result.and_then(|_| fs::rename(&tmp, dest)) {
+        let _ = fs::remove_file(&tmp);
+        return Err(format!("Failed to write '{}': {}", dest.display(), e));
+    }
+    Ok(())
+}
+
+/// Compress one rollout into the archive, removing the original only once the
+/// archived copy is complete. Returns the bytes reclaimed.
+fn archive_one(path: &Path, sessions_root: &Path, archive_root: &Path) -> Result<u64, String> {
+    let relative = path.strip_prefix(sessions_root).unwrap_or(path);
+    let mut dest_name = archive_root.join(relative).into_os_string();
+    dest_name.push(".gz");
+    let dest = PathBuf::from(dest_name);
+
+    let original_size = fs::metadata(path)
+        .map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?
+        .len();
+    write_then_rename(&dest, |file| {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        io::copy(&mut fs::File::open(path)?, &mut encoder)?;
+        encoder.finish()?.flush()
+    })?;
+    // An interruption before this line leaves both copies, never neither
+    fs::remove_file(path).map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+
+    let archived_size = fs::metadata(&dest).map(|m| m.len()).unwrap_or(0);
+    Ok(original_size.saturating_sub(archived_size))
+}
+
+/// Compress rollouts not modified for more than `days` days into the archive.
+pub fn archive_sessions_older_than(codex_home: &Path, days: u64) -> Result<ArchiveSummary, String> {
+    let sessions_root = canonical_sessions_root(&sessions_path_in(codex_home))?;
+    let archive_root = archive_root(codex_home);
+    let cutoff = SystemTime::now()
+        .checked_sub(Duration::from_secs(days.saturating_mul(SECONDS_PER_DAY)))
+        .unwrap_or(SystemTime::UNIX_EPOCH);
+
+    let mut summary = ArchiveSummary {
+        moved: 0,
+        bytes_reclaimed: 0,
+        failed: Vec::new(),
+    };
+    let stale: Vec<PathBuf> = scan_jsonl_files(&sessions_root)
+        .map(|entry| entry.into_path())
+        .filter(|path| get_file_modification_time(path).is_some_and(|modified| modified < cutoff))
+        .collect();
+    for path in stale {
+        match archive_one(&path, &sessions_root, &archive_root) {
+            Ok(reclaimed) => {
+                summary.moved += 1;
+                summary.bytes_reclaimed += reclaimed;
+            }
+            Err(e) => summary.failed.push(e),
+        }
+    }
+
+    log::info!(
+        "Archived {} sessions older than {} days, reclaimed {} bytes",
+        summary.moved,
+        days,
+        summary.bytes_reclaimed
+    );
+    Ok(summary)
+}
+
+/// Everything under the archive, compressed or not, newest first.
+pub fn list_archived_sessions(codex_home: &Path) -> Result<Vec<ArchivedSession>, String> {
+    let root = archive_root(codex_home);
+    if !root.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut sessions: Vec<ArchivedSession> = WalkDir::new(&root)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_archived_rollout(e.path()))
+        .map(|entry| {
+            let path = entry.path();
+            let metadata = entry.metadata().ok();
+            ArchivedSession {
+                file_path: path.to_string_lossy().to_string(),
+                session_id: normalize_session_id(&path.to_string_lossy().replace(".gz", "")).ok(),
+                size_bytes: metadata.as_ref().map_or(0, |m| m.len()),
+                modified_at: metadata
+                    .and_then(|m| m.modified().ok())
+                    .map(|t| chrono::DateTime::<chrono::Utc>::from(t).timestamp_millis()),
+                compressed: path.extension().is_some_and(|e| e == "gz"),
+            }
+        })
+        .collect();
+    sessions.sort_by_key(|s| std::cmp::Reverse(s.modified_at));
+    Ok(sessions)
+}
+
+/// Put an archived rollout back at its original place in the sessions tree and
+/// return that path. Refuses to overwrite an existing session.
+pub fn restore_archived_session(codex_home: &Path, file_path: &str) -> Result<String, String> {
+    let root = fs::canonicalize(archive_root(codex_home))
+        .map_err(|e| format!("Failed to resolve archive directory: {}", e))?;
+    let path = fs::canonicalize(file_path)
+        .map_err(|e| format!("Failed to resolve '{}': {}", file_path, e))?;
+    if !path.starts_with(&root) || !path.is_file() || !is_archived_rollout(&path) {
+        return Err(format!("'{}' is not an archived session", file_path));
+    }
+
+    let relative = path.strip_prefix(&root).unwrap_or(&path);
+    let compressed = path.extension().is_some_and(|e| e == "gz");
+    let mut dest = canonical_sessions_root(&sessions_path_in(codex_home))?.join(relative);
+    if compressed {
+        dest.set_extension("");
+    }
+    if dest.exists() {
+        return Err(format!("A session already exists at '{}'", dest.display()));
+    }
+
+    write_then_rename(&dest, |file| {
+        let mut source = fs::File::open(&path)?;
+        if compressed {
+            io::copy(&mut GzDecoder::new(source), file)?;
+        } else {
+            io::copy(&mut source, file)?;
+        }
+        Ok(())
+    })?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+
+    Ok(dest.to_string_lossy().to_string())
+}
+
+/// Gzip a rollout in place to `<rollout>.gz` and return the new path. The
+/// compressed copy is decompressed and compared with the original before the
+/// original is removed, so a bad write never costs the session.
+pub fn compress_rollout(path: &Path) -> Result<PathBuf, String> {
+    let original =
+        fs::read(path).map_err(|e| format!("Failed to read '{}': {}", path.display(), e))?;
+    let mut dest_name = path.as_os_str().to_owned();
+    dest_name.push(".gz");
+    let dest = PathBuf::from(dest_name);
+
+    write_then_rename(&dest, |file| {
+        let mut encoder = GzEncoder::new(file, Compression::default());
+        encoder.write_all(&original)?;
+        encoder.finish()?.flush()
+    })?;
+
+    let mut round_trip = Vec::with_capacity(original.len());
+    let verified = fs::File::open(&dest)
+        .and_then(|file| GzDecoder::new(file).read_to_end(&mut round_trip))
+        .is_ok_and(|_| round_trip == original);
+    if !verified {
+        let _ = fs::remove_file(&dest);
+        return Err(format!(
+            "Compressed copy of '{}' did not match the original",
+            path.display()
+        ));
+    }
+    fs::remove_file(path).map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+    Ok(dest)
+}
+
+/// Undo `compress_rollout` and return the plain `.jsonl` path. Refuses to
+/// overwrite a plain rollout that already exists next to it.
+pub fn decompress_rollout(path: &Path) -> Result<PathBuf, String> {
+    if !is_compressed_rollout(path) {
+        return Ok(path.to_path_buf());
+    }
+    let mut dest = path.to_path_buf();
+    dest.set_extension("");
+    if dest.exists() {
+        return Err(format!("A session already exists at '{}'", dest.display()));
+    }
+
+    write_then_rename(&dest, |file| {
+        io::copy(&mut GzDecoder::new(fs::File::open(path)?), file)?;
+        Ok(())
+    })?;
+    fs::remove_file(path).map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+    Ok(dest)
+}
@@ -0,0 +1,65 @@
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use base64::Engine;
+use serde::Serialize;
+use serde_json::Value;
+use std::fs;
+use std::path::Path;
+
+/// Login state as far as `~/.codex/auth.json` tells us. Token material is
+/// never logged or returned, only an account label.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status")]
+pub enum CodexAuthStatus {
+    LoggedIn { account: Option<String> },
+    LoggedOut,
+    Unknown,
+}
+
+/// Pull the `email` claim out of a JWT without verifying it; it's only used as a label.
+fn email_from_id_token(token: &str) -> Option<String> {
+    let claims = token.split('.').nth(1)?;
+    let decoded = URL_SAFE_NO_PAD.decode(claims.trim_end_matches('=')).ok()?;
+    let claims: Value = serde_json::from_slice(&decoded).ok()?;
+    claims.get("email")?.as_str().map(|s| s.to_string())
+}
+
+fn non_empty_str<'a>(value: &'a Value, key: &str) -> Option<&'a str> {
+    value.get(key)?.as_str().filter(|s| !s.is_empty())
+}
+
+pub async fn check_codex_auth(codex_home: &Path) -> Result<CodexAuthStatus, String> {
+    let auth_path = codex_home.join("auth.json");
+    if !auth_path.exists() {
+        return Ok(CodexAuthStatus::LoggedOut);
+    }
+
+    let content = match fs::read_to_string(&auth_path) {
+        Ok(content) => content,
+        Err(e) => {
+            log::warn!("Failed to read codex auth file: {}", e);
+            return Ok(CodexAuthStatus::Unknown);
+        }
+    };
+    let Ok(auth) = serde_json::from_str::<Value>(&content) else {
+        log::warn!("Codex auth file is not valid JSON");
+        return Ok(CodexAuthStatus::Unknown);
+    };
+
+    // ChatGPT sign-in stores OAuth tokens; API key sign-in stores OPENAI_API_KEY.
+    if let Some(tokens) = auth.get("tokens").filter(|t| t.is_object()) {
+        if non_empty_str(tokens, "access_token").is_some()
+            || non_empty_str(tokens, "refresh_token").is_some()
+        {
+            let account = non_empty_str(tokens, "id_token")
+                .and_then(email_from_id_token)
+                .or_else(|| non_empty_str(tokens, "account_id").map(|s| s.to_string()));
+            return Ok(CodexAuthStatus::LoggedIn { account });
+        }
+    }
+
+    if non_empty_str(&auth, "OPENAI_API_KEY").is_some() {
+        return Ok(CodexAuthStatus::LoggedIn { account: None });
+    }
+
+    Ok(CodexAuthStatus::LoggedOut)
+}
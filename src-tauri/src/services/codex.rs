@@ -1,9 +1,200 @@
-use crate::codex_client::CodexClient;
+use crate::codex_client::auto_approve::validate_rules;
+use crate::codex_client::{CapturedOutput, CodexClient};
+use crate::filesystem::watch::unwatch_session_files_for;
 use crate::protocol::CodexConfig;
-use crate::state::CodexState;
-use crate::utils::codex_discovery::discover_codex_command;
+use crate::services::approval_log::{self, ApprovalLogEntry};
+use crate::services::archive;
+use crate::services::models::model_availability_warning;
+use crate::services::patch::{build_preview, patch_files, PatchPreview};
+use crate::services::preflight::{
+    resolve_working_directory, validate_codex_path, validate_env, validate_instructions,
+    validate_model_selection,
+};
+use crate::services::{session, session_index};
+use crate::settings::load_settings;
+use crate::state::{
+    CodexState, PendingApproval, PendingPatch, SessionRuntime, SessionStatus, READ_ONLY_MESSAGE,
+};
+use crate::utils::codex_discovery::{
+    classify_codex_binary, discover_codex_command, invalidate_codex_path_cache,
+    locate_codex_candidates, CodexBinaryKind,
+};
+use crate::utils::file::is_compressed_rollout;
+use crate::utils::pricing::estimate_cost_usd;
+use serde::Serialize;
+use std::future::Future;
+use std::pin::Pin;
 use std::process::Command;
-use tauri::{AppHandle, State};
+use std::sync::atomic::Ordering;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager, State};
+use tokio::sync::MutexGuard;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct StateResetPayload {
+    pub closed_sessions: usize,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionHeartbeatPayload {
+    pub session_id: String,
+    /// "idle" or "generating".
+    pub status: String,
+    /// Milliseconds since codex last emitted an event.
+    pub idle_ms: i64,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionRuntimeInfo {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "startedAt")]
+    pub started_at: i64,
+    #[serde(rename = "elapsedMs")]
+    pub elapsed_ms: i64,
+    #[serde(rename = "turnsCompleted")]
+    pub turns_completed: u32,
+    /// Start of the turn currently generating, if any.
+    #[serde(rename = "turnStartedAt")]
+    pub turn_started_at: Option<i64>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PendingApprovalInfo {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "approvalId")]
+    pub approval_id: String,
+    /// "exec" or "patch".
+    pub kind: String,
+    pub summary: String,
+    #[serde(rename = "requestedAt")]
+    pub requested_at: i64,
+}
+
+/// Error returned by the session commands. Serialized as `{ kind, message, ... }`
+/// so the frontend can branch on `kind` and still show `message` as is.
+#[derive(Serialize, Debug, Clone)]
+#[serde(tag = "kind", rename_all = "camelCase")]
+pub enum CodexError {
+    /// No running codex process for this id, e.g. it exited or was never started.
+    SessionNotRunning {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        message: String,
+    },
+    /// Read-only mode is on, so nothing that runs codex or changes state is allowed.
+    ReadOnlyMode {
+        message: String,
+    },
+    /// The session is running but not generating, so there is nothing to interrupt.
+    NoActiveTurn {
+        #[serde(rename = "sessionId")]
+        session_id: String,
+        message: String,
+    },
+    /// No approval with this id is waiting in the session, e.g. it was already answered.
+    ApprovalNotPending {
+        #[serde(rename = "approvalId")]
+        approval_id: String,
+        message: String,
+    },
+    /// Only some files of a patch were approved, which codex can't apply.
+    /// The frontend should fall back to approving or denying the whole patch.
+    PatchSplitUnsupported {
+        #[serde(rename = "approvalId")]
+        approval_id: String,
+        message: String,
+    },
+    /// `max_concurrent_sessions` codex processes are already running.
+    TooManySessions {
+        limit: usize,
+        running: usize,
+        message: String,
+    },
+    Failed {
+        message: String,
+    },
+}
+
+impl CodexError {
+    pub fn not_running(session_id: &str) -> Self {
+        CodexError::SessionNotRunning {
+            session_id: session_id.to_string(),
+            message: format!("Session {} is not running", session_id),
+        }
+    }
+
+    /// Err while read-only mode is on.
+    pub fn ensure_writable(state: &CodexState) -> Result<(), Self> {
+        if state.is_read_only() {
+            Err(CodexError::ReadOnlyMode {
+                message: READ_ONLY_MESSAGE.to_string(),
+            })
+        } else {
+            Ok(())
+        }
+    }
+}
+
+impl std::fmt::Display for CodexError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            CodexError::SessionNotRunning { message, .. }
+            | CodexError::ReadOnlyMode { message }
+            | CodexError::NoActiveTurn { message, .. }
+            | CodexError::ApprovalNotPending { message, .. }
+            | CodexError::PatchSplitUnsupported { message, .. }
+            | CodexError::TooManySessions { message, .. }
+            | CodexError::Failed { message } => f.write_str(message),
+        }
+    }
+}
+
+impl From<String> for CodexError {
+    fn from(message: String) -> Self {
+        CodexError::Failed { message }
+    }
+}
+
+impl From<&str> for CodexError {
+    fn from(message: &str) -> Self {
+        CodexError::Failed {
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Emit `session-heartbeat` for a session until its runtime entry goes away.
+/// `started_at` identifies this run, so a restarted session id doesn't get two heartbeats.
+fn spawn_heartbeat(app: AppHandle, session_id: String, started_at: i64) {
+    let interval_secs = load_settings().heartbeat_interval_secs.max(1);
+    tokio::spawn(async move {
+        let mut interval = tokio::time::interval(std::time::Duration::from_secs(interval_secs));
+        interval.tick().await;
+        loop {
+            interval.tick().await;
+            let Some(state) = app.try_state::<CodexState>() else {
+                break;
+            };
+            let payload = {
+                let runtimes = state.runtimes.lock().await;
+                match runtimes.get(&session_id) {
+                    Some(runtime) if runtime.started_at == started_at => SessionHeartbeatPayload {
+                        session_id: session_id.clone(),
+                        status: runtime.status().to_string(),
+                        idle_ms: chrono::Utc::now().timestamp_millis() - runtime.last_activity_at,
+                    },
+                    _ => break,
+                }
+            };
+            if let Err(e) = app.emit("session-heartbeat", &payload) {
+                log::error!("Failed to emit session-heartbeat: {}", e);
+            }
+        }
+        log::debug!("Heartbeat stopped for session: {}", session_id);
+    });
+}
 
 // Note: Frontend now properly extracts raw session IDs before calling backend
 // so we no longer need complex ID normalization
@@ -12,9 +203,56 @@ pub async fn start_codex_session(
     app: AppHandle,
     state: State<'_, CodexState>,
     session_id: String,
-    config: CodexConfig,
-) -> Result<(), String> {
+    mut config: CodexConfig,
+) -> Result<(), CodexError> {
     log::debug!("Starting session with ID: {}", session_id);
+    CodexError::ensure_writable(&state)?;
+
+    // Codex only reads plain JSONL, so undo compress-on-close before resuming
+    if let Some(resume_path) = config
+        .resume_path
+        .as_deref()
+        .map(std::path::PathBuf::from)
+        .filter(|p| is_compressed_rollout(p))
+    {
+        let plain = tokio::task::spawn_blocking(move || archive::decompress_rollout(&resume_path))
+            .await
+            .map_err(|e| format!("Decompressing rollout failed: {}", e))??;
+        config.resume_path = Some(plain.to_string_lossy().to_string());
+    }
+
+    if let Some(instructions) = &config.instructions {
+        validate_instructions(instructions)?;
+    }
+    validate_rules(&config.auto_approve)?;
+    if let Some(env) = &config.env {
+        validate_env(env)?;
+    }
+    validate_model_selection(&config)?;
+    // Resolve the root up front so a bad path fails here, not inside the spawned process
+    if let Some(cwd) = config.cwd.take().filter(|c| !c.trim().is_empty()) {
+        config.working_directory = cwd;
+    }
+    if !config.working_directory.is_empty() {
+        config.working_directory = resolve_working_directory(&config.working_directory)?;
+    }
+    config.codex_path = config.codex_path.filter(|p| !p.trim().is_empty());
+    if let Some(path) = &config.codex_path {
+        validate_codex_path(path)?;
+    }
+    // Listing models can mean a network round trip; don't hold up the start for a warning
+    let model_config = config.clone();
+    let codex_home = state.codex_home().map_err(String::from)?;
+    tokio::spawn(async move {
+        if let Some(warning) = model_availability_warning(&codex_home, &model_config).await {
+            log::warn!("{}", warning);
+        }
+    });
+    if config.approval_policy.is_empty() {
+        if let Some(policy) = load_settings().default_approval_policy {
+            config.approval_policy = policy;
+        }
+    }
 
     {
         let sessions = state.sessions.lock().await;
@@ -23,11 +261,22 @@ pub async fn start_codex_session(
             return Ok(());
         }
     }
+    let _slot = acquire_session_slot(state.clone()).await?;
 
+    let model = config.model.clone();
     let codex_client = CodexClient::new(&app, session_id.clone(), config)
         .await
         .map_err(|e| format!("Failed to start Codex session: {}", e))?;
 
+    let started_at = chrono::Utc::now().timestamp_millis();
+    state.ended_sessions.lock().await.remove(&session_id);
+    state
+        .runtimes
+        .lock()
+        .await
+        .insert(session_id.clone(), SessionRuntime::new(started_at, model));
+    spawn_heartbeat(app.clone(), session_id.clone(), started_at);
+
     {
         let mut sessions = state.sessions.lock().await;
         sessions.insert(session_id.clone(), codex_client);
@@ -41,20 +290,274 @@ pub async fn start_codex_session(
     Ok(())
 }
 
+/// With a session limit set, check there is room for one more session, waiting
+/// for a slot when `queue_over_limit` is on. The returned guard keeps other
+/// starts from taking the same slot until this one has stored its client.
+async fn acquire_session_slot(
+    state: State<'_, CodexState>,
+) -> Result<Option<MutexGuard<'_, ()>>, CodexError> {
+    let state_for_count = state.clone();
+    wait_for_session_slot(state.inner(), || {
+        get_running_sessions(state_for_count.clone())
+    })
+    .await
+}
+
+/// `acquire_session_slot`, with the running sessions counted by `running`.
+async fn wait_for_session_slot<'a, F, Fut>(
+    state: &'a CodexState,
+    running: F,
+) -> Result<Option<MutexGuard<'a, ()>>, CodexError>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<Vec<String>, String>>,
+{
+    if state.session_limit().is_none() {
+        return Ok(None);
+    }
+    let guard = state.session_start.lock().await;
+    loop {
+        // Register before counting so a session ending in between still wakes us
+        let freed = state.session_slot_freed.notified();
+        let Some(limit) = state.session_limit() else {
+            return Ok(Some(guard));
+        };
+        let running = running().await?.len();
+        if running < limit {
+            return Ok(Some(guard));
+        }
+        if !state.queue_over_limit.load(Ordering::SeqCst) {
+            return Err(CodexError::TooManySessions {
+                limit,
+                running,
+                message: format!(
+                    "{} sessions are already running, the limit is {}; close one first",
+                    running, limit
+                ),
+            });
+        }
+        log::info!("Session limit of {} reached, waiting for a slot", limit);
+        freed.await;
+    }
+}
+
+/// Send `message`, or queue it behind a running turn or earlier queued messages.
+/// Sending resumes a paused session, which also delivers what it queued.
 pub async fn send_message(
+    app: AppHandle,
     state: State<'_, CodexState>,
     session_id: String,
     message: String,
-) -> Result<(), String> {
+) -> Result<(), CodexError> {
+    CodexError::ensure_writable(&state)?;
     let mut sessions = state.sessions.lock().await;
-    if let Some(client) = sessions.get_mut(&session_id) {
-        client
-            .send_user_input(message)
-            .await
-            .map_err(|e| format!("Failed to send message: {}", e))?;
-        Ok(())
-    } else {
-        Err("Session not found".to_string())
+    let Some(client) = sessions.get_mut(&session_id) else {
+        return Err(CodexError::not_running(&session_id));
+    };
+
+    // While a turn runs, or earlier messages still wait, the message joins the
+    // queue; see `send_next_queued`
+    let mut runtimes = state.runtimes.lock().await;
+    if let Some(runtime) = runtimes.get_mut(&session_id) {
+        runtime.paused = false;
+        if runtime.is_busy() || !runtime.queued_messages.is_empty() {
+            runtime.queued_messages.push_back(message);
+            log::debug!(
+                "Queued message for session {} ({} waiting)",
+                session_id,
+                runtime.queued_messages.len()
+            );
+            // An idle session with a queue was paused or its turn aborted; start on it now
+            let idle = !runtime.is_busy();
+            drop(runtimes);
+            drop(sessions);
+            if idle {
+                send_next_queued(&app, &session_id).await;
+            }
+            return Ok(());
+        }
+        runtime.awaiting_turn = true;
+    }
+    drop(runtimes);
+
+    if let Err(e) = client.send_user_input(message).await {
+        if let Some(runtime) = state.runtimes.lock().await.get_mut(&session_id) {
+            runtime.awaiting_turn = false;
+        }
+        return Err(format!("Failed to send message: {}", e).into());
+    }
+    Ok(())
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct QueuedMessageSentPayload {
+    pub session_id: String,
+    pub message: String,
+    /// Messages still waiting after this one.
+    pub remaining: usize,
+}
+
+/// Send the oldest message queued for a session whose turn has just ended and
+/// emit `queued-message-sent`. Does nothing while the session is busy or paused,
+/// or its queue is empty.
+pub async fn send_next_queued(app: &AppHandle, session_id: &str) {
+    let Some(state) = app.try_state::<CodexState>() else {
+        return;
+    };
+    let sessions = state.sessions.lock().await;
+    let Some(client) = sessions.get(session_id) else {
+        return;
+    };
+    let (message, remaining) = {
+        let mut runtimes = state.runtimes.lock().await;
+        let Some(runtime) = runtimes.get_mut(session_id) else {
+            return;
+        };
+        if runtime.is_busy() || runtime.paused {
+            return;
+        }
+        let Some(message) = runtime.queued_messages.pop_front() else {
+            return;
+        };
+        runtime.awaiting_turn = true;
+        (message, runtime.queued_messages.len())
+    };
+
+    if let Err(e) = client.send_user_input(message.clone()).await {
+        log::error!("Failed to send queued message for {}: {}", session_id, e);
+        if let Some(runtime) = state.runtimes.lock().await.get_mut(session_id) {
+            runtime.queued_messages.push_front(message);
+            runtime.awaiting_turn = false;
+        }
+        return;
+    }
+
+    let payload = QueuedMessageSentPayload {
+        session_id: session_id.to_string(),
+        message,
+        remaining,
+    };
+    if let Err(e) = app.emit("queued-message-sent", &payload) {
+        log::error!("Failed to emit queued-message-sent: {}", e);
+    }
+}
+
+/// Messages waiting for the current turn of a session to finish, oldest first.
+pub async fn get_queued_messages(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<Vec<String>, CodexError> {
+    let runtimes = state.runtimes.lock().await;
+    let runtime = runtimes
+        .get(&session_id)
+        .ok_or_else(|| CodexError::not_running(&session_id))?;
+    Ok(runtime.queued_messages.iter().cloned().collect())
+}
+
+/// Drop every queued message of a session, returning how many there were.
+pub async fn clear_message_queue(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<usize, CodexError> {
+    let mut runtimes = state.runtimes.lock().await;
+    let runtime = runtimes
+        .get_mut(&session_id)
+        .ok_or_else(|| CodexError::not_running(&session_id))?;
+    let cleared = runtime.queued_messages.len();
+    runtime.queued_messages.clear();
+    Ok(cleared)
+}
+
+/// First codex release that accepts `override_turn_context` submissions.
+const MIN_TURN_OVERRIDE_VERSION: (u64, u64, u64) = (0, 24, 0);
+
+/// Parse the version from `codex -V` output such as `codex-cli 0.24.1`.
+pub fn parse_codex_version(output: &str) -> Option<(u64, u64, u64)> {
+    let version = output.split_whitespace().last()?.trim_start_matches('v');
+    let mut parts = version.split('.').map(|part| {
+        let digits: String = part.chars().take_while(|c| c.is_ascii_digit()).collect();
+        digits.parse::<u64>().ok()
+    });
+    Some((
+        parts.next()??,
+        parts.next()??,
+        parts.next().flatten().unwrap_or(0),
+    ))
+}
+
+/// Answer a single message with `model` without changing the session's model.
+/// Needs a codex build with per-turn context overrides (0.24.0 or later) and a
+/// session started with an explicit model; otherwise this returns an error and
+/// nothing is sent.
+pub async fn send_message_with_model(
+    state: State<'_, CodexState>,
+    session_id: String,
+    message: String,
+    model: String,
+) -> Result<(), CodexError> {
+    CodexError::ensure_writable(&state)?;
+    let model = model.trim().to_string();
+    if model.is_empty() {
+        return Err("Model cannot be empty".into());
+    }
+
+    let version_output = check_codex_version().await?;
+    match parse_codex_version(&version_output) {
+        Some(version) if version >= MIN_TURN_OVERRIDE_VERSION => {}
+        _ => {
+            return Err(CodexError::from(format!(
+                "Per-message model override needs codex {}.{}.{} or later (found '{}')",
+                MIN_TURN_OVERRIDE_VERSION.0,
+                MIN_TURN_OVERRIDE_VERSION.1,
+                MIN_TURN_OVERRIDE_VERSION.2,
+                version_output
+            )));
+        }
+    }
+
+    let sessions = state.sessions.lock().await;
+    let client = sessions
+        .get(&session_id)
+        .ok_or_else(|| CodexError::not_running(&session_id))?;
+    client
+        .send_user_input_with_model(message, model)
+        .await
+        .map_err(|e| format!("Failed to send message: {}", e).into())
+}
+
+/// Record a decision in the approval log. Failures are logged, never surfaced,
+/// so a broken log file can't block approvals.
+async fn log_approval_decision(
+    state: &State<'_, CodexState>,
+    session_id: &str,
+    approval_id: &str,
+    kind: &str,
+    approved: bool,
+) {
+    let pending = state.pending_approvals.lock().await.remove(approval_id);
+    record_approval_decision(session_id, approval_id, kind, pending, approved);
+}
+
+fn record_approval_decision(
+    session_id: &str,
+    approval_id: &str,
+    kind: &str,
+    pending: Option<PendingApproval>,
+    approved: bool,
+) {
+    let entry = ApprovalLogEntry {
+        timestamp: chrono::Utc::now().to_rfc3339(),
+        session_id: session_id.to_string(),
+        approval_id: approval_id.to_string(),
+        kind: pending
+            .as_ref()
+            .map_or_else(|| kind.to_string(), |p| p.kind.clone()),
+        decision: if approved { "approved" } else { "denied" }.to_string(),
+        auto: false,
+        summary: pending.map(|p| p.summary.clone()).unwrap_or_default(),
+    };
+    if let Err(e) = approval_log::append_entry(&entry) {
+        log::warn!("Failed to record approval decision: {}", e);
     }
 }
 
@@ -63,16 +566,19 @@ pub async fn approve_execution(
     session_id: String,
     approval_id: String,
     approved: bool,
-) -> Result<(), String> {
+) -> Result<(), CodexError> {
+    CodexError::ensure_writable(&state)?;
     let mut sessions = state.sessions.lock().await;
     if let Some(client) = sessions.get_mut(&session_id) {
         client
-            .send_exec_approval(approval_id, approved)
+            .send_exec_approval(approval_id.clone(), approved)
             .await
             .map_err(|e| format!("Failed to send approval: {}", e))?;
+        drop(sessions);
+        log_approval_decision(&state, &session_id, &approval_id, "exec", approved).await;
         Ok(())
     } else {
-        Err("Session not found".to_string())
+        Err(CodexError::not_running(&session_id))
     }
 }
 
@@ -81,7 +587,8 @@ pub async fn approve_patch(
     session_id: String,
     approval_id: String,
     approved: bool,
-) -> Result<(), String> {
+) -> Result<(), CodexError> {
+    CodexError::ensure_writable(&state)?;
     log::debug!(
         "approve_patch: session_id={}, approval_id={}, approved={}",
         session_id,
@@ -91,16 +598,86 @@ pub async fn approve_patch(
     let mut sessions = state.sessions.lock().await;
     if let Some(client) = sessions.get_mut(&session_id) {
         client
-            .send_apply_patch_approval(approval_id, approved)
+            .send_apply_patch_approval(approval_id.clone(), approved)
             .await
             .map_err(|e| format!("Failed to send patch approval: {}", e))?;
+        drop(sessions);
+        log_approval_decision(&state, &session_id, &approval_id, "patch", approved).await;
         Ok(())
     } else {
-        Err("Session not found".to_string())
+        Err(CodexError::not_running(&session_id))
     }
 }
 
-pub async fn pause_session(state: State<'_, CodexState>, session_id: String) -> Result<(), String> {
+/// Answer every approval currently pending for `session_id` with the same
+/// decision and return how many were answered. Requests arriving while this
+/// runs aren't part of the batch and stay pending for the usual prompt.
+pub async fn approve_all_pending(
+    state: State<'_, CodexState>,
+    session_id: String,
+    approved: bool,
+) -> Result<usize, CodexError> {
+    CodexError::ensure_writable(&state)?;
+    if !state.sessions.lock().await.contains_key(&session_id) {
+        return Err(CodexError::not_running(&session_id));
+    }
+
+    // Take the batch out of the map so a concurrent single answer can't resolve it twice
+    let mut batch: Vec<(String, PendingApproval)> = {
+        let mut pending = state.pending_approvals.lock().await;
+        let ids: Vec<String> = pending
+            .iter()
+            .filter(|(_, p)| p.session_id == session_id)
+            .map(|(id, _)| id.clone())
+            .collect();
+        ids.into_iter()
+            .filter_map(|id| pending.remove(&id).map(|p| (id, p)))
+            .collect()
+    };
+    batch.sort_by_key(|(_, p)| p.requested_at);
+
+    let mut handled = 0;
+    let mut remaining = batch.into_iter();
+    while let Some((approval_id, pending)) = remaining.next() {
+        let result = {
+            let sessions = state.sessions.lock().await;
+            match sessions.get(&session_id) {
+                Some(client) if pending.kind == "exec" => client
+                    .send_exec_approval(approval_id.clone(), approved)
+                    .await
+                    .map_err(|e| format!("Failed to send approval: {}", e)),
+                Some(client) => client
+                    .send_apply_patch_approval(approval_id.clone(), approved)
+                    .await
+                    .map_err(|e| format!("Failed to send patch approval: {}", e)),
+                None => Err(format!("Session {} stopped during the batch", session_id)),
+            }
+        };
+        if let Err(e) = result {
+            // Put back what wasn't answered so it can still be resolved one by one
+            let mut map = state.pending_approvals.lock().await;
+            map.insert(approval_id, pending);
+            map.extend(remaining);
+            return Err(e.into());
+        }
+        let kind = pending.kind.clone();
+        record_approval_decision(&session_id, &approval_id, &kind, Some(pending), approved);
+        handled += 1;
+    }
+
+    log::info!(
+        "{} {} pending approvals in session {}",
+        if approved { "Approved" } else { "Denied" },
+        handled,
+        session_id
+    );
+    Ok(handled)
+}
+
+pub async fn pause_session(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), CodexError> {
     let sessions = state.sessions.lock().await;
     let stored_sessions: Vec<String> = sessions.keys().cloned().collect();
 
@@ -109,27 +686,390 @@ pub async fn pause_session(state: State<'_, CodexState>, session_id: String) ->
 
     if let Some(client) = sessions.get(&session_id) {
         log::debug!("Found session, sending interrupt (pause): {}", session_id);
-        client
-            .interrupt()
-            .await
-            .map_err(|e| format!("Failed to pause session: {}", e))?;
+        // Mark it first so the TurnAborted that follows keeps the queue held
+        if let Some(runtime) = state.runtimes.lock().await.get_mut(&session_id) {
+            runtime.paused = true;
+        }
+        if let Err(e) = client.interrupt().await {
+            if let Some(runtime) = state.runtimes.lock().await.get_mut(&session_id) {
+                runtime.paused = false;
+            }
+            return Err(format!("Failed to pause session: {}", e).into());
+        }
         Ok(())
     } else {
         log::debug!("Session not found: {}", session_id);
-        Err("Session not found".to_string())
+        Err(CodexError::not_running(&session_id))
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct ResumeCompatibilityWarningPayload {
+    pub session_id: String,
+    /// Version recorded in the rollout header.
+    pub rollout_version: String,
+    pub installed_version: String,
+    pub message: String,
+}
+
+/// Start a codex process that continues the conversation in an existing rollout,
+/// found by path or by session UUID, and return the session id to send messages
+/// to. Resuming a session that is already running just returns its id. Empty
+/// `working_directory` and `model` fall back to what the rollout recorded.
+pub async fn resume_session(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    file_path: String,
+    mut config: CodexConfig,
+) -> Result<String, CodexError> {
+    CodexError::ensure_writable(&state)?;
+    let session_id = session::normalize_session_id(&file_path)?;
+    let sessions_path = state.sessions_path()?;
+    let rollout_path = match std::path::Path::new(&file_path) {
+        path if path.is_file() => path.to_path_buf(),
+        _ => session::find_rollout_path_for_session(&sessions_path, &session_id)?.ok_or_else(
+            || {
+                CodexError::from(format!(
+                    "Rollout file for session '{}' not found",
+                    file_path
+                ))
+            },
+        )?,
+    };
+
+    if state.sessions.lock().await.contains_key(&session_id) {
+        return Ok(session_id);
+    }
+
+    let metadata = session::read_session_metadata(&sessions_path, session_id.clone())
+        .await
+        .ok();
+    if let Some(metadata) = &metadata {
+        if config.working_directory.is_empty() && config.cwd.is_none() {
+            config.working_directory = metadata.cwd.clone().unwrap_or_default();
+        }
+        if config.model.is_empty() {
+            config.model = metadata.model.clone().unwrap_or_default();
+        }
+    }
+
+    // Rollouts from another release may use a format this codex reads differently
+    let rollout_version = metadata.and_then(|m| m.codex_version);
+    if let (Some(rollout_version), Ok(installed)) = (rollout_version, check_codex_version().await) {
+        let recorded = parse_codex_version(&rollout_version);
+        let current = parse_codex_version(&installed);
+        let same_series = matches!(
+            (recorded, current),
+            (Some(a), Some(b)) if (a.0, a.1) == (b.0, b.1)
+        );
+        if !same_series {
+            let message = format!(
+                "This session was recorded by codex {} but {} is installed; resuming may not restore it exactly",
+                rollout_version, installed
+            );
+            log::warn!("{}", message);
+            let payload = ResumeCompatibilityWarningPayload {
+                session_id: session_id.clone(),
+                rollout_version,
+                installed_version: installed,
+                message,
+            };
+            if let Err(e) = app.emit("resume-compatibility-warning", &payload) {
+                log::error!("Failed to emit resume-compatibility-warning: {}", e);
+            }
+        }
+    }
+
+    config.resume_path = Some(rollout_path.to_string_lossy().to_string());
+    start_codex_session(app, state, session_id.clone(), config).await?;
+    Ok(session_id)
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TurnInterruptedPayload {
+    pub session_id: String,
+    /// Unix millis when the turn being interrupted started.
+    pub turn_started_at: i64,
+}
+
+/// Stop the turn that is generating and keep the session open for the next message.
+pub async fn interrupt_turn(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), CodexError> {
+    let turn_started_at = {
+        let runtimes = state.runtimes.lock().await;
+        let runtime = runtimes
+            .get(&session_id)
+            .ok_or_else(|| CodexError::not_running(&session_id))?;
+        runtime
+            .turn_started_at
+            .ok_or_else(|| CodexError::NoActiveTurn {
+                session_id: session_id.clone(),
+                message: format!("Session {} has no turn in progress", session_id),
+            })?
+    };
+
+    {
+        let sessions = state.sessions.lock().await;
+        let client = sessions
+            .get(&session_id)
+            .ok_or_else(|| CodexError::not_running(&session_id))?;
+        client
+            .interrupt()
+            .await
+            .map_err(|e| format!("Failed to interrupt turn: {}", e))?;
+    }
+
+    let payload = TurnInterruptedPayload {
+        session_id,
+        turn_started_at,
+    };
+    app.emit("turn-interrupted", &payload)
+        .map_err(|e| format!("Failed to emit turn-interrupted: {}", e).into())
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct TurnRetriedPayload {
+    pub session_id: String,
+    /// Prompt sent in place of the abandoned turn.
+    pub prompt: String,
+    pub edited: bool,
+}
+
+/// Throw away the last exchange of a running session and send its prompt again,
+/// or `edited_message` instead. Codex can't drop a turn from a live session, so
+/// the process is closed, the turn is cut from the rollout and the session is
+/// resumed from it under the same id before the prompt goes out.
+pub async fn retry_last_turn(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    session_id: String,
+    edited_message: Option<String>,
+) -> Result<(), CodexError> {
+    CodexError::ensure_writable(&state)?;
+    let mut config = state
+        .sessions
+        .lock()
+        .await
+        .get(&session_id)
+        .map(|client| client.config().clone())
+        .ok_or_else(|| CodexError::not_running(&session_id))?;
+    let rollout_path =
+        match session::find_rollout_path_for_session(&state.sessions_path()?, &session_id)? {
+            Some(path) => path,
+            None => config
+                .resume_path
+                .clone()
+                .filter(|p| !p.is_empty())
+                .map(std::path::PathBuf::from)
+                .ok_or_else(|| format!("Rollout file for session '{}' not found", session_id))?,
+        };
+    // Check there is a turn to retry while the session is still untouched
+    let original = session::last_user_prompt(&rollout_path)?;
+    let edited_message = edited_message.filter(|m| !m.trim().is_empty());
+    let edited = edited_message.is_some();
+    let prompt = edited_message.unwrap_or(original);
+
+    // Stop codex first so nothing is appended while the rollout is rewritten
+    let client = state.sessions.lock().await.remove(&session_id);
+    if let Some(mut client) = client {
+        client
+            .close_session()
+            .await
+            .map_err(|e| format!("Failed to close session: {}", e))?;
     }
+    state.runtimes.lock().await.remove(&session_id);
+    state
+        .pending_approvals
+        .lock()
+        .await
+        .retain(|_, pending| pending.session_id != session_id);
+
+    // Bring the session back even if the rewrite failed, then report the failure
+    let rollback = session::rollback_last_turn(&rollout_path);
+    config.resume_path = Some(rollout_path.to_string_lossy().to_string());
+    start_codex_session(app.clone(), state.clone(), session_id.clone(), config).await?;
+    rollback?;
+
+    send_message(app.clone(), state, session_id.clone(), prompt.clone()).await?;
+    let payload = TurnRetriedPayload {
+        session_id,
+        prompt,
+        edited,
+    };
+    app.emit("turn-retried", &payload)
+        .map_err(|e| format!("Failed to emit turn-retried: {}", e).into())
 }
 
-pub async fn close_session(state: State<'_, CodexState>, session_id: String) -> Result<(), String> {
+pub async fn close_session(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), CodexError> {
+    unwatch_session_files_for(&state, &session_id).await;
+    state.runtimes.lock().await.remove(&session_id);
+    state
+        .pending_approvals
+        .lock()
+        .await
+        .retain(|_, pending| pending.session_id != session_id);
     let mut sessions = state.sessions.lock().await;
     if let Some(mut client) = sessions.remove(&session_id) {
+        state
+            .ended_sessions
+            .lock()
+            .await
+            .insert(session_id.clone(), SessionStatus::Closed);
         client
             .close_session()
             .await
             .map_err(|e| format!("Failed to close session: {}", e))?;
+        drop(sessions);
+        state.release_session_slot();
+        compress_closed_rollout(&state, &session_id).await;
+        Ok(())
+    } else {
+        Err(CodexError::not_running(&session_id))
+    }
+}
+
+/// With `compress_rollouts_on_close`, gzip the rollout of a session that was
+/// just closed. Failures are only logged and leave the plain rollout in place.
+async fn compress_closed_rollout(state: &CodexState, session_id: &str) {
+    if !load_settings().compress_rollouts_on_close {
+        return;
+    }
+    let Ok(sessions_path) = state.sessions_path() else {
+        return;
+    };
+    let Ok(Some(path)) = session::find_rollout_path_for_session(&sessions_path, session_id) else {
+        return;
+    };
+    if is_compressed_rollout(&path) {
+        return;
+    }
+    match tokio::task::spawn_blocking(move || archive::compress_rollout(&path)).await {
+        Ok(Ok(dest)) => log::info!("Compressed rollout to {}", dest.display()),
+        Ok(Err(e)) => log::warn!("Failed to compress rollout of {}: {}", session_id, e),
+        Err(e) => log::warn!("Compressing rollout of {} failed: {}", session_id, e),
+    }
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionCrashedPayload {
+    pub session_id: String,
+    /// `None` when codex was killed by a signal or its code couldn't be read.
+    pub exit_code: Option<i32>,
+    pub stderr_tail: Vec<String>,
+    /// Whether `auto_restart` is bringing the session back.
+    pub restarting: bool,
+}
+
+/// Number of stderr lines sent with `session-crashed`.
+const CRASH_STDERR_LINES: usize = 20;
+/// A session that dies sooner than this after starting isn't restarted, so a
+/// codex that can't stay up doesn't respawn forever.
+const MIN_UPTIME_FOR_RESTART_MS: i64 = 10_000;
+
+/// Called when a session's codex process exits on its own. Drops the session,
+/// emits `session-crashed` and, with `auto_restart`, resumes its rollout in a
+/// new process under the same id.
+///
+/// Boxed because a restarted client spawns a task awaiting this again, a
+/// recursive future type the compiler can't prove `Send` otherwise.
+pub fn handle_process_exit(
+    app: AppHandle,
+    session_id: String,
+) -> Pin<Box<dyn Future<Output = ()> + Send>> {
+    Box::pin(async move {
+        let Some(state) = app.try_state::<CodexState>() else {
+            return;
+        };
+        // Closed in the meantime, or already handled
+        let Some(mut client) = state.sessions.lock().await.remove(&session_id) else {
+            return;
+        };
+        state.release_session_slot();
+        let exit_code = client.exit_code().await;
+        let stderr_tail = client.stderr_tail(CRASH_STDERR_LINES);
+        let mut config = client.config().clone();
+        drop(client);
+
+        let started_at = state
+            .runtimes
+            .lock()
+            .await
+            .remove(&session_id)
+            .map(|runtime| runtime.started_at);
+        state
+            .pending_approvals
+            .lock()
+            .await
+            .retain(|_, pending| pending.session_id != session_id);
+        state
+            .ended_sessions
+            .lock()
+            .await
+            .insert(session_id.clone(), SessionStatus::Crashed);
+
+        let uptime_ms = started_at.map(|at| chrono::Utc::now().timestamp_millis() - at);
+        let restarting = config.auto_restart
+            && uptime_ms.is_some_and(|ms| ms >= MIN_UPTIME_FOR_RESTART_MS)
+            && !state.is_read_only();
+        log::error!(
+            "Codex process for session {} exited unexpectedly (code {:?})",
+            session_id,
+            exit_code
+        );
+
+        let payload = SessionCrashedPayload {
+            session_id: session_id.clone(),
+            exit_code,
+            stderr_tail,
+            restarting,
+        };
+        if let Err(e) = app.emit("session-crashed", &payload) {
+            log::error!("Failed to emit session-crashed: {}", e);
+        }
+        if !restarting {
+            return;
+        }
+
+        // Prefer the rollout codex recorded for this id; a resumed session keeps its original file
+        let rollout_path = state
+            .sessions_path()
+            .and_then(|dir| session::find_rollout_path_for_session(&dir, &session_id));
+        match rollout_path {
+            Ok(Some(path)) => config.resume_path = Some(path.to_string_lossy().to_string()),
+            Ok(None) => {}
+            Err(e) => log::warn!("Could not look up rollout for {}: {}", session_id, e),
+        }
+        if config.resume_path.is_none() {
+            log::warn!(
+                "No rollout found for session {}, restarting without history",
+                session_id
+            );
+        }
+        log::info!("Restarting crashed session {}", session_id);
+        if let Err(e) = start_codex_session(app.clone(), state, session_id.clone(), config).await {
+            log::error!("Failed to restart session {}: {}", session_id, e);
+        }
+    })
+}
+
+pub async fn set_raw_event_debug(
+    state: State<'_, CodexState>,
+    session_id: String,
+    enabled: bool,
+) -> Result<(), CodexError> {
+    let sessions = state.sessions.lock().await;
+    if let Some(client) = sessions.get(&session_id) {
+        log::debug!("Raw event debug for {}: {}", session_id, enabled);
+        client.set_raw_event_debug(enabled);
         Ok(())
     } else {
-        Err("Session not found".to_string())
+        Err(CodexError::not_running(&session_id))
     }
 }
 
@@ -149,7 +1089,7 @@ pub async fn get_running_sessions(state: State<'_, CodexState>) -> Result<Vec<St
 pub async fn check_codex_version() -> Result<String, String> {
     let path = match discover_codex_command() {
         Some(p) => p.to_string_lossy().to_string(),
-        None => "codex".to_string(),
+        None => load_settings().codex_binary_name,
     };
 
     let output = Command::new(&path)
@@ -165,3 +1105,507 @@ pub async fn check_codex_version() -> Result<String, String> {
         Err(format!("Codex binary returned error: {}", err_msg))
     }
 }
+
+/// How long `validate_codex_binary` waits for `--version` before giving up.
+pub const VALIDATE_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CodexBinaryInfo {
+    pub path: String,
+    pub kind: CodexBinaryKind,
+    /// Whether `--version` exited successfully within the timeout.
+    pub ok: bool,
+    pub version: Option<String>,
+    #[serde(rename = "exitCode")]
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+    /// What went wrong, phrased for display in settings.
+    pub message: Option<String>,
+}
+
+/// Run `path` (or the discovered codex) with `--version` and report whether it works.
+/// Only a missing binary is an error; launch failures are reported in the result.
+pub async fn validate_codex_binary(path: Option<String>) -> Result<CodexBinaryInfo, String> {
+    let path = match path.filter(|p| !p.trim().is_empty()) {
+        Some(p) => std::path::PathBuf::from(p),
+        None => discover_codex_command().ok_or_else(|| {
+            format!(
+                "Could not find a '{}' binary; install codex or set its path",
+                load_settings().codex_binary_name
+            )
+        })?,
+    };
+    if !path.exists() {
+        return Err(format!("Codex binary not found at {}", path.display()));
+    }
+    let (kind, _) = classify_codex_binary(&path);
+
+    let mut info = CodexBinaryInfo {
+        path: path.to_string_lossy().to_string(),
+        kind,
+        ok: false,
+        version: None,
+        exit_code: None,
+        stderr: String::new(),
+        message: None,
+    };
+
+    let child = tokio::process::Command::new(&path)
+        .arg("--version")
+        .stdin(std::process::Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = match tokio::time::timeout(VALIDATE_TIMEOUT, child).await {
+        Err(_) => {
+            info.message = Some(format!(
+                "'{} --version' did not finish within {} seconds",
+                info.path,
+                VALIDATE_TIMEOUT.as_secs()
+            ));
+            return Ok(info);
+        }
+        Ok(Err(e)) => {
+            info.message = Some(match kind {
+                CodexBinaryKind::Wrapper => {
+                    format!("Failed to run wrapper script (is node installed?): {}", e)
+                }
+                CodexBinaryKind::Native => format!("Failed to run codex binary: {}", e),
+            });
+            return Ok(info);
+        }
+        Ok(Ok(output)) => output,
+    };
+
+    info.exit_code = output.status.code();
+    info.stderr = String::from_utf8_lossy(&output.stderr).trim().to_string();
+    let stdout = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    if output.status.success() {
+        info.ok = true;
+        info.version = parse_codex_version(&stdout)
+            .map(|(major, minor, patch)| format!("{}.{}.{}", major, minor, patch));
+        if info.version.is_none() {
+            info.message = Some(format!("Unrecognized version output: {}", stdout));
+        }
+    } else {
+        info.message = Some(match info.exit_code {
+            Some(code) => format!("Codex exited with status {}", code),
+            None => "Codex was terminated by a signal".to_string(),
+        });
+    }
+    Ok(info)
+}
+
+/// Every codex install discovery can find, each checked with `--version`, so the
+/// user can pick one per session via `CodexConfig::codex_path`.
+pub async fn list_codex_binaries() -> Result<Vec<CodexBinaryInfo>, String> {
+    let candidates = tokio::task::spawn_blocking(locate_codex_candidates)
+        .await
+        .map_err(|e| format!("Failed to locate codex: {}", e))?;
+
+    let checks: Vec<_> = candidates
+        .into_iter()
+        .map(|candidate| tokio::spawn(validate_codex_binary(Some(candidate.path))))
+        .collect();
+    let mut binaries = Vec::new();
+    for check in checks {
+        match check.await {
+            Ok(Ok(info)) => binaries.push(info),
+            Ok(Err(e)) => log::debug!("Skipping codex candidate: {}", e),
+            Err(e) => log::warn!("Codex candidate check failed: {}", e),
+        }
+    }
+    Ok(binaries)
+}
+
+/// Longest a single session gets to shut down before its process is killed.
+const SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Close every running codex process concurrently and drop their runtime and
+/// approval state. Returns how many sessions were closed.
+pub async fn shutdown_all_sessions(state: &CodexState) -> usize {
+    // Take the clients out first so the lock isn't held while processes shut down
+    let clients: Vec<(String, CodexClient)> = state.sessions.lock().await.drain().collect();
+    let closed_sessions = clients.len();
+    state.release_session_slot();
+    {
+        let mut ended_sessions = state.ended_sessions.lock().await;
+        for (session_id, _) in &clients {
+            ended_sessions.insert(session_id.clone(), SessionStatus::Closed);
+        }
+    }
+
+    let closing: Vec<_> = clients
+        .into_iter()
+        .map(|(session_id, mut client)| {
+            tokio::spawn(async move {
+                // close_session sends Shutdown and kills the process if it doesn't exit;
+                // on timeout the client is dropped, which kills it as well
+                match tokio::time::timeout(SHUTDOWN_TIMEOUT, client.close_session()).await {
+                    Ok(Ok(())) => {}
+                    Ok(Err(e)) => log::error!("Failed to close session {}: {}", session_id, e),
+                    Err(_) => log::warn!(
+                        "Session {} did not shut down within {}s, killing it",
+                        session_id,
+                        SHUTDOWN_TIMEOUT.as_secs()
+                    ),
+                }
+            })
+        })
+        .collect();
+    for task in closing {
+        if let Err(e) = task.await {
+            log::error!("Session shutdown task failed: {}", e);
+        }
+    }
+
+    state.runtimes.lock().await.clear();
+    state.pending_approvals.lock().await.clear();
+    closed_sessions
+}
+
+/// Close every running session and drop all per-session state, the cached codex
+/// path and the session index. Safe to call repeatedly; with nothing running it
+/// just clears the caches and emits `state-reset`.
+pub async fn reset_all_sessions(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+) -> Result<(), String> {
+    let closed_sessions = shutdown_all_sessions(&state).await;
+    state.watchers.lock().await.clear();
+    state.session_file_watchers.lock().await.clear();
+
+    // Any scan still running would return a list from before the reset
+    session::cancel_all_session_loads();
+    // Start over from disk: find codex again and parse every rollout on the next load
+    invalidate_codex_path_cache();
+    let codex_home = state.codex_home()?;
+    tokio::task::spawn_blocking(move || session_index::clear_index(&codex_home))
+        .await
+        .map_err(|e| format!("Clearing session index failed: {}", e))??;
+
+    log::info!("State reset, closed {} sessions", closed_sessions);
+    app.emit("state-reset", &StateResetPayload { closed_sessions })
+        .map_err(|e| format!("Failed to emit state-reset: {}", e))
+}
+
+/// Whether a session is running, paused, or how it ended.
+pub async fn get_session_status(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<SessionStatus, CodexError> {
+    if state.sessions.lock().await.contains_key(&session_id) {
+        let paused = state
+            .runtimes
+            .lock()
+            .await
+            .get(&session_id)
+            .is_some_and(|runtime| runtime.paused);
+        return Ok(if paused {
+            SessionStatus::Paused
+        } else {
+            SessionStatus::Running
+        });
+    }
+    state
+        .ended_sessions
+        .lock()
+        .await
+        .get(&session_id)
+        .copied()
+        .ok_or_else(|| CodexError::not_running(&session_id))
+}
+
+pub async fn get_session_runtime(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<SessionRuntimeInfo, CodexError> {
+    let runtimes = state.runtimes.lock().await;
+    let runtime = runtimes
+        .get(&session_id)
+        .ok_or_else(|| CodexError::not_running(&session_id))?;
+
+    Ok(SessionRuntimeInfo {
+        session_id,
+        started_at: runtime.started_at,
+        elapsed_ms: chrono::Utc::now().timestamp_millis() - runtime.started_at,
+        turns_completed: runtime.turns_completed,
+        turn_started_at: runtime.turn_started_at,
+    })
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct SessionUsage {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    pub model: String,
+    /// Prompt tokens, including the ones served from cache.
+    #[serde(rename = "inputTokens")]
+    pub input_tokens: u64,
+    #[serde(rename = "cachedInputTokens")]
+    pub cached_input_tokens: u64,
+    #[serde(rename = "outputTokens")]
+    pub output_tokens: u64,
+    #[serde(rename = "reasoningOutputTokens")]
+    pub reasoning_output_tokens: u64,
+    /// USD at the configured rates; `None` when the model has no known rate.
+    #[serde(rename = "estimatedCostUsd")]
+    pub estimated_cost_usd: Option<f64>,
+}
+
+/// Tokens used so far by a running session and what they cost.
+pub async fn get_session_usage(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<SessionUsage, CodexError> {
+    let runtimes = state.runtimes.lock().await;
+    let runtime = runtimes
+        .get(&session_id)
+        .ok_or_else(|| CodexError::not_running(&session_id))?;
+    let usage = runtime.usage;
+
+    Ok(SessionUsage {
+        estimated_cost_usd: estimate_cost_usd(
+            &runtime.model,
+            usage.input_tokens,
+            usage.cached_input_tokens,
+            usage.output_tokens,
+        ),
+        session_id,
+        model: runtime.model.clone(),
+        input_tokens: usage.input_tokens,
+        cached_input_tokens: usage.cached_input_tokens,
+        output_tokens: usage.output_tokens,
+        reasoning_output_tokens: usage.reasoning_output_tokens,
+    })
+}
+
+/// The config a running session was launched with, after the default approval
+/// policy and binary discovery were applied. The API key and `env` values are masked.
+pub async fn get_effective_config(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<CodexConfig, CodexError> {
+    let sessions = state.sessions.lock().await;
+    let client = sessions
+        .get(&session_id)
+        .ok_or_else(|| CodexError::not_running(&session_id))?;
+
+    Ok(client.config().redacted())
+}
+
+/// Every unanswered approval across running sessions, oldest request first.
+pub async fn list_all_pending_approvals(
+    state: State<'_, CodexState>,
+) -> Result<Vec<PendingApprovalInfo>, String> {
+    let running: Vec<String> = state.sessions.lock().await.keys().cloned().collect();
+    let pending = state.pending_approvals.lock().await;
+
+    let mut approvals: Vec<PendingApprovalInfo> = pending
+        .iter()
+        .filter(|(_, p)| running.contains(&p.session_id))
+        .map(|(approval_id, p)| PendingApprovalInfo {
+            session_id: p.session_id.clone(),
+            approval_id: approval_id.clone(),
+            kind: p.kind.clone(),
+            summary: p.summary.clone(),
+            requested_at: p.requested_at,
+        })
+        .collect();
+    approvals.sort_by(|a, b| {
+        a.requested_at
+            .cmp(&b.requested_at)
+            .then_with(|| a.approval_id.cmp(&b.approval_id))
+    });
+    Ok(approvals)
+}
+
+/// The patch of an approval still waiting for an answer. Answered, timed out
+/// and unknown ids are all reported as not pending, so the frontend never acts
+/// on a patch that can no longer be approved.
+async fn pending_patch(
+    state: &CodexState,
+    session_id: &str,
+    approval_id: &str,
+) -> Result<PendingPatch, CodexError> {
+    let pending = state.pending_approvals.lock().await;
+    let approval = pending
+        .get(approval_id)
+        .filter(|p| p.session_id == session_id)
+        .ok_or_else(|| CodexError::ApprovalNotPending {
+            approval_id: approval_id.to_string(),
+            message: format!(
+                "Approval {} is not pending in session {}",
+                approval_id, session_id
+            ),
+        })?;
+    approval
+        .patch
+        .clone()
+        .ok_or_else(|| format!("Approval {} is not a patch approval", approval_id).into())
+}
+
+/// Diff preview for a patch approval that is still waiting for an answer.
+pub async fn get_pending_patch(
+    state: State<'_, CodexState>,
+    session_id: String,
+    approval_id: String,
+) -> Result<PatchPreview, CodexError> {
+    let patch = pending_patch(&state, &session_id, &approval_id).await?;
+    Ok(build_preview(&session_id, &approval_id, &patch)?)
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PatchFilesResolvedPayload {
+    pub session_id: String,
+    pub approval_id: String,
+    pub applied: Vec<String>,
+    pub skipped: Vec<String>,
+}
+
+/// Answer a patch approval file by file. Codex only takes a yes or no for the
+/// whole patch, so selecting every file approves it and selecting none denies
+/// it; anything in between fails with `PatchSplitUnsupported` and the approval
+/// stays pending. Emits `patch-files-resolved` once codex has the answer.
+pub async fn approve_patch_files(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    session_id: String,
+    approval_id: String,
+    approved_paths: Vec<String>,
+) -> Result<(), CodexError> {
+    CodexError::ensure_writable(&state)?;
+    let files = patch_files(&pending_patch(&state, &session_id, &approval_id).await?);
+
+    if let Some(unknown) = approved_paths.iter().find(|p| !files.contains(p)) {
+        return Err(format!("{} is not part of approval {}", unknown, approval_id).into());
+    }
+    let (applied, skipped): (Vec<String>, Vec<String>) = files
+        .into_iter()
+        .partition(|file| approved_paths.contains(file));
+    if !applied.is_empty() && !skipped.is_empty() {
+        return Err(CodexError::PatchSplitUnsupported {
+            approval_id,
+            message: format!(
+                "Codex can only apply or reject a patch as a whole; approve all {} files or none",
+                applied.len() + skipped.len()
+            ),
+        });
+    }
+
+    let approved = !applied.is_empty();
+    approve_patch(state, session_id.clone(), approval_id.clone(), approved).await?;
+
+    let payload = PatchFilesResolvedPayload {
+        session_id,
+        approval_id,
+        applied,
+        skipped,
+    };
+    if let Err(e) = app.emit("patch-files-resolved", &payload) {
+        log::error!("Failed to emit patch-files-resolved: {}", e);
+    }
+    Ok(())
+}
+
+/// The stdout/stderr lines captured for a running session, up to the
+/// `output_buffer_lines` setting per stream.
+pub async fn get_session_output(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<CapturedOutput, CodexError> {
+    let sessions = state.sessions.lock().await;
+    let client = sessions
+        .get(&session_id)
+        .ok_or_else(|| CodexError::not_running(&session_id))?;
+    Ok(client.captured_output())
+}
+
+/// Drop captured stdout/stderr for a session. Unknown sessions are ignored.
+pub async fn clear_session_output(
+    state: State<'_, CodexState>,
+    session_id: String,
+) -> Result<(), String> {
+    let sessions = state.sessions.lock().await;
+    if let Some(client) = sessions.get(&session_id) {
+        client.clear_output();
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::{Arc, Mutex};
+
+    /// Start a session against `running` the way `start_codex_session` does:
+    /// take a slot, then record the session while still holding it.
+    async fn start(
+        state: &CodexState,
+        running: &Arc<Mutex<Vec<String>>>,
+        id: &str,
+    ) -> Result<(), CodexError> {
+        let count = || {
+            let ids = running.lock().unwrap().clone();
+            async move { Ok::<_, String>(ids) }
+        };
+        let _slot = wait_for_session_slot(state, count).await?;
+        running.lock().unwrap().push(id.to_string());
+        Ok(())
+    }
+
+    fn close(state: &CodexState, running: &Arc<Mutex<Vec<String>>>, id: &str) {
+        running.lock().unwrap().retain(|r| r != id);
+        state.release_session_slot();
+    }
+
+    #[tokio::test]
+    async fn start_over_the_limit_is_rejected_until_a_session_closes() {
+        let state = CodexState::new();
+        state.max_concurrent_sessions.store(2, Ordering::SeqCst);
+        let running = Arc::new(Mutex::new(Vec::new()));
+
+        start(&state, &running, "a").await.unwrap();
+        start(&state, &running, "b").await.unwrap();
+        match start(&state, &running, "c").await {
+            Err(CodexError::TooManySessions { limit, running, .. }) => {
+                assert_eq!((limit, running), (2, 2));
+            }
+            other => panic!("expected TooManySessions, got {:?}", other),
+        }
+
+        close(&state, &running, "a");
+        start(&state, &running, "c").await.unwrap();
+        assert_eq!(*running.lock().unwrap(), ["b", "c"]);
+    }
+
+    #[tokio::test]
+    async fn queued_start_waits_for_a_session_to_close() {
+        let state = Arc::new(CodexState::new());
+        state.max_concurrent_sessions.store(1, Ordering::SeqCst);
+        state.queue_over_limit.store(true, Ordering::SeqCst);
+        let running = Arc::new(Mutex::new(Vec::new()));
+        start(&state, &running, "a").await.unwrap();
+
+        let queued = {
+            let (state, running) = (state.clone(), running.clone());
+            tokio::spawn(async move { start(&state, &running, "b").await })
+        };
+        tokio::time::sleep(Duration::from_millis(50)).await;
+        assert!(!queued.is_finished());
+
+        close(&state, &running, "a");
+        queued.await.unwrap().unwrap();
+        assert_eq!(*running.lock().unwrap(), ["b"]);
+    }
+
+    #[tokio::test]
+    async fn no_limit_never_counts_sessions() {
+        let state = CodexState::new();
+        let slot = wait_for_session_slot(&state, || async {
+            Err::<Vec<String>, _>("should not be called".to_string())
+        })
+        .await
+        .unwrap();
+        assert!(slot.is_none());
+    }
+}
@@ -0,0 +1,203 @@
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::utils::codex_discovery::{
+    classify_codex_binary, discover_codex_command, CodexBinaryKind,
+};
+use crate::utils::file::{probe_writable, sessions_path_in};
+
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiagnosticStatus {
+    Ok,
+    /// Works, but something is likely to cause trouble later.
+    Warning,
+    Error,
+    /// Not run because a check it depends on failed.
+    Skipped,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DiagnosticCheck {
+    pub name: String,
+    pub status: DiagnosticStatus,
+    pub message: String,
+    /// What the user can do about it; `None` when the check passed.
+    pub hint: Option<String>,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct DiagnosticsReport {
+    /// No check ended in `Error`.
+    pub healthy: bool,
+    pub checks: Vec<DiagnosticCheck>,
+}
+
+fn passed(name: &str, message: String) -> DiagnosticCheck {
+    DiagnosticCheck {
+        name: name.to_string(),
+        status: DiagnosticStatus::Ok,
+        message,
+        hint: None,
+    }
+}
+
+fn flagged(name: &str, status: DiagnosticStatus, message: String, hint: &str) -> DiagnosticCheck {
+    DiagnosticCheck {
+        name: name.to_string(),
+        status,
+        message,
+        hint: Some(hint.to_string()),
+    }
+}
+
+fn check_binary() -> (DiagnosticCheck, Option<PathBuf>) {
+    let Some(path) = discover_codex_command() else {
+        let check = flagged(
+            "codex_binary",
+            DiagnosticStatus::Error,
+            "No codex executable found".to_string(),
+            "Install codex (`npm install -g @openai/codex` or your package manager), or set its path in settings",
+        );
+        return (check, None);
+    };
+
+    let check = match Command::new(&path).arg("-V").output() {
+        Ok(output) if output.status.success() => passed(
+            "codex_binary",
+            format!(
+                "{} ({})",
+                String::from_utf8_lossy(&output.stdout).trim(),
+                path.display()
+            ),
+        ),
+        Ok(output) => flagged(
+            "codex_binary",
+            DiagnosticStatus::Error,
+            format!(
+                "{} returned error: {}",
+                path.display(),
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+            "Reinstall codex, or choose another binary with list_codex_binaries",
+        ),
+        Err(e) => flagged(
+            "codex_binary",
+            DiagnosticStatus::Error,
+            format!("Failed to execute {}: {}", path.display(), e),
+            "Check the file is executable and built for this platform, or reinstall codex",
+        ),
+    };
+    (check, Some(path))
+}
+
+fn check_binary_kind(path: Option<&Path>) -> DiagnosticCheck {
+    let Some(path) = path else {
+        return flagged(
+            "binary_kind",
+            DiagnosticStatus::Skipped,
+            "No codex executable to inspect".to_string(),
+            "Fix the codex_binary check first",
+        );
+    };
+    match classify_codex_binary(path) {
+        (CodexBinaryKind::Native, reason) => passed("binary_kind", format!("Native: {}", reason)),
+        (CodexBinaryKind::Wrapper, reason) => flagged(
+            "binary_kind",
+            DiagnosticStatus::Warning,
+            format!("Node wrapper: {}", reason),
+            "The wrapper needs a working node on PATH; a native install avoids that",
+        ),
+    }
+}
+
+fn check_codex_home(home: Option<&Path>) -> DiagnosticCheck {
+    let Some(home) = home else {
+        return flagged(
+            "codex_home",
+            DiagnosticStatus::Error,
+            "Could not determine the home directory".to_string(),
+            "Set HOME (or CODEX_HOME) in the environment codexia is launched from",
+        );
+    };
+    if !home.is_dir() {
+        return flagged(
+            "codex_home",
+            DiagnosticStatus::Error,
+            format!("{} does not exist", home.display()),
+            "Run `codex` once in a terminal to create it",
+        );
+    }
+    match probe_writable(home) {
+        Ok(()) => passed("codex_home", format!("{} is writable", home.display())),
+        Err(e) => flagged(
+            "codex_home",
+            DiagnosticStatus::Error,
+            format!("{} is not writable: {}", home.display(), e),
+            "Fix the ownership or permissions of the directory",
+        ),
+    }
+}
+
+fn check_history(home: Option<&Path>) -> DiagnosticCheck {
+    let Some(home) = home.filter(|h| h.is_dir()) else {
+        return flagged(
+            "history",
+            DiagnosticStatus::Skipped,
+            "No codex home to look in".to_string(),
+            "Fix the codex_home check first",
+        );
+    };
+    let history_path = home.join("history.jsonl");
+    if history_path.is_file() {
+        passed("history", format!("{} found", history_path.display()))
+    } else {
+        flagged(
+            "history",
+            DiagnosticStatus::Warning,
+            format!("{} not found", history_path.display()),
+            "Codex creates it after the first prompt; if you have sent prompts, check CODEX_HOME",
+        )
+    }
+}
+
+fn check_sessions(home: Option<&Path>) -> DiagnosticCheck {
+    let Some(home) = home else {
+        return flagged(
+            "sessions",
+            DiagnosticStatus::Skipped,
+            "No codex home to look in".to_string(),
+            "Fix the codex_home check first",
+        );
+    };
+    let sessions_dir = sessions_path_in(home);
+    if sessions_dir.is_dir() {
+        passed("sessions", format!("{} found", sessions_dir.display()))
+    } else {
+        flagged(
+            "sessions",
+            DiagnosticStatus::Warning,
+            format!("{} not found", sessions_dir.display()),
+            "Codex creates it with the first session; if you moved the store, check sessions_dir in settings",
+        )
+    }
+}
+
+/// Check the codex install and the data directories under `home` end to end.
+/// Runs the binary and touches the filesystem, so call it off the async runtime.
+pub fn diagnose(home: Option<PathBuf>) -> DiagnosticsReport {
+    let (binary, binary_path) = check_binary();
+    let checks = vec![
+        binary,
+        check_binary_kind(binary_path.as_deref()),
+        check_codex_home(home.as_deref()),
+        check_history(home.as_deref()),
+        check_sessions(home.as_deref()),
+    ];
+
+    DiagnosticsReport {
+        healthy: checks.iter().all(|c| c.status != DiagnosticStatus::Error),
+        checks,
+    }
+}
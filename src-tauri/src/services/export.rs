@@ -0,0 +1,240 @@
+use serde::Deserialize;
+use std::fs;
+
+use crate::filesystem::file_io::{resolve_readable_file, resolve_writable_text_file};
+use crate::services::session::{parse_session_file, ChatMessage, Conversation, ToolCall};
+use crate::settings::load_settings;
+use crate::utils::disk_space::ensure_space;
+
+const DEFAULT_MAX_OUTPUT_LINES: usize = 40;
+const DEFAULT_WRAP_WIDTH: usize = 100;
+
+/// Cleanup applied to a Markdown export. Everything is off unless `compact` is set.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct MarkdownExportOptions {
+    /// Collapse blank runs, truncate long tool output and wrap long lines.
+    #[serde(default)]
+    pub compact: bool,
+    /// Tool output longer than this keeps only its first and last lines.
+    #[serde(rename = "maxOutputLines", default)]
+    pub max_output_lines: Option<usize>,
+    /// Column at which long lines are wrapped.
+    #[serde(rename = "wrapWidth", default)]
+    pub wrap_width: Option<usize>,
+}
+
+/// Squeeze runs of blank (or whitespace-only) lines down to one.
+fn collapse_blank_lines(text: &str) -> String {
+    let mut out = Vec::new();
+    let mut previous_blank = false;
+    for line in text.lines() {
+        let line = line.trim_end();
+        if line.is_empty() {
+            if previous_blank {
+                continue;
+            }
+            previous_blank = true;
+        } else {
+            previous_blank = false;
+        }
+        out.push(line);
+    }
+    out.join("\n").trim_matches('\n').to_string()
+}
+
+/// Keep the head and tail of `text` when it has more than `max_lines` lines.
+fn truncate_lines(text: &str, max_lines: usize) -> String {
+    let lines: Vec<&str> = text.lines().collect();
+    if lines.len() <= max_lines {
+        return text.to_string();
+    }
+    let head = max_lines.div_ceil(2);
+    let tail = max_lines - head;
+    let omitted = lines.len() - head - tail;
+    let mut out: Vec<String> = lines[..head].iter().map(|l| l.to_string()).collect();
+    out.push(format!("… {} lines omitted …", omitted));
+    out.extend(lines[lines.len() - tail..].iter().map(|l| l.to_string()));
+    out.join("\n")
+}
+
+/// Wrap prose at word boundaries. Fenced code is left alone so it stays runnable.
+fn wrap_prose(text: &str, width: usize) -> String {
+    let mut out = Vec::new();
+    let mut in_fence = false;
+    for line in text.lines() {
+        if line.trim_start().starts_with("```") {
+            in_fence = !in_fence;
+        }
+        if in_fence || line.chars().count() <= width {
+            out.push(line.to_string());
+            continue;
+        }
+
+        let mut current = String::new();
+        for word in line.split_whitespace() {
+            if !current.is_empty() && current.chars().count() + 1 + word.chars().count() > width {
+                out.push(std::mem::take(&mut current));
+            }
+            if !current.is_empty() {
+                current.push(' ');
+            }
+            current.push_str(word);
+        }
+        out.push(current);
+    }
+    out.join("\n")
+}
+
+/// Hard-wrap each line of command output at `width` characters.
+fn wrap_output(text: &str, width: usize) -> String {
+    let mut out = Vec::new();
+    for line in text.lines() {
+        let chars: Vec<char> = line.chars().collect();
+        if chars.is_empty() {
+            out.push(String::new());
+        }
+        for chunk in chars.chunks(width) {
+            out.push(chunk.iter().collect());
+        }
+    }
+    out.join("\n")
+}
+
+/// A fence longer than any backtick run in `text`, so the content can't close it early.
+fn fence_for(text: &str) -> String {
+    let longest = text
+        .split(|c| c != '`')
+        .map(|run| run.len())
+        .max()
+        .unwrap_or(0);
+    "`".repeat(longest.max(2) + 1)
+}
+
+struct Renderer {
+    compact: bool,
+    max_output_lines: usize,
+    wrap_width: usize,
+}
+
+impl Renderer {
+    fn prose(&self, text: &str) -> String {
+        if self.compact {
+            wrap_prose(&collapse_blank_lines(text), self.wrap_width)
+        } else {
+            text.trim_end().to_string()
+        }
+    }
+
+    fn output(&self, text: &str) -> String {
+        if self.compact {
+            let text = collapse_blank_lines(text);
+            wrap_output(
+                &truncate_lines(&text, self.max_output_lines),
+                self.wrap_width,
+            )
+        } else {
+            text.trim_end().to_string()
+        }
+    }
+
+    fn tool_call(&self, call: &ToolCall, md: &mut String) {
+        md.push_str(&format!("### Tool: `{}`\n\n", call.name));
+
+        let invocation = match &call.command {
+            Some(argv) => argv.join(" "),
+            None => serde_json::to_string_pretty(&call.arguments).unwrap_or_default(),
+        };
+        let fence = fence_for(&invocation);
+        let lang = if call.command.is_some() { "sh" } else { "json" };
+        md.push_str(&format!("{fence}{lang}\n{invocation}\n{fence}\n\n"));
+
+        let summary = match call.exit_code {
+            Some(code) => format!("Output (exit {})", code),
+            None => "Output".to_string(),
+        };
+        match call.output.as_deref() {
+            Some(output) => {
+                let output = self.output(output);
+                let fence = fence_for(&output);
+                md.push_str(&format!(
+                    "<details>\n<summary>{summary}</summary>\n\n{fence}\n{output}\n{fence}\n\n</details>\n\n"
+                ));
+            }
+            None => md.push_str("_No output recorded._\n\n"),
+        }
+    }
+
+    fn message(&self, message: &ChatMessage, md: &mut String) {
+        if let Some(call) = &message.tool_call {
+            self.tool_call(call, md);
+            return;
+        }
+        let heading = match message.role.as_str() {
+            "user" => "User",
+            "assistant" => "Assistant",
+            "system" => "System",
+            other => other,
+        };
+        md.push_str(&format!(
+            "## {}\n\n{}\n\n",
+            heading,
+            self.prose(&message.content)
+        ));
+    }
+
+    fn render(&self, conversation: &Conversation) -> String {
+        let mut md = format!("# {}\n\n", conversation.title);
+        if let Some(started) = chrono::DateTime::from_timestamp_millis(conversation.created_at) {
+            md.push_str(&format!("- Started: {}\n", started.to_rfc3339()));
+        }
+        if let Some(cwd) = &conversation.cwd {
+            md.push_str(&format!("- Working directory: `{}`\n", cwd));
+        }
+        md.push('\n');
+
+        for message in &conversation.messages {
+            self.message(message, &mut md);
+        }
+        format!("{}\n", md.trim_end())
+    }
+}
+
+pub fn render_markdown(conversation: &Conversation, options: &MarkdownExportOptions) -> String {
+    let renderer = Renderer {
+        compact: options.compact,
+        max_output_lines: options
+            .max_output_lines
+            .unwrap_or(DEFAULT_MAX_OUTPUT_LINES)
+            .max(2),
+        wrap_width: options.wrap_width.unwrap_or(DEFAULT_WRAP_WIDTH).max(20),
+    };
+    renderer.render(conversation)
+}
+
+/// Render the rollout at `file_path` as Markdown and write it to `out_path`.
+pub fn export_session_markdown(
+    file_path: &str,
+    out_path: &str,
+    options: &MarkdownExportOptions,
+) -> Result<(), String> {
+    let source = resolve_readable_file(file_path)?;
+    let out_path = resolve_writable_text_file(out_path)?;
+    let content =
+        fs::read_to_string(&source).map_err(|e| format!("Failed to read session file: {}", e))?;
+    let conversation = parse_session_file(&content, &source)
+        .ok_or_else(|| "Session file has no conversation to export".to_string())?;
+
+    let markdown = render_markdown(&conversation, options);
+    let max_bytes = load_settings().write_limit();
+    if markdown.len() as u64 > max_bytes {
+        return Err(format!(
+            "Export is too large to write ({} bytes, limit is {} bytes)",
+            markdown.len(),
+            max_bytes
+        ));
+    }
+
+    ensure_space(&out_path, markdown.len() as u64)?;
+
+    fs::write(&out_path, markdown).map_err(|e| format!("Failed to write export: {}", e))
+}
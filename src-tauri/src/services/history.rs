@@ -0,0 +1,89 @@
+use serde::{Deserialize, Serialize};
+use std::fs::{self, OpenOptions};
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+/// One prompt from codex's `~/.codex/history.jsonl`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    #[serde(rename(serialize = "sessionId"))]
+    pub session_id: String,
+    /// Unix seconds when the prompt was sent.
+    pub ts: i64,
+    pub text: String,
+}
+
+pub fn get_history_path(codex_home: &Path) -> PathBuf {
+    codex_home.join("history.jsonl")
+}
+
+/// Prompts containing `query` (case-insensitive, empty matches all), most recent
+/// first and at most `limit` of them. A prompt repeated back to back is returned
+/// once. Lines that fail to parse are skipped.
+pub fn search_history(
+    codex_home: &Path,
+    query: &str,
+    limit: usize,
+) -> Result<Vec<HistoryEntry>, String> {
+    let history_path = get_history_path(codex_home);
+    if !history_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let file =
+        fs::File::open(&history_path).map_err(|e| format!("Failed to open history file: {}", e))?;
+    let needle = query.trim().to_lowercase();
+    let mut matches: Vec<HistoryEntry> = Vec::new();
+    for entry in BufReader::new(file)
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| serde_json::from_str::<HistoryEntry>(&line).ok())
+        .filter(|entry| entry.text.to_lowercase().contains(&needle))
+    {
+        // Keep the latest of a run of identical prompts
+        match matches.last_mut() {
+            Some(last) if last.text.trim() == entry.text.trim() => *last = entry,
+            _ => matches.push(entry),
+        }
+    }
+
+    Ok(matches.into_iter().rev().take(limit).collect())
+}
+
+/// Empty the history file, keeping its contents in a timestamped
+/// `history-<time>.jsonl.bak` next to it. The file is renamed rather than
+/// copied, so prompts codex appends meanwhile end up in the backup instead of
+/// being lost. Returns the backup path, or `None` when there was no history.
+pub fn clear_history(codex_home: &Path) -> Result<Option<String>, String> {
+    let history_path = get_history_path(codex_home);
+    if !history_path.exists() {
+        return Ok(None);
+    }
+
+    let permissions = fs::metadata(&history_path)
+        .map_err(|e| format!("Failed to read history file: {}", e))?
+        .permissions();
+    let backup_path = history_path.with_file_name(format!(
+        "history-{}.jsonl.bak",
+        chrono::Local::now().format("%Y%m%dT%H%M%S")
+    ));
+    if backup_path.exists() {
+        return Err(format!(
+            "Backup {} already exists, try again in a second",
+            backup_path.display()
+        ));
+    }
+    fs::rename(&history_path, &backup_path)
+        .map_err(|e| format!("Failed to back up history file: {}", e))?;
+
+    let file = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&history_path)
+        .map_err(|e| format!("Failed to recreate history file: {}", e))?;
+    // History can hold secrets pasted into prompts; keep the original permissions
+    file.set_permissions(permissions)
+        .map_err(|e| format!("Failed to set history file permissions: {}", e))?;
+
+    Ok(Some(backup_path.to_string_lossy().to_string()))
+}
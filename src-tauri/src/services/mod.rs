@@ -1,2 +1,16 @@
+pub mod approval_log;
+pub mod archive;
+pub mod auth;
 pub mod codex;
+pub mod diagnostics;
+pub mod export;
+pub mod history;
+pub mod models;
+pub mod patch;
+pub mod preflight;
+pub mod search;
 pub mod session;
+pub mod session_index;
+pub mod session_meta;
+pub mod shell;
+pub mod stats;
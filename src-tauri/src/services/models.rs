@@ -0,0 +1,216 @@
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::path::Path;
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+
+use crate::config::load_model_providers;
+use crate::protocol::CodexConfig;
+
+/// How long a successful model listing is reused before querying the provider again.
+const MODEL_CACHE_TTL: Duration = Duration::from_secs(300);
+const REQUEST_TIMEOUT: Duration = Duration::from_secs(10);
+
+type ModelCache = HashMap<String, (Instant, Vec<String>)>;
+
+/// Results keyed by "provider|base_url|api key hash", so switching keys doesn't
+/// return a listing fetched with another account's credentials.
+static MODEL_CACHE: Mutex<Option<ModelCache>> = Mutex::new(None);
+
+#[derive(Serialize, Debug, Clone)]
+pub struct AvailableModels {
+    pub models: Vec<String>,
+    /// "provider", "cache" or "default" (bundled list, used when the query fails).
+    pub source: String,
+}
+
+/// Built-in base URL, key variable and model list for providers that don't
+/// have to be declared in `config.toml`. Kept in line with the frontend defaults.
+fn builtin_provider(provider: &str) -> (&'static str, &'static str, &'static [&'static str]) {
+    match provider {
+        "ollama" => (
+            "http://localhost:11434/v1",
+            "OLLAMA_API_KEY",
+            &[
+                "gpt-oss:20b",
+                "mistral",
+                "qwen3",
+                "deepseek-r1",
+                "llama3.2",
+                "gemma3",
+            ],
+        ),
+        "google" | "gemini" => (
+            "https://generativelanguage.googleapis.com/v1beta/openai",
+            "GEMINI_API_KEY",
+            &["gemini-2.5-flash", "gemini-2.5-pro"],
+        ),
+        "openrouter" => (
+            "https://openrouter.ai/api/v1",
+            "OPENROUTER_API_KEY",
+            &[
+                "anthropic/claude-opus-4.1",
+                "anthropic/claude-sonnet-4",
+                "openai/gpt-oss-20b:free",
+                "x-ai/grok-code-fast-1",
+                "qwen/qwen3-coder:free",
+            ],
+        ),
+        "xai" => (
+            "https://api.x.ai/v1",
+            "XAI_API_KEY",
+            &["grok-code-fast-1", "grok-4"],
+        ),
+        _ => (
+            "https://api.openai.com/v1",
+            "OPENAI_API_KEY",
+            &["gpt-5", "gpt-5-codex"],
+        ),
+    }
+}
+
+/// Model ids from an OpenAI-style `{"data": [{"id": ..}]}` body, or Ollama's `{"models": [{"name": ..}]}`.
+fn parse_model_ids(body: &Value) -> Vec<String> {
+    let (list, key) = match (body.get("data"), body.get("models")) {
+        (Some(data), _) => (data, "id"),
+        (None, Some(models)) => (models, "name"),
+        _ => return Vec::new(),
+    };
+    let mut ids: Vec<String> = list
+        .as_array()
+        .map(|items| {
+            items
+                .iter()
+                .filter_map(|m| m.get(key).and_then(|id| id.as_str()))
+                .map(|id| id.to_string())
+                .collect()
+        })
+        .unwrap_or_default();
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+async fn fetch_models(base_url: &str, api_key: Option<&str>) -> Result<Vec<String>, String> {
+    let client = reqwest::Client::builder()
+        .timeout(REQUEST_TIMEOUT)
+        .build()
+        .map_err(|e| format!("Failed to create HTTP client: {}", e))?;
+
+    let url = format!("{}/models", base_url.trim_end_matches('/'));
+    let mut request = client.get(&url);
+    if let Some(key) = api_key {
+        request = request.bearer_auth(key);
+    }
+
+    let response = request
+        .send()
+        .await
+        .map_err(|e| format!("Request failed: {}", e.without_url()))?;
+    if !response.status().is_success() {
+        return Err(format!("Provider returned {}", response.status()));
+    }
+    let body: Value = response
+        .json()
+        .await
+        .map_err(|e| format!("Invalid response: {}", e.without_url()))?;
+
+    let ids = parse_model_ids(&body);
+    if ids.is_empty() {
+        return Err("Provider returned no models".to_string());
+    }
+    Ok(ids)
+}
+
+pub async fn list_available_models(
+    codex_home: &Path,
+    config: CodexConfig,
+) -> Result<AvailableModels, String> {
+    let provider = if config.use_oss {
+        "ollama".to_string()
+    } else if config.provider.is_empty() {
+        "openai".to_string()
+    } else {
+        config.provider.to_lowercase()
+    };
+    let (default_base_url, default_env_key, default_models) = builtin_provider(&provider);
+
+    // A provider declared in config.toml overrides the built-in endpoint and key variable
+    let declared = load_model_providers(codex_home)
+        .ok()
+        .and_then(|providers| providers.get(&provider).cloned());
+    let base_url = declared
+        .as_ref()
+        .map(|p| p.base_url.clone())
+        .filter(|url| !url.is_empty())
+        .unwrap_or_else(|| default_base_url.to_string());
+    let env_key = declared
+        .as_ref()
+        .map(|p| p.env_key.clone())
+        .filter(|key| !key.is_empty())
+        .unwrap_or_else(|| default_env_key.to_string());
+
+    let api_key = config
+        .api_key
+        .clone()
+        .filter(|k| !k.is_empty())
+        .or_else(|| std::env::var(&env_key).ok().filter(|k| !k.is_empty()));
+
+    let mut key_hasher = DefaultHasher::new();
+    api_key.hash(&mut key_hasher);
+    let cache_key = format!("{}|{}|{:x}", provider, base_url, key_hasher.finish());
+    if let Ok(cache) = MODEL_CACHE.lock() {
+        if let Some((fetched_at, models)) = cache.as_ref().and_then(|c| c.get(&cache_key)) {
+            if fetched_at.elapsed() < MODEL_CACHE_TTL {
+                return Ok(AvailableModels {
+                    models: models.clone(),
+                    source: "cache".to_string(),
+                });
+            }
+        }
+    }
+
+    match fetch_models(&base_url, api_key.as_deref()).await {
+        Ok(models) => {
+            if let Ok(mut cache) = MODEL_CACHE.lock() {
+                cache
+                    .get_or_insert_with(HashMap::new)
+                    .insert(cache_key, (Instant::now(), models.clone()));
+            }
+            Ok(AvailableModels {
+                models,
+                source: "provider".to_string(),
+            })
+        }
+        Err(e) => {
+            log::warn!("Failed to list models for provider {}: {}", provider, e);
+            Ok(AvailableModels {
+                models: default_models.iter().map(|m| m.to_string()).collect(),
+                source: "default".to_string(),
+            })
+        }
+    }
+}
+
+/// Warning when `config.model` isn't among the models its provider reports.
+/// Unknown models are still passed to codex, since listings can lag behind
+/// what a provider accepts. Nothing is flagged against the bundled fallback list.
+pub async fn model_availability_warning(codex_home: &Path, config: &CodexConfig) -> Option<String> {
+    let model = config.model.trim();
+    if model.is_empty() {
+        return None;
+    }
+    let available = list_available_models(codex_home, config.clone())
+        .await
+        .ok()?;
+    if available.source == "default" || available.models.iter().any(|m| m == model) {
+        return None;
+    }
+    Some(format!(
+        "Model '{}' is not in the provider's model list; codex may reject it",
+        model
+    ))
+}
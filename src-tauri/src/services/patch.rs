@@ -0,0 +1,129 @@
+use serde::Serialize;
+use std::fs;
+
+use crate::state::PendingPatch;
+use crate::utils::diff::unified_diff;
+
+/// What a pending patch approval would change.
+#[derive(Serialize, Debug, Clone)]
+pub struct PatchPreview {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "approvalId")]
+    pub approval_id: String,
+    pub files: Vec<String>,
+    /// One unified diff covering every file, with `---`/`+++` headers.
+    #[serde(rename = "unifiedDiff")]
+    pub unified_diff: String,
+    pub added: usize,
+    pub removed: usize,
+}
+
+/// Split one entry of codex's `changes` map into its kind and fields. Older
+/// versions tag externally (`{"add": {...}}`), newer ones inline a `"type"`.
+fn change_kind(change: &serde_json::Value) -> Option<(&str, &serde_json::Value)> {
+    if let Some(kind) = change.get("type").and_then(|t| t.as_str()) {
+        return Some((kind, change));
+    }
+    let object = change.as_object()?;
+    let (kind, fields) = object.iter().next()?;
+    Some((kind.as_str(), fields))
+}
+
+fn string_field<'a>(fields: &'a serde_json::Value, key: &str) -> Option<&'a str> {
+    fields.get(key).and_then(|v| v.as_str())
+}
+
+/// Diff for one file change.
+fn change_diff(path: &str, change: &serde_json::Value) -> Result<String, String> {
+    let (kind, fields) =
+        change_kind(change).ok_or_else(|| format!("Unrecognized change for {}", path))?;
+    match kind {
+        "add" => {
+            let content = string_field(fields, "content").unwrap_or_default();
+            Ok(unified_diff("", content, "/dev/null", path, 3))
+        }
+        "delete" => {
+            // Codex only started sending the removed content recently
+            let content = match string_field(fields, "content") {
+                Some(content) => content.to_string(),
+                None => fs::read_to_string(path).unwrap_or_default(),
+            };
+            Ok(unified_diff(&content, "", path, "/dev/null", 3))
+        }
+        "update" => {
+            let diff = string_field(fields, "unified_diff").unwrap_or_default();
+            let new_path = string_field(fields, "move_path").unwrap_or(path);
+            if diff.starts_with("--- ") {
+                Ok(diff.to_string())
+            } else {
+                Ok(format!("--- {}\n+++ {}\n{}", path, new_path, diff))
+            }
+        }
+        other => Err(format!("Unknown change kind '{}' for {}", other, path)),
+    }
+}
+
+/// Added and removed line counts, ignoring file headers.
+fn count_lines(diff: &str) -> (usize, usize) {
+    diff.lines().fold((0, 0), |(added, removed), line| {
+        if line.starts_with('+') && !line.starts_with("+++") {
+            (added + 1, removed)
+        } else if line.starts_with('-') && !line.starts_with("---") {
+            (added, removed + 1)
+        } else {
+            (added, removed)
+        }
+    })
+}
+
+/// Paths `patch` touches, in path order.
+pub fn patch_files(patch: &PendingPatch) -> Vec<String> {
+    match patch {
+        PendingPatch::Changes(changes) => {
+            let mut files: Vec<String> = changes
+                .as_object()
+                .map(|changes| changes.keys().cloned().collect())
+                .unwrap_or_default();
+            files.sort();
+            files
+        }
+        PendingPatch::Text { files, .. } => files.clone(),
+    }
+}
+
+/// Build the preview for `patch`. Files are listed in path order.
+pub fn build_preview(
+    session_id: &str,
+    approval_id: &str,
+    patch: &PendingPatch,
+) -> Result<PatchPreview, String> {
+    let (files, diff) = match patch {
+        PendingPatch::Changes(changes) => {
+            let changes = changes
+                .as_object()
+                .ok_or("Patch changes are not a map of files")?;
+            let files = patch_files(patch);
+            let mut diff = String::new();
+            for path in &files {
+                let file_diff = change_diff(path, &changes[path])?;
+                diff.push_str(&file_diff);
+                if !diff.is_empty() && !diff.ends_with('\n') {
+                    diff.push('\n');
+                }
+            }
+            (files, diff)
+        }
+        PendingPatch::Text { patch, files } => (files.clone(), patch.clone()),
+    };
+
+    let (added, removed) = count_lines(&diff);
+    Ok(PatchPreview {
+        session_id: session_id.to_string(),
+        approval_id: approval_id.to_string(),
+        files,
+        unified_diff: diff,
+        added,
+        removed,
+    })
+}
@@ -0,0 +1,280 @@
+use serde::Serialize;
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Stdio;
+use tokio::process::Command;
+
+use crate::protocol::{CodexConfig, ReasoningEffort};
+use crate::services::auth::{check_codex_auth, CodexAuthStatus};
+use crate::services::codex::VALIDATE_TIMEOUT;
+use crate::services::models::model_availability_warning;
+use crate::utils::codex_discovery::discover_codex_command;
+use crate::utils::file::user_home;
+
+const APPROVAL_POLICIES: &[&str] = &["untrusted", "on-failure", "on-request", "never"];
+const SANDBOX_MODES: &[&str] = &["read-only", "workspace-write", "danger-full-access"];
+/// Upper bound on per-session instructions, in characters.
+pub const MAX_INSTRUCTIONS_CHARS: usize = 32_000;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PreflightCheck {
+    pub name: String,
+    pub ok: bool,
+    pub message: String,
+}
+
+#[derive(Serialize, Debug, Clone)]
+pub struct PreflightReport {
+    pub ready: bool,
+    pub checks: Vec<PreflightCheck>,
+}
+
+fn check(name: &str, result: Result<String, String>) -> PreflightCheck {
+    let (ok, message) = match result {
+        Ok(message) => (true, message),
+        Err(message) => (false, message),
+    };
+    PreflightCheck {
+        name: name.to_string(),
+        ok,
+        message,
+    }
+}
+
+/// Per-session instructions must have content and stay under `MAX_INSTRUCTIONS_CHARS`.
+pub fn validate_instructions(instructions: &str) -> Result<(), String> {
+    if instructions.trim().is_empty() {
+        return Err("Instructions cannot be empty".to_string());
+    }
+    let len = instructions.chars().count();
+    if len > MAX_INSTRUCTIONS_CHARS {
+        return Err(format!(
+            "Instructions are too long ({} characters, limit is {})",
+            len, MAX_INSTRUCTIONS_CHARS
+        ));
+    }
+    Ok(())
+}
+
+/// An explicitly chosen codex binary must exist and be executable.
+pub fn validate_codex_path(path: &str) -> Result<(), String> {
+    let metadata =
+        std::fs::metadata(path).map_err(|_| format!("Codex binary not found at {}", path))?;
+    if !metadata.is_file() {
+        return Err(format!("Codex path {} is not a file", path));
+    }
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        if metadata.permissions().mode() & 0o111 == 0 {
+            return Err(format!("Codex binary at {} is not executable", path));
+        }
+    }
+    Ok(())
+}
+
+/// Per-session environment keys must be usable names, and nothing may contain
+/// a NUL byte, which the OS can't pass to a child process.
+pub fn validate_env(env: &HashMap<String, String>) -> Result<(), String> {
+    for (key, value) in env {
+        if key.is_empty() || key.contains('=') || key.contains('\0') {
+            return Err(format!("Invalid environment variable name '{}'", key));
+        }
+        if value.contains('\0') {
+            return Err(format!("Environment variable {} contains a NUL byte", key));
+        }
+    }
+    Ok(())
+}
+
+pub fn validate_approval_policy(policy: &str) -> Result<(), String> {
+    if APPROVAL_POLICIES.contains(&policy) {
+        Ok(())
+    } else {
+        Err(format!("Unknown approval policy '{}'", policy))
+    }
+}
+
+/// Reject reasoning efforts the selected model can't use: local models ignore
+/// the setting and `minimal` is only accepted by the gpt-5 family.
+pub fn validate_model_selection(config: &CodexConfig) -> Result<(), String> {
+    let Some(effort) = config.reasoning_effort else {
+        return Ok(());
+    };
+    if config.use_oss || config.provider.eq_ignore_ascii_case("ollama") {
+        return Err(format!(
+            "Reasoning effort '{}' is not supported for local models",
+            effort.as_str()
+        ));
+    }
+    let model = config.model.trim();
+    if effort == ReasoningEffort::Minimal && !model.is_empty() && !model.starts_with("gpt-5") {
+        return Err(format!(
+            "Reasoning effort 'minimal' needs a gpt-5 model, not '{}'",
+            model
+        ));
+    }
+    Ok(())
+}
+
+/// Problems with `config` that would make codex reject it or behave unexpectedly.
+pub fn validate_config(config: &CodexConfig) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    if !config.approval_policy.is_empty() {
+        if let Err(e) = validate_approval_policy(&config.approval_policy) {
+            problems.push(e);
+        }
+    }
+    if !config.sandbox_mode.is_empty() && !SANDBOX_MODES.contains(&config.sandbox_mode.as_str()) {
+        problems.push(format!("Unknown sandbox mode '{}'", config.sandbox_mode));
+    }
+    if let Err(e) = validate_model_selection(config) {
+        problems.push(e);
+    }
+    if let Some(instructions) = &config.instructions {
+        if let Err(e) = validate_instructions(instructions) {
+            problems.push(e);
+        }
+    }
+    if config.approval_timeout_secs == Some(0) {
+        problems.push("Approval timeout must be at least 1 second".to_string());
+    }
+    if let Some(resume_path) = config.resume_path.as_deref().filter(|p| !p.is_empty()) {
+        if !Path::new(resume_path).is_file() {
+            problems.push(format!("Resume file '{}' does not exist", resume_path));
+        }
+    }
+
+    problems
+}
+
+/// Path of the binary `config` would launch and its `-V` output. A binary that
+/// doesn't answer within `VALIDATE_TIMEOUT` is killed and reported.
+async fn binary_version(config: &CodexConfig) -> Result<(String, String), String> {
+    let path = match config.codex_path.as_deref().filter(|p| !p.is_empty()) {
+        Some(path) => path.to_string(),
+        None => tokio::task::spawn_blocking(discover_codex_command)
+            .await
+            .map_err(|e| format!("Binary check failed: {}", e))?
+            .map(|p| p.to_string_lossy().to_string())
+            .ok_or_else(|| "Could not find codex executable".to_string())?,
+    };
+
+    // Short-lived `-V` run to confirm the binary actually starts
+    let child = Command::new(&path)
+        .arg("-V")
+        .stdin(Stdio::null())
+        .kill_on_drop(true)
+        .output();
+    let output = tokio::time::timeout(VALIDATE_TIMEOUT, child)
+        .await
+        .map_err(|_| {
+            format!(
+                "'{} -V' did not finish within {} seconds",
+                path,
+                VALIDATE_TIMEOUT.as_secs()
+            )
+        })?
+        .map_err(|e| format!("Failed to execute {}: {}", path, e))?;
+    if output.status.success() {
+        let version = String::from_utf8_lossy(&output.stdout).trim().to_string();
+        Ok((path, version))
+    } else {
+        Err(format!(
+            "{} returned error: {}",
+            path,
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
+
+fn check_sandbox(config: &CodexConfig) -> Result<String, String> {
+    Ok(match config.effective_sandbox() {
+        Some(sandbox) => format!("Sandbox policy '{}'", sandbox.as_str()),
+        None => "Using codex's default sandbox".to_string(),
+    })
+}
+
+async fn check_model(codex_home: &Path, config: &CodexConfig) -> Result<String, String> {
+    if config.model.trim().is_empty() {
+        return Ok("Using codex's default model".to_string());
+    }
+    match model_availability_warning(codex_home, config).await {
+        Some(warning) => Err(warning),
+        None => Ok(format!("Model '{}'", config.model.trim())),
+    }
+}
+
+async fn check_auth(codex_home: &Path, config: &CodexConfig) -> Result<String, String> {
+    if config.api_key.as_deref().is_some_and(|k| !k.is_empty()) {
+        return Ok("Using the API key from the session config".to_string());
+    }
+    // Local models don't need an account
+    if config.use_oss || config.provider.eq_ignore_ascii_case("ollama") {
+        return Ok("No login needed for local models".to_string());
+    }
+    match check_codex_auth(codex_home).await? {
+        CodexAuthStatus::LoggedIn { account } => Ok(match account {
+            Some(account) => format!("Logged in as {}", account),
+            None => "Logged in".to_string(),
+        }),
+        CodexAuthStatus::LoggedOut => Err("Not logged in; run `codex login`".to_string()),
+        CodexAuthStatus::Unknown => Err("Could not determine login state".to_string()),
+    }
+}
+
+/// Expand `~/` and canonicalize `dir`, requiring an existing directory.
+pub fn resolve_working_directory(dir: &str) -> Result<String, String> {
+    let expanded = match dir.strip_prefix("~/") {
+        Some(rest) => user_home()?.join(rest),
+        None => Path::new(dir).to_path_buf(),
+    };
+    let resolved = std::fs::canonicalize(&expanded)
+        .map_err(|e| format!("Working directory '{}' does not exist: {}", dir, e))?;
+    if !resolved.is_dir() {
+        return Err(format!("Working directory '{}' is not a directory", dir));
+    }
+    Ok(resolved.to_string_lossy().to_string())
+}
+
+fn check_working_directory(config: &CodexConfig) -> Result<String, String> {
+    let dir = config.effective_cwd();
+    if dir.is_empty() {
+        return Err("No working directory set".to_string());
+    }
+    resolve_working_directory(dir)
+}
+
+/// Run every check `start_codex_session` depends on without starting a session.
+/// `codex_home` is where the session's config.toml and login are read from.
+pub async fn preview_start_session(
+    codex_home: &Path,
+    config: CodexConfig,
+) -> Result<PreflightReport, String> {
+    let problems = validate_config(&config);
+    let config_result = if problems.is_empty() {
+        Ok("Configuration is valid".to_string())
+    } else {
+        Err(problems.join("; "))
+    };
+
+    let binary_result = binary_version(&config).await;
+
+    let checks = vec![
+        check("config", config_result),
+        check(
+            "codex_binary",
+            binary_result.map(|(path, version)| format!("{} ({})", version, path)),
+        ),
+        check("sandbox", check_sandbox(&config)),
+        check("model", check_model(codex_home, &config).await),
+        check("auth", check_auth(codex_home, &config).await),
+        check("working_directory", check_working_directory(&config)),
+    ];
+
+    Ok(PreflightReport {
+        ready: checks.iter().all(|c| c.ok),
+        checks,
+    })
+}
@@ -0,0 +1,236 @@
+use serde::{Deserialize, Serialize};
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::filesystem::file_io::resolve_readable_file;
+use crate::services::session::{message_identity, normalize_session_id, parse_session_content};
+use crate::settings::load_settings;
+use crate::utils::file::{
+    get_file_modification_time, open_rollout, read_to_string_with_retry_blocking,
+    scan_rollout_files,
+};
+use crate::utils::parallel::parallel_map;
+
+const DEFAULT_MAX_RESULTS: usize = 100;
+/// Characters of context kept on each side of a match.
+const SNIPPET_CONTEXT: usize = 80;
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SearchOptions {
+    #[serde(rename = "caseSensitive", default)]
+    pub case_sensitive: bool,
+    /// Only look at what the user typed, not assistant replies.
+    #[serde(rename = "userOnly", default)]
+    pub user_only: bool,
+    #[serde(rename = "maxResults", default)]
+    pub max_results: Option<usize>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct SessionMatch {
+    #[serde(rename = "sessionId")]
+    pub session_id: String,
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    /// 1-based line of the matching message in the rollout.
+    pub line: usize,
+    pub role: String,
+    pub snippet: String,
+}
+
+/// The match in `text` with up to `SNIPPET_CONTEXT` characters either side.
+fn snippet_around(text: &str, char_start: usize, char_len: usize) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let from = char_start.saturating_sub(SNIPPET_CONTEXT);
+    let to = (char_start + char_len + SNIPPET_CONTEXT).min(chars.len());
+    let mut snippet: String = chars[from..to].iter().collect();
+    snippet = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+    if from > 0 {
+        snippet.insert(0, '…');
+    }
+    if to < chars.len() {
+        snippet.push('…');
+    }
+    snippet
+}
+
+/// Character offset of `needle` in `text`, honoring `case_sensitive`.
+fn find_match(text: &str, needle: &str, case_sensitive: bool) -> Option<usize> {
+    if case_sensitive {
+        let pos = text.find(needle)?;
+        Some(text[..pos].chars().count())
+    } else {
+        let haystack = text.to_lowercase();
+        let pos = haystack.find(needle)?;
+        Some(haystack[..pos].chars().count())
+    }
+}
+
+/// Stream one rollout, collecting matches until the shared budget runs out.
+fn search_file(
+    path: &Path,
+    needle: &str,
+    options: &SearchOptions,
+    found: &AtomicUsize,
+    max_results: usize,
+) -> Vec<SessionMatch> {
+    let mut matches = Vec::new();
+    let Ok(reader) = open_rollout(path) else {
+        return matches;
+    };
+    let session_id = path
+        .to_str()
+        .and_then(|p| normalize_session_id(p).ok())
+        .unwrap_or_default();
+    let needle_len = needle.chars().count();
+
+    for (index, line) in reader.lines().enumerate() {
+        if found.load(Ordering::Relaxed) >= max_results {
+            break;
+        }
+        let Ok(line) = line else {
+            break;
+        };
+        // Cheap pre-filter before parsing the JSON
+        if options.case_sensitive && !line.contains(needle) {
+            continue;
+        }
+        let Some((role, text)) = message_identity(&line) else {
+            continue;
+        };
+        if options.user_only && role != "user" {
+            continue;
+        }
+        if let Some(start) = find_match(&text, needle, options.case_sensitive) {
+            if found.fetch_add(1, Ordering::Relaxed) >= max_results {
+                break;
+            }
+            matches.push(SessionMatch {
+                session_id: session_id.clone(),
+                file_path: path.to_string_lossy().to_string(),
+                line: index + 1,
+                role,
+                snippet: snippet_around(&text, start, needle_len),
+            });
+        }
+    }
+    matches
+}
+
+/// Find messages containing `query` across every rollout, most recently
+/// modified sessions first, stopping at `max_results` matches.
+pub async fn search_sessions(
+    sessions_path: &Path,
+    query: String,
+    options: SearchOptions,
+) -> Result<Vec<SessionMatch>, String> {
+    let query = query.trim().to_string();
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    if !sessions_path.exists() {
+        return Ok(Vec::new());
+    }
+    let sessions_path = sessions_path.to_path_buf();
+
+    let needle = if options.case_sensitive {
+        query
+    } else {
+        query.to_lowercase()
+    };
+    let max_results = options.max_results.unwrap_or(DEFAULT_MAX_RESULTS).max(1);
+    let workers = load_settings().scan_worker_count();
+
+    tokio::task::spawn_blocking(move || {
+        let mut paths: Vec<PathBuf> = scan_rollout_files(&sessions_path)
+            .map(|entry| entry.into_path())
+            .collect();
+        paths.sort_by_key(|p| std::cmp::Reverse(get_file_modification_time(p)));
+
+        let found = AtomicUsize::new(0);
+        let mut results: Vec<SessionMatch> = parallel_map(&paths, workers, |path| {
+            search_file(path, &needle, &options, &found, max_results)
+        })
+        .into_iter()
+        .flatten()
+        .collect();
+        results.truncate(max_results);
+        results
+    })
+    .await
+    .map_err(|e| format!("Session search failed: {}", e))
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct SessionSearchOptions {
+    /// Treat the query as a regular expression instead of plain text.
+    #[serde(default)]
+    pub regex: bool,
+    #[serde(rename = "caseSensitive", default)]
+    pub case_sensitive: bool,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct MessageMatch {
+    /// Index into the conversation's `messages`.
+    #[serde(rename = "messageIndex")]
+    pub message_index: usize,
+    #[serde(rename = "messageId")]
+    pub message_id: String,
+    pub role: String,
+    /// Character (not byte) offsets of the hit in the message content, end exclusive.
+    pub start: usize,
+    pub end: usize,
+    pub text: String,
+}
+
+/// Every hit of `query` in one rollout's transcript, in message order, with
+/// offsets precise enough to highlight. Plain queries are matched literally.
+pub fn search_in_session(
+    file_path: &str,
+    query: &str,
+    options: &SessionSearchOptions,
+) -> Result<Vec<MessageMatch>, String> {
+    if query.is_empty() {
+        return Err("Search query cannot be empty".to_string());
+    }
+    let pattern = if options.regex {
+        query.to_string()
+    } else {
+        regex::escape(query)
+    };
+    let matcher = regex::RegexBuilder::new(&pattern)
+        .case_insensitive(!options.case_sensitive)
+        .build()
+        .map_err(|e| format!("Invalid regex: {}", e))?;
+
+    let path = resolve_readable_file(file_path)?;
+    let content = read_to_string_with_retry_blocking(&path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let Some(conversation) = parse_session_content(&content, &path, false) else {
+        return Ok(Vec::new());
+    };
+
+    let mut matches = Vec::new();
+    for (message_index, message) in conversation.messages.iter().enumerate() {
+        let text = &message.content;
+        // Walk hits in order so char offsets are counted incrementally
+        let (mut byte_pos, mut char_pos) = (0, 0);
+        for hit in matcher.find_iter(text).filter(|m| !m.is_empty()) {
+            char_pos += text[byte_pos..hit.start()].chars().count();
+            let len = hit.as_str().chars().count();
+            matches.push(MessageMatch {
+                message_index,
+                message_id: message.id.clone(),
+                role: message.role.clone(),
+                start: char_pos,
+                end: char_pos + len,
+                text: hit.as_str().to_string(),
+            });
+            char_pos += len;
+            byte_pos = hit.end();
+        }
+    }
+    Ok(matches)
+}
@@ -1,21 +1,79 @@
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
 use std::fs;
-use std::path::Path;
+use std::hash::{Hash, Hasher};
+use std::io::{BufRead, Read};
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
 use walkdir::WalkDir;
 
-#[derive(Debug, Serialize, Deserialize)]
+use crate::services::session_index::{file_stamp, SessionIndex};
+use crate::services::{archive, session_index, session_meta};
+use crate::settings::{load_settings, save_settings};
+use crate::utils::disk_space::ensure_space;
+use crate::utils::file::{
+    get_file_modification_time, is_compressed_rollout, is_rollout_file, open_rollout,
+    read_first_and_last_lines, read_to_string_with_retry_blocking, scan_jsonl_files_parallel,
+    scan_rollout_files, sessions_path_in, user_home, COMPRESSED_ROLLOUT_SUFFIX,
+};
+use crate::utils::parallel::parallel_map;
+use crate::utils::time::parse_timestamp;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ChatMessage {
     pub id: String,
     pub role: String,
     pub content: String,
     pub timestamp: i64,
+    /// "tool_call" for entries built from function/shell calls; absent for chat turns.
+    #[serde(
+        rename = "messageType",
+        default,
+        skip_serializing_if = "Option::is_none"
+    )]
+    pub message_type: Option<String>,
+    #[serde(rename = "toolCall", default, skip_serializing_if = "Option::is_none")]
+    pub tool_call: Option<ToolCall>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ToolCallStatus {
+    Completed,
+    /// No output was recorded, e.g. the turn was aborted mid-call.
+    Interrupted,
+}
+
+/// A tool invocation from a rollout, kept apart from chat text so the UI can render it.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ToolCall {
+    #[serde(rename = "callId")]
+    pub call_id: String,
+    pub name: String,
+    /// Arguments as recorded; JSON-encoded argument strings are decoded.
+    pub arguments: serde_json::Value,
+    /// argv for shell-style calls.
+    pub command: Option<Vec<String>>,
+    #[serde(rename = "exitCode")]
+    pub exit_code: Option<i64>,
+    pub output: Option<String>,
+    pub status: ToolCallStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Conversation {
     pub id: String,
     pub title: String,
+    /// Empty in session lists, whether an entry came from the index or was just
+    /// parsed; load it with `load_session` to get the messages.
     pub messages: Vec<ChatMessage>,
+    #[serde(rename = "messageCount", default)]
+    pub message_count: usize,
+    /// Start of the last message, so lists can show it without the messages.
+    #[serde(default)]
+    pub preview: Option<String>,
     pub mode: String,
     #[serde(rename = "createdAt")]
     pub created_at: i64,
@@ -23,10 +81,31 @@ pub struct Conversation {
     pub updated_at: i64,
     #[serde(rename = "isFavorite")]
     pub is_favorite: bool,
+    /// Labels from the session metadata sidecar.
+    #[serde(default)]
+    pub tags: Vec<String>,
     #[serde(rename = "filePath")]
     pub file_path: Option<String>,
     #[serde(rename = "projectRealpath")]
     pub project_realpath: Option<String>,
+    /// Working directory recorded in the rollout metadata (falls back to the environment context)
+    pub cwd: Option<String>,
+}
+
+impl Conversation {
+    /// The list entry for this conversation: everything but the messages, which
+    /// `load_session` returns when the session is opened.
+    pub fn into_summary(mut self) -> Self {
+        self.messages = Vec::new();
+        self
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DuplicateSessionGroup {
+    #[serde(rename = "contentHash")]
+    pub content_hash: String,
+    pub sessions: Vec<Conversation>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -37,9 +116,214 @@ struct SessionRecord {
     message_type: Option<String>,
     role: Option<String>,
     content: Option<serde_json::Value>,
+    cwd: Option<String>,
+    payload: Option<serde_json::Value>,
+}
+
+impl SessionRecord {
+    /// `cwd` from a metadata line, either top-level or inside a `session_meta` payload.
+    fn metadata_cwd(&self) -> Option<String> {
+        self.cwd.clone().or_else(|| {
+            if self.message_type.as_deref() != Some("session_meta") {
+                return None;
+            }
+            self.payload
+                .as_ref()?
+                .get("cwd")?
+                .as_str()
+                .map(|s| s.to_string())
+        })
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TruncateResult {
+    #[serde(rename = "removedMessages")]
+    pub removed_messages: usize,
+    #[serde(rename = "keptMessages")]
+    pub kept_messages: usize,
+    #[serde(rename = "backupPath")]
+    pub backup_path: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionValidationReport {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    #[serde(rename = "totalLines")]
+    pub total_lines: usize,
+    #[serde(rename = "validLines")]
+    pub valid_lines: usize,
+    #[serde(rename = "invalidLines")]
+    pub invalid_lines: usize,
+    /// 1-based line numbers that failed to parse
+    #[serde(rename = "invalidLineNumbers")]
+    pub invalid_line_numbers: Vec<usize>,
+    #[serde(rename = "headerPresent")]
+    pub header_present: bool,
+    #[serde(rename = "headerValid")]
+    pub header_valid: bool,
+    #[serde(rename = "isValid")]
+    pub is_valid: bool,
+}
+
+/// A rollout line that couldn't be read.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParseError {
+    /// 1-based
+    #[serde(rename = "lineNumber")]
+    pub line_number: usize,
+    /// The line as found, with invalid UTF-8 replaced.
+    pub raw: String,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ParsedSession {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    /// Built from the lines that parsed; `None` when those hold no header or no messages.
+    pub conversation: Option<Conversation>,
+    pub errors: Vec<ParseError>,
+    #[serde(rename = "totalLines")]
+    pub total_lines: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionMetadata {
+    pub id: Option<String>,
+    pub model: Option<String>,
+    pub cwd: Option<String>,
+    #[serde(rename = "startTime")]
+    pub start_time: Option<String>,
+    #[serde(rename = "codexVersion")]
+    pub codex_version: Option<String>,
+}
+
+fn extract_content_text(content_value: &serde_json::Value) -> String {
+    if let Some(array) = content_value.as_array() {
+        array
+            .iter()
+            .filter_map(|item| item.as_object().and_then(|obj| obj.get("text")))
+            .filter_map(|text| text.as_str())
+            .collect::<Vec<_>>()
+            .join("")
+    } else if let Some(text) = content_value.as_str() {
+        text.to_string()
+    } else {
+        String::new()
+    }
+}
+
+/// The rollout item on a line: new rollouts wrap it in a `response_item` payload,
+/// older ones store it at the top level.
+pub fn rollout_item(line: &serde_json::Value) -> &serde_json::Value {
+    match line.get("type").and_then(|t| t.as_str()) {
+        Some("response_item") => line.get("payload").unwrap_or(line),
+        _ => line,
+    }
+}
+
+/// Decode a value that may be a JSON document stored as a string.
+fn decode_json_string(value: &serde_json::Value) -> serde_json::Value {
+    value
+        .as_str()
+        .and_then(|s| serde_json::from_str(s).ok())
+        .unwrap_or_else(|| value.clone())
+}
+
+pub fn parse_tool_call(item: &serde_json::Value) -> Option<ToolCall> {
+    let item_type = item.get("type")?.as_str()?;
+    let call_id = item
+        .get("call_id")
+        .or_else(|| item.get("id"))
+        .and_then(|v| v.as_str())
+        .unwrap_or_default()
+        .to_string();
+
+    let (name, arguments) = match item_type {
+        "function_call" => (
+            item.get("name")?.as_str()?.to_string(),
+            item.get("arguments")
+                .map(decode_json_string)
+                .unwrap_or(serde_json::Value::Null),
+        ),
+        "custom_tool_call" => (
+            item.get("name")?.as_str()?.to_string(),
+            item.get("input")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null),
+        ),
+        "local_shell_call" => (
+            "shell".to_string(),
+            item.get("action")
+                .cloned()
+                .unwrap_or(serde_json::Value::Null),
+        ),
+        _ => return None,
+    };
+
+    let command = arguments
+        .get("command")
+        .and_then(|c| c.as_array())
+        .map(|argv| {
+            argv.iter()
+                .filter_map(|a| a.as_str().map(|s| s.to_string()))
+                .collect()
+        });
+
+    Some(ToolCall {
+        call_id,
+        name,
+        arguments,
+        command,
+        exit_code: None,
+        output: None,
+        status: ToolCallStatus::Interrupted,
+    })
+}
+
+/// Fill in a call's result from a `function_call_output` / `custom_tool_call_output` item.
+/// Shell output is usually `{"output": ..., "metadata": {"exit_code": ..}}` encoded as a string.
+fn apply_tool_output(call: &mut ToolCall, item: &serde_json::Value) {
+    let output = item
+        .get("output")
+        .map(decode_json_string)
+        .unwrap_or(serde_json::Value::Null);
+
+    let text = match &output {
+        serde_json::Value::String(text) => Some(text.clone()),
+        serde_json::Value::Object(obj) => obj
+            .get("output")
+            .or_else(|| obj.get("content"))
+            .map(extract_content_text),
+        serde_json::Value::Null => None,
+        other => Some(other.to_string()),
+    };
+
+    call.exit_code = output
+        .get("metadata")
+        .and_then(|m| m.get("exit_code"))
+        .and_then(|c| c.as_i64());
+    call.output = text;
+    call.status = ToolCallStatus::Completed;
+}
+
+fn is_meta_block(content_text: &str) -> bool {
+    content_text.contains("<user_instructions>") || content_text.contains("<environment_context>")
 }
 
 pub fn parse_session_file(content: &str, file_path: &Path) -> Option<Conversation> {
+    parse_session_content(content, file_path, true)
+}
+
+/// Build a conversation from rollout `content`. With `prune_metadata_only`, a
+/// file holding nothing but its header is deleted from disk.
+pub fn parse_session_content(
+    content: &str,
+    file_path: &Path,
+    prune_metadata_only: bool,
+) -> Option<Conversation> {
     let lines: Vec<&str> = content.trim().lines().collect();
     if lines.is_empty() {
         return None;
@@ -49,9 +333,59 @@ pub fn parse_session_file(content: &str, file_path: &Path) -> Option<Conversatio
     let mut session_timestamp = None;
     let mut messages = Vec::new();
     let mut project_realpath: Option<String> = None;
+    let mut metadata_cwd: Option<String> = None;
+    // call_id -> index into `messages`, so outputs can be attached to their call
+    let mut pending_calls: HashMap<String, usize> = HashMap::new();
 
     for line in &lines {
+        if let Ok(value) = serde_json::from_str::<serde_json::Value>(line) {
+            let item = rollout_item(&value);
+            if let Some(call) = parse_tool_call(item) {
+                let timestamp = session_timestamp
+                    .as_deref()
+                    .and_then(|ts| chrono::DateTime::parse_from_rfc3339(ts).ok())
+                    .map(|dt| dt.timestamp_millis())
+                    .unwrap_or_else(|| chrono::Utc::now().timestamp_millis());
+                let content = match &call.command {
+                    Some(argv) => argv.join(" "),
+                    None => call.name.clone(),
+                };
+                pending_calls.insert(call.call_id.clone(), messages.len());
+                messages.push(ChatMessage {
+                    id: format!(
+                        "{}-tool-{}",
+                        session_id.as_ref().unwrap_or(&"unknown".to_string()),
+                        call.call_id
+                    ),
+                    role: "assistant".to_string(),
+                    content,
+                    timestamp,
+                    message_type: Some("tool_call".to_string()),
+                    tool_call: Some(call),
+                });
+                continue;
+            }
+
+            let item_type = item.get("type").and_then(|t| t.as_str());
+            if matches!(
+                item_type,
+                Some("function_call_output") | Some("custom_tool_call_output")
+            ) {
+                let call_id = item.get("call_id").and_then(|c| c.as_str());
+                if let Some(idx) = call_id.and_then(|id| pending_calls.remove(id)) {
+                    if let Some(call) = messages[idx].tool_call.as_mut() {
+                        apply_tool_output(call, item);
+                    }
+                }
+                continue;
+            }
+        }
+
         if let Ok(record) = serde_json::from_str::<SessionRecord>(line) {
+            if metadata_cwd.is_none() {
+                metadata_cwd = record.metadata_cwd();
+            }
+
             // Get session metadata
             if record.id.is_some() && record.timestamp.is_some() {
                 session_id = record.id;
@@ -64,29 +398,7 @@ pub fn parse_session_file(content: &str, file_path: &Path) -> Option<Conversatio
                 && record.content.is_some()
             {
                 let role = record.role.unwrap();
-                let content_value = record.content.unwrap();
-
-                let content_text = if let Some(array) = content_value.as_array() {
-                    array
-                        .iter()
-                        .filter_map(|item| {
-                            if let Some(obj) = item.as_object() {
-                                if let Some(text) = obj.get("text") {
-                                    text.as_str()
-                                } else {
-                                    None
-                                }
-                            } else {
-                                None
-                            }
-                        })
-                        .collect::<Vec<_>>()
-                        .join("")
-                } else if let Some(text) = content_value.as_str() {
-                    text.to_string()
-                } else {
-                    String::new()
-                };
+                let content_text = extract_content_text(&record.content.unwrap());
 
                 // Capture project cwd from environment_context (may be recorded as user message)
                 if project_realpath.is_none() {
@@ -105,8 +417,7 @@ pub fn parse_session_file(content: &str, file_path: &Path) -> Option<Conversatio
 
                 if !content_text.trim().is_empty() {
                     // Filter out meta/system blocks from transcript rendering, but still use them to extract metadata
-                    let is_meta_block = content_text.contains("<user_instructions>")
-                        || content_text.contains("<environment_context>");
+                    let is_meta_block = is_meta_block(&content_text);
 
                     let timestamp = if let Some(ts) = &session_timestamp {
                         chrono::DateTime::parse_from_rfc3339(ts)
@@ -127,6 +438,8 @@ pub fn parse_session_file(content: &str, file_path: &Path) -> Option<Conversatio
                             role,
                             content: content_text.trim().to_string(),
                             timestamp,
+                            message_type: None,
+                            tool_call: None,
                         });
                     }
                 }
@@ -135,7 +448,7 @@ pub fn parse_session_file(content: &str, file_path: &Path) -> Option<Conversatio
     }
 
     // If we only have metadata (one line) and no messages, delete the file
-    if lines.len() == 1 && messages.is_empty() && session_id.is_some() {
+    if prune_metadata_only && lines.len() == 1 && messages.is_empty() && session_id.is_some() {
         if let Err(e) = fs::remove_file(file_path) {
             eprintln!("Failed to delete metadata-only file {:?}: {}", file_path, e);
         } else {
@@ -178,12 +491,16 @@ pub fn parse_session_file(content: &str, file_path: &Path) -> Option<Conversatio
             let convo = Conversation {
                 id: full_session_id,
                 title,
+                message_count: messages.len(),
+                preview: message_preview(&messages),
                 messages,
                 mode: "agent".to_string(),
                 created_at: timestamp,
                 updated_at: timestamp,
                 is_favorite: false,
+                tags: Vec::new(),
                 file_path: file_path_str,
+                cwd: metadata_cwd.or_else(|| project_realpath.clone()),
                 project_realpath,
             };
             /*
@@ -202,50 +519,1273 @@ pub fn parse_session_file(content: &str, file_path: &Path) -> Option<Conversatio
     None
 }
 
-pub async fn load_sessions_from_disk() -> Result<Vec<Conversation>, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let sessions_path = home_dir.join(".codex").join("sessions");
+/// Characters of the last message kept as a conversation's `preview`.
+const PREVIEW_CHARS: usize = 100;
+
+fn message_preview(messages: &[ChatMessage]) -> Option<String> {
+    let content = &messages.last()?.content;
+    Some(if content.chars().count() > PREVIEW_CHARS {
+        format!(
+            "{}...",
+            content.chars().take(PREVIEW_CHARS).collect::<String>()
+        )
+    } else {
+        content.clone()
+    })
+}
+
+fn line_timestamp_millis(line: &str) -> Option<i64> {
+    let record: serde_json::Value = serde_json::from_str(line).ok()?;
+    parse_timestamp(record.get("timestamp")?.as_str()?).ok()
+}
+
+/// `created_at`/`updated_at` from a rollout's first and last lines. When the last
+/// line carries no timestamp, the file modification time is used instead.
+fn rollout_time_bounds(
+    first_line: &str,
+    last_line: &str,
+    file_path: &Path,
+) -> (Option<i64>, Option<i64>) {
+    let updated_at = line_timestamp_millis(last_line).or_else(|| {
+        get_file_modification_time(file_path)
+            .map(|modified| chrono::DateTime::<chrono::Utc>::from(modified).timestamp_millis())
+    });
+    (line_timestamp_millis(first_line), updated_at)
+}
 
+/// Time bounds of the rollout at `file_path`, reading only its first and last lines.
+fn read_rollout_time_bounds(file_path: &Path) -> (Option<i64>, Option<i64>) {
+    match read_first_and_last_lines(file_path) {
+        Ok(Some((first_line, last_line))) => {
+            rollout_time_bounds(&first_line, &last_line, file_path)
+        }
+        _ => (None, None),
+    }
+}
+
+/// Set `created_at`/`updated_at` from the first and last lines of `content`,
+/// the rollout already read from `file_path`.
+fn apply_rollout_time_bounds(conversation: &mut Conversation, content: &str, file_path: &Path) {
+    let mut lines = content.lines().filter(|line| !line.trim().is_empty());
+    let Some(first_line) = lines.next() else {
+        return;
+    };
+    let last_line = lines.next_back().unwrap_or(first_line);
+
+    let (created_at, updated_at) = rollout_time_bounds(first_line, last_line, file_path);
+    if let Some(created_at) = created_at {
+        conversation.created_at = created_at;
+    }
+    if let Some(updated_at) = updated_at {
+        conversation.updated_at = updated_at.max(conversation.created_at);
+    }
+}
+
+fn load_conversation(file_path: &Path) -> Option<Conversation> {
+    match read_to_string_with_retry_blocking(file_path) {
+        Ok(content) => {
+            let mut conversation = parse_session_file(&content, file_path)?;
+            apply_rollout_time_bounds(&mut conversation, &content, file_path);
+            Some(conversation)
+        }
+        Err(e) => {
+            eprintln!("Error reading file {:?}: {}", file_path, e);
+            None
+        }
+    }
+}
+
+/// Server-side ordering for session lists. Ties always break on session id so
+/// the order is stable between loads.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SessionSort {
+    /// Newest first, like `UpdatedAt`.
+    CreatedAt,
+    #[default]
+    UpdatedAt,
+    /// Case-insensitive, A to Z.
+    Title,
+    /// Longest conversations first.
+    MessageCount,
+}
+
+fn sort_conversations(conversations: &mut [Conversation], sort_by: SessionSort) {
+    conversations.sort_by(|a, b| {
+        let primary = match sort_by {
+            SessionSort::CreatedAt => b.created_at.cmp(&a.created_at),
+            SessionSort::UpdatedAt => b.updated_at.cmp(&a.updated_at),
+            SessionSort::Title => a.title.to_lowercase().cmp(&b.title.to_lowercase()),
+            SessionSort::MessageCount => b.message_count.cmp(&a.message_count),
+        };
+        primary.then_with(|| a.id.cmp(&b.id))
+    });
+}
+
+/// Error returned by a session scan that was cancelled before it finished.
+pub const SESSION_LOAD_CANCELLED: &str = "Session load cancelled";
+
+/// Scans in flight: a unique key, the request id the caller passed, and the
+/// flag that cancels the scan.
+type SessionLoads = Vec<(u64, Option<String>, Arc<AtomicBool>)>;
+
+static SESSION_LOADS: Mutex<SessionLoads> = Mutex::new(Vec::new());
+static NEXT_SESSION_LOAD: AtomicU64 = AtomicU64::new(0);
+
+/// One scan's entry in `SESSION_LOADS`, removed again when dropped.
+struct LoadToken {
+    key: u64,
+    cancelled: Arc<AtomicBool>,
+}
+
+impl LoadToken {
+    fn register(request_id: Option<String>) -> Self {
+        let key = NEXT_SESSION_LOAD.fetch_add(1, Ordering::Relaxed);
+        let cancelled = Arc::new(AtomicBool::new(false));
+        if let Ok(mut loads) = SESSION_LOADS.lock() {
+            loads.push((key, request_id, cancelled.clone()));
+        }
+        Self { key, cancelled }
+    }
+
+    fn check(&self) -> Result<(), String> {
+        if self.cancelled.load(Ordering::SeqCst) {
+            return Err(SESSION_LOAD_CANCELLED.to_string());
+        }
+        Ok(())
+    }
+}
+
+impl Drop for LoadToken {
+    fn drop(&mut self) {
+        if let Ok(mut loads) = SESSION_LOADS.lock() {
+            loads.retain(|(key, ..)| *key != self.key);
+        }
+    }
+}
+
+fn cancel_loads_where(matches: impl Fn(Option<&str>) -> bool) -> bool {
+    let Ok(loads) = SESSION_LOADS.lock() else {
+        return false;
+    };
+    let mut found = false;
+    for (_, request_id, cancelled) in loads.iter() {
+        if matches(request_id.as_deref()) {
+            cancelled.store(true, Ordering::SeqCst);
+            found = true;
+        }
+    }
+    found
+}
+
+/// Cancel the scans started with `request_id`, which then fail with
+/// `SESSION_LOAD_CANCELLED`. Returns whether any were running.
+pub fn cancel_session_load(request_id: &str) -> bool {
+    cancel_loads_where(|id| id == Some(request_id))
+}
+
+/// Cancel every scan in flight, whatever its request id.
+pub fn cancel_all_session_loads() {
+    cancel_loads_where(|_| true);
+}
+
+/// One window of the session list, as returned by `load_sessions_page`.
+#[derive(Debug, Serialize)]
+pub struct SessionPage {
+    pub conversations: Vec<Conversation>,
+    /// Rollout files in the sessions directory. Files that don't parse into a
+    /// conversation are counted here but leave their page short.
+    pub total: usize,
+    pub offset: usize,
+    #[serde(rename = "hasMore")]
+    pub has_more: bool,
+}
+
+/// Title a rollout would get, read only up to its first user message.
+fn peek_title(path: &Path) -> String {
+    let Ok(reader) = open_rollout(path) else {
+        return String::new();
+    };
+    reader
+        .lines()
+        .map_while(Result::ok)
+        .filter_map(|line| message_identity(&line))
+        .find(|(role, _)| role == "user")
+        .map(|(_, text)| text.chars().take(50).collect::<String>().to_lowercase())
+        .unwrap_or_default()
+}
+
+/// Order rollout files for `sort_by` from cheap per-file data: the last line's
+/// timestamp for `UpdatedAt`, the timestamped file name for `CreatedAt`, and the
+/// first user message (or stored title) for `Title`. Returns false for orders
+/// that need every message parsed.
+fn sort_session_files(paths: &mut [PathBuf], sort_by: SessionSort, workers: usize) -> bool {
+    match sort_by {
+        SessionSort::UpdatedAt => {
+            let updated = parallel_map(paths, workers, |path| read_rollout_time_bounds(path).1);
+            let mut keyed: Vec<(Option<i64>, PathBuf)> =
+                updated.into_iter().zip(paths.to_vec()).collect();
+            keyed.sort_by(|a, b| b.0.cmp(&a.0).then_with(|| a.1.cmp(&b.1)));
+            for (slot, (_, path)) in paths.iter_mut().zip(keyed) {
+                *slot = path;
+            }
+        }
+        SessionSort::CreatedAt => {
+            paths.sort_by(|a, b| b.file_name().cmp(&a.file_name()));
+        }
+        SessionSort::Title => {
+            let store = session_meta::load_store();
+            let titles = parallel_map(paths, workers, |path| {
+                let stored = path
+                    .to_str()
+                    .and_then(|p| normalize_session_id(p).ok())
+                    .and_then(|id| store.get(&id)?.title.clone());
+                match stored {
+                    Some(title) => title.to_lowercase(),
+                    None => peek_title(path),
+                }
+            });
+            let mut keyed: Vec<(String, PathBuf)> =
+                titles.into_iter().zip(paths.to_vec()).collect();
+            keyed.sort();
+            for (slot, (_, path)) in paths.iter_mut().zip(keyed) {
+                *slot = path;
+            }
+        }
+        SessionSort::MessageCount => return false,
+    }
+    true
+}
+
+/// Load `limit` sessions starting at `offset` in `sort_by` order (or the persisted
+/// `session_sort` setting). Only the files in the window are parsed, except for
+/// `MessageCount`, which has to read every rollout to order them. `request_id`
+/// lets `cancel_session_load` stop this scan without touching others.
+///
+/// Entries are summaries without messages, whether they came from the session
+/// index under `codex_home` or were parsed just now.
+pub async fn load_sessions_page(
+    codex_home: &Path,
+    offset: usize,
+    limit: usize,
+    sort_by: Option<SessionSort>,
+    request_id: Option<String>,
+) -> Result<SessionPage, String> {
+    let token = LoadToken::register(request_id);
+    scan_sessions_page(codex_home, offset, limit, sort_by, &token).await
+}
+
+async fn scan_sessions_page(
+    codex_home: &Path,
+    offset: usize,
+    limit: usize,
+    sort_by: Option<SessionSort>,
+    token: &LoadToken,
+) -> Result<SessionPage, String> {
+    let codex_home = codex_home.to_path_buf();
+    let sessions_path = sessions_path_in(&codex_home);
+
+    if !sessions_path.exists() {
+        return Ok(SessionPage {
+            conversations: Vec::new(),
+            total: 0,
+            offset,
+            has_more: false,
+        });
+    }
+
+    let cancelled = token.cancelled.clone();
+    let is_cancelled = move || cancelled.load(Ordering::SeqCst);
+
+    let settings = load_settings();
+    let workers = settings.scan_worker_count();
+    let sort_by = sort_by.unwrap_or(settings.session_sort);
+
+    let (mut conversations, total) = tokio::task::spawn_blocking(move || {
+        let mut file_paths: Vec<PathBuf> = scan_rollout_files(&sessions_path)
+            .take_while(|_| !is_cancelled())
+            .map(|entry| entry.into_path())
+            .collect();
+        let total = file_paths.len();
+        let presorted = sort_session_files(&mut file_paths, sort_by, workers);
+        let window: &[PathBuf] = if presorted {
+            let start = offset.min(total);
+            &file_paths[start..start.saturating_add(limit).min(total)]
+        } else {
+            &file_paths
+        };
+        let mut index = SessionIndex::load(&codex_home);
+        let loaded = parallel_map(window, workers, |path| {
+            if is_cancelled() {
+                return None;
+            }
+            let stamp = file_stamp(path);
+            if let Some(summary) = stamp.and_then(|stamp| index.lookup(path, stamp)) {
+                return Some((summary, None));
+            }
+            let conversation = load_conversation(path)?;
+            Some((conversation, stamp.map(|stamp| (path.clone(), stamp))))
+        });
+
+        // A cancelled scan may have missed files, so only prune after a full one
+        let mut index_changed = !is_cancelled() && index.retain_paths(&file_paths);
+        let conversations: Vec<Conversation> = loaded
+            .into_iter()
+            .flatten()
+            .map(|(conversation, parsed)| {
+                if let Some((path, stamp)) = parsed {
+                    index.insert(&path, stamp, &conversation);
+                    index_changed = true;
+                }
+                conversation.into_summary()
+            })
+            .collect();
+        if index_changed {
+            if let Err(e) = index.save(&codex_home) {
+                log::warn!("{}", e);
+            }
+        }
+        (conversations, total)
+    })
+    .await
+    .map_err(|e| format!("Session scan failed: {}", e))?;
+    // A cancelled scan has a short file list and a partial window, so return neither
+    token.check()?;
+
+    session_meta::apply_to_conversations(&mut conversations);
+    if sort_by == SessionSort::MessageCount {
+        sort_conversations(&mut conversations, sort_by);
+        conversations = conversations.into_iter().skip(offset).take(limit).collect();
+    }
+
+    Ok(SessionPage {
+        conversations,
+        total,
+        offset,
+        has_more: offset.saturating_add(limit) < total,
+    })
+}
+
+/// One rollout with all its messages, for a session from one of the lists.
+pub async fn load_session(sessions_path: &Path, file_path: String) -> Result<Conversation, String> {
+    let path = resolve_session_file(&canonical_sessions_root(sessions_path)?, &file_path)?;
+    let mut conversation = tokio::task::spawn_blocking(move || load_conversation(&path))
+        .await
+        .map_err(|e| format!("Failed to load session: {}", e))?
+        .ok_or_else(|| format!("'{}' does not contain a conversation", file_path))?;
+    session_meta::apply_to_conversations(std::slice::from_mut(&mut conversation));
+    Ok(conversation)
+}
+
+/// Discard the session index and parse every rollout again. Returns how many
+/// sessions were indexed.
+pub async fn rebuild_session_index(
+    codex_home: &Path,
+    request_id: Option<String>,
+) -> Result<usize, String> {
+    let token = LoadToken::register(request_id);
+    let index_home = codex_home.to_path_buf();
+    tokio::task::spawn_blocking(move || session_index::clear_index(&index_home))
+        .await
+        .map_err(|e| format!("Clearing session index failed: {}", e))??;
+    let page = scan_sessions_page(
+        codex_home,
+        0,
+        usize::MAX,
+        Some(SessionSort::CreatedAt),
+        &token,
+    )
+    .await?;
+    Ok(page.conversations.len())
+}
+
+/// Summaries of every session, ordered by `sort_by` or the persisted
+/// `session_sort` setting.
+pub async fn load_sessions_from_disk(
+    codex_home: &Path,
+    sort_by: Option<SessionSort>,
+    request_id: Option<String>,
+) -> Result<Vec<Conversation>, String> {
+    let sort_by = sort_by.unwrap_or(load_settings().session_sort);
+    let mut conversations =
+        load_sessions_page(codex_home, 0, usize::MAX, Some(sort_by), request_id)
+            .await?
+            .conversations;
+    // File-level keys only approximate the parsed timestamps, so order the full list exactly
+    sort_conversations(&mut conversations, sort_by);
+
+    println!("Total conversations loaded: {}", conversations.len());
+
+    Ok(conversations)
+}
+
+/// Hash only the conversation turns (role + content) so copies of the same
+/// rollout with different ids or timestamps end up in the same bucket.
+fn hash_conversation_content(messages: &[ChatMessage]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    for message in messages {
+        message.role.hash(&mut hasher);
+        message.content.hash(&mut hasher);
+    }
+    hasher.finish()
+}
+
+fn same_content(a: &[ChatMessage], b: &[ChatMessage]) -> bool {
+    a.len() == b.len()
+        && a.iter()
+            .zip(b)
+            .all(|(x, y)| x.role == y.role && x.content == y.content)
+}
+
+/// Split a hash bucket into groups whose turns are actually equal, so a hash
+/// collision never reports different conversations as duplicates.
+fn split_by_content(bucket: Vec<Conversation>) -> Vec<Vec<Conversation>> {
+    let mut groups: Vec<Vec<Conversation>> = Vec::new();
+    for conversation in bucket {
+        match groups
+            .iter_mut()
+            .find(|group| same_content(&group[0].messages, &conversation.messages))
+        {
+            Some(group) => group.push(conversation),
+            None => groups.push(vec![conversation]),
+        }
+    }
+    groups
+}
+
+/// Parse every rollout with its messages, bypassing the session index, newest first.
+async fn load_full_sessions(sessions_path: &Path) -> Result<Vec<Conversation>, String> {
+    let sessions_path = sessions_path.to_path_buf();
     if !sessions_path.exists() {
         return Ok(Vec::new());
     }
+    let workers = load_settings().scan_worker_count();
+    let mut conversations: Vec<Conversation> = tokio::task::spawn_blocking(move || {
+        let paths: Vec<PathBuf> = scan_rollout_files(&sessions_path)
+            .map(|entry| entry.into_path())
+            .collect();
+        parallel_map(&paths, workers, |path| load_conversation(path))
+    })
+    .await
+    .map_err(|e| format!("Session scan failed: {}", e))?
+    .into_iter()
+    .flatten()
+    .collect();
+
+    session_meta::apply_to_conversations(&mut conversations);
+    sort_conversations(&mut conversations, SessionSort::UpdatedAt);
+    Ok(conversations)
+}
 
-    let mut conversations = Vec::new();
+pub async fn find_duplicate_sessions(
+    sessions_path: &Path,
+) -> Result<Vec<DuplicateSessionGroup>, String> {
+    let conversations = load_full_sessions(sessions_path).await?;
 
-    for entry in WalkDir::new(&sessions_path)
+    let mut buckets: HashMap<u64, Vec<Conversation>> = HashMap::new();
+    for conversation in conversations {
+        let hash = hash_conversation_content(&conversation.messages);
+        buckets.entry(hash).or_default().push(conversation);
+    }
+
+    let mut groups: Vec<DuplicateSessionGroup> = buckets
         .into_iter()
-        .filter_map(|e| e.ok())
-        .filter(|e| e.file_type().is_file())
-        .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
-    {
-        match fs::read_to_string(entry.path()) {
-            Ok(content) => {
-                if let Some(conversation) = parse_session_file(&content, entry.path()) {
-                    conversations.push(conversation);
-                }
+        .flat_map(|(hash, bucket)| {
+            split_by_content(bucket)
+                .into_iter()
+                .map(move |sessions| (hash, sessions))
+        })
+        .filter(|(_, sessions)| sessions.len() > 1)
+        .map(|(hash, sessions)| DuplicateSessionGroup {
+            content_hash: format!("{:016x}", hash),
+            sessions,
+        })
+        .collect();
+
+    // Sessions inside a group keep the newest-first order from load_full_sessions,
+    // groups themselves are ordered by their most recent member.
+    groups.sort_by(|a, b| b.sessions[0].updated_at.cmp(&a.sessions[0].updated_at));
+
+    Ok(groups)
+}
+
+/// Canonical lowercase UUID for a session, given a bare uuid, `codex-event-<uuid>`,
+/// a `rollout-<timestamp>-<uuid>.jsonl` file name or a full path to one.
+pub fn normalize_session_id(input: &str) -> Result<String, String> {
+    let trimmed = input.trim();
+    let name = Path::new(trimmed)
+        .file_name()
+        .map(|name| name.to_string_lossy().to_string())
+        .unwrap_or_else(|| trimmed.to_string());
+    let stem = name
+        .strip_suffix(COMPRESSED_ROLLOUT_SUFFIX)
+        .or_else(|| name.strip_suffix(".jsonl"))
+        .unwrap_or(&name);
+    let stem = stem.strip_prefix("codex-event-").unwrap_or(stem);
+
+    // A uuid is 36 characters and always ends the rollout file name
+    let candidate = stem
+        .len()
+        .checked_sub(36)
+        .and_then(|start| stem.get(start..))
+        .unwrap_or(stem);
+    uuid::Uuid::parse_str(candidate)
+        .map(|uuid| uuid.hyphenated().to_string())
+        .map_err(|_| format!("'{}' does not contain a session id", input))
+}
+
+/// Rollout of `session_uuid` under the sessions directory (normally `YYYY/MM/DD`),
+/// matched on the `-<uuid>.jsonl` or `-<uuid>.jsonl.gz` file name suffix.
+pub fn find_rollout_path_for_session(
+    sessions_dir: &Path,
+    session_uuid: &str,
+) -> Result<Option<PathBuf>, String> {
+    if !sessions_dir.exists() {
+        return Ok(None);
+    }
+    let session_uuid = normalize_session_id(session_uuid)?;
+    let plain = format!("-{}.jsonl", session_uuid);
+    let compressed = format!("-{}{}", session_uuid, COMPRESSED_ROLLOUT_SUFFIX);
+    let rollout_path = scan_rollout_files(sessions_dir)
+        .find(|entry| {
+            let name = entry.file_name().to_string_lossy();
+            name.ends_with(&plain) || name.ends_with(&compressed)
+        })
+        .map(|entry| entry.into_path());
+    Ok(rollout_path)
+}
+
+/// Every rollout under `sessions_dir`, found with the same recursive walk as
+/// `find_rollout_path_for_session`, so it doesn't depend on the `YYYY/MM/DD`
+/// nesting. Day directories are walked on up to `workers` threads.
+pub fn list_session_files(sessions_dir: &Path, workers: usize) -> Vec<PathBuf> {
+    if !sessions_dir.exists() {
+        return Vec::new();
+    }
+    scan_jsonl_files_parallel(sessions_dir, workers)
+}
+
+/// Resolve a session id in any form `normalize_session_id` accepts to its rollout file.
+pub fn resolve_rollout_path(sessions_path: &Path, session_id: &str) -> Result<PathBuf, String> {
+    find_rollout_path_for_session(sessions_path, session_id)?
+        .ok_or_else(|| format!("Rollout file for session '{}' not found", session_id))
+}
+
+/// Rewrite a rollout so only the last `keep_last_n` transcript messages remain.
+/// Session metadata and meta blocks (instructions, environment context) are always kept,
+/// and the original file is copied to `<rollout>.bak` before anything is rewritten.
+pub async fn truncate_session(
+    sessions_path: &Path,
+    session_id: String,
+    keep_last_n: usize,
+) -> Result<TruncateResult, String> {
+    let rollout_path = resolve_rollout_path(sessions_path, &session_id)?;
+    if is_compressed_rollout(&rollout_path) {
+        return Err("Session is compressed; resume it before truncating".to_string());
+    }
+    let content = fs::read_to_string(&rollout_path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+
+    // Classify each line: header lines are always retained, message lines are counted.
+    // Newer rollouts wrap messages in `response_item` records, older ones don't.
+    let mut is_header = vec![false; lines.len()];
+    let mut message_indices = Vec::new();
+    for (idx, line) in lines.iter().enumerate() {
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+            continue;
+        };
+        if is_session_header(&value) {
+            is_header[idx] = true;
+            continue;
+        }
+        let item = rollout_item(&value);
+        if item.get("type").and_then(|t| t.as_str()) != Some("message") {
+            continue;
+        }
+        let text = item
+            .get("content")
+            .map(extract_content_text)
+            .unwrap_or_default();
+        if is_meta_block(&text) {
+            is_header[idx] = true;
+        } else if !text.trim().is_empty() {
+            message_indices.push(idx);
+        }
+    }
+
+    let total = message_indices.len();
+    if keep_last_n > total {
+        return Err(format!(
+            "Cannot keep {} messages: session only has {}",
+            keep_last_n, total
+        ));
+    }
+
+    // Everything from the first retained message onwards is kept verbatim
+    let cut = if keep_last_n == 0 {
+        lines.len()
+    } else {
+        message_indices[total - keep_last_n]
+    };
+
+    let backup_path = rollout_path.with_extension("jsonl.bak");
+    fs::copy(&rollout_path, &backup_path)
+        .map_err(|e| format!("Failed to back up session file: {}", e))?;
+
+    let mut truncated = lines
+        .iter()
+        .enumerate()
+        .filter(|(idx, _)| *idx >= cut || is_header[*idx])
+        .map(|(_, line)| *line)
+        .collect::<Vec<_>>()
+        .join("\n");
+    truncated.push('\n');
+
+    // Swap the file in whole so a crash can't leave it half-written
+    let tmp_path = rollout_path.with_extension("jsonl.tmp");
+    fs::write(&tmp_path, truncated)
+        .map_err(|e| format!("Failed to write truncated session file: {}", e))?;
+    fs::rename(&tmp_path, &rollout_path)
+        .map_err(|e| format!("Failed to replace session file: {}", e))?;
+
+    Ok(TruncateResult {
+        removed_messages: total - keep_last_n,
+        kept_messages: keep_last_n,
+        backup_path: backup_path.to_string_lossy().to_string(),
+    })
+}
+
+/// Index of the line holding the last user prompt of a rollout and its text.
+fn last_user_message(lines: &[&str]) -> Option<(usize, String)> {
+    lines
+        .iter()
+        .enumerate()
+        .rev()
+        .find_map(|(idx, line)| match message_identity(line) {
+            Some((role, text)) if role == "user" && !text.is_empty() => Some((idx, text)),
+            _ => None,
+        })
+}
+
+/// Prompt of the last user turn recorded in the rollout at `path`.
+pub fn last_user_prompt(path: &Path) -> Result<String, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read session file: {}", e))?;
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    last_user_message(&lines)
+        .map(|(_, text)| text)
+        .ok_or_else(|| "Session has no previous turn to retry".to_string())
+}
+
+/// Remove the last user prompt of a rollout and everything recorded after it,
+/// so resuming it continues from before that turn. The original file is copied
+/// to `<rollout>.bak` first. Returns the removed prompt.
+pub fn rollback_last_turn(path: &Path) -> Result<String, String> {
+    let content =
+        fs::read_to_string(path).map_err(|e| format!("Failed to read session file: {}", e))?;
+    let lines: Vec<&str> = content.lines().filter(|l| !l.trim().is_empty()).collect();
+    let (mut cut, prompt) = last_user_message(&lines)
+        .ok_or_else(|| "Session has no previous turn to retry".to_string())?;
+    // Newer rollouts may log the prompt as a `user_message` event just before the item
+    if cut > 0 && is_user_message_event(lines[cut - 1]) {
+        cut -= 1;
+    }
+
+    let backup_path = path.with_extension("jsonl.bak");
+    fs::copy(path, &backup_path).map_err(|e| format!("Failed to back up session file: {}", e))?;
+
+    let mut kept = lines[..cut].join("\n");
+    kept.push('\n');
+    fs::write(path, kept).map_err(|e| format!("Failed to write session file: {}", e))?;
+    Ok(prompt)
+}
+
+fn is_user_message_event(line: &str) -> bool {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+        return false;
+    };
+    value.get("type").and_then(|t| t.as_str()) == Some("event_msg")
+        && value
+            .get("payload")
+            .and_then(|p| p.get("type"))
+            .and_then(|t| t.as_str())
+            == Some("user_message")
+}
+
+struct RolloutLines {
+    header: serde_json::Value,
+    started_at: i64,
+    body: Vec<String>,
+}
+
+/// Split a rollout into its header and the remaining lines, verbatim.
+fn read_rollout_lines(path: &Path) -> Result<RolloutLines, String> {
+    let content = read_to_string_with_retry_blocking(path)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+    let mut header = None;
+    let mut body = Vec::new();
+    for line in content.lines().filter(|l| !l.trim().is_empty()) {
+        match serde_json::from_str::<serde_json::Value>(line) {
+            Ok(record) if header.is_none() && is_session_header(&record) => header = Some(record),
+            _ => body.push(line.to_string()),
+        }
+    }
+
+    let header = header.ok_or_else(|| format!("'{}' has no session header", path.display()))?;
+    let started_at = header
+        .get("timestamp")
+        .or_else(|| header.get("payload").and_then(|p| p.get("timestamp")))
+        .and_then(|t| t.as_str())
+        .and_then(|t| parse_timestamp(t).ok())
+        .unwrap_or(0);
+    Ok(RolloutLines {
+        header,
+        started_at,
+        body,
+    })
+}
+
+/// (role, text) of a chat message line, used to spot a repeated boundary message.
+pub fn message_identity(line: &str) -> Option<(String, String)> {
+    let value: serde_json::Value = serde_json::from_str(line).ok()?;
+    let item = rollout_item(&value);
+    if item.get("type")?.as_str()? != "message" {
+        return None;
+    }
+    let role = item.get("role")?.as_str()?.to_string();
+    let text = extract_content_text(item.get("content")?);
+    if is_meta_block(&text) {
+        return None;
+    }
+    Some((role, text.trim().to_string()))
+}
+
+/// Whether a line is an instructions/environment block that a resumed rollout repeats.
+fn is_meta_line(line: &str) -> bool {
+    let Ok(value) = serde_json::from_str::<serde_json::Value>(line) else {
+        return false;
+    };
+    let item = rollout_item(&value);
+    item.get("type").and_then(|t| t.as_str()) == Some("message")
+        && item
+            .get("content")
+            .is_some_and(|content| is_meta_block(&extract_content_text(content)))
+}
+
+/// Write a new rollout holding both sessions' transcripts, earliest first, and
+/// return its id. The originals are left untouched. When the later session opens
+/// with the message the earlier one ended on, that message is kept only once.
+pub async fn merge_sessions(
+    sessions_path: &Path,
+    first_id: String,
+    second_id: String,
+) -> Result<String, String> {
+    let first_path = resolve_rollout_path(sessions_path, &first_id)?;
+    let second_path = resolve_rollout_path(sessions_path, &second_id)?;
+    if first_path == second_path {
+        return Err("Cannot merge a session with itself".to_string());
+    }
+
+    let mut earlier = read_rollout_lines(&first_path)?;
+    let mut later = read_rollout_lines(&second_path)?;
+    if later.started_at < earlier.started_at {
+        std::mem::swap(&mut earlier, &mut later);
+    }
+
+    // The later rollout restates instructions and environment; keep the earlier copy only
+    later.body.retain(|line| !is_meta_line(line));
+    let last_message = earlier
+        .body
+        .iter()
+        .rev()
+        .find_map(|line| message_identity(line));
+    let first_message_idx = later
+        .body
+        .iter()
+        .position(|line| message_identity(line).is_some());
+    if let (Some(last), Some(idx)) = (last_message, first_message_idx) {
+        if message_identity(&later.body[idx]).as_ref() == Some(&last) {
+            later.body.remove(idx);
+        }
+    }
+
+    let new_id = uuid::Uuid::new_v4().to_string();
+    let mut header = earlier.header;
+    set_header_id(&mut header, &new_id);
+    let path = new_rollout_path(sessions_path, &new_id)?;
+
+    let mut lines = vec![header.to_string()];
+    lines.extend(earlier.body);
+    lines.extend(later.body);
+    let mut merged = lines.join("\n");
+    merged.push('\n');
+    fs::write(&path, merged).map_err(|e| format!("Failed to write merged session: {}", e))?;
+
+    log::info!(
+        "Merged {} and {} into {}",
+        first_id,
+        second_id,
+        path.display()
+    );
+    Ok(new_id)
+}
+
+fn header_has_payload_id(payload: &serde_json::Value) -> bool {
+    payload.get("id").and_then(|v| v.as_str()).is_some()
+}
+
+fn set_header_id(header: &mut serde_json::Value, id: &str) {
+    match header.get_mut("payload") {
+        Some(payload) if header_has_payload_id(payload) => payload["id"] = id.into(),
+        _ => header["id"] = id.into(),
+    }
+}
+
+/// Where codex would put a rollout for `id` started now, creating the day directory.
+fn new_rollout_path(sessions_path: &Path, id: &str) -> Result<PathBuf, String> {
+    let now = chrono::Local::now();
+    let dir = sessions_path.join(now.format("%Y/%m/%d").to_string());
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create session directory: {}", e))?;
+    Ok(dir.join(format!(
+        "rollout-{}-{}.jsonl",
+        now.format("%Y-%m-%dT%H-%M-%S"),
+        id
+    )))
+}
+
+/// Copy a rollout under a new UUID so it can be continued separately. Only the
+/// header's id changes; every other line is copied verbatim and in order, and the
+/// original file is only read. No codex process is started for the copy.
+pub async fn fork_session(sessions_path: &Path, file_path: String) -> Result<Conversation, String> {
+    let source = resolve_rollout_path(sessions_path, &file_path)?;
+    let content = read_to_string_with_retry_blocking(&source)
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    let new_id = uuid::Uuid::new_v4().to_string();
+    let mut header_replaced = false;
+    let mut forked: String = content
+        .split_inclusive('\n')
+        .map(|line| {
+            if header_replaced {
+                return line.to_string();
             }
-            Err(e) => {
-                eprintln!("Error reading file {:?}: {}", entry.path(), e);
+            match serde_json::from_str::<serde_json::Value>(line.trim_end()) {
+                Ok(mut record) if is_session_header(&record) => {
+                    header_replaced = true;
+                    set_header_id(&mut record, &new_id);
+                    let ending = &line[line.trim_end_matches(['\r', '\n']).len()..];
+                    format!("{}{}", record, ending)
+                }
+                _ => line.to_string(),
             }
+        })
+        .collect();
+    if !header_replaced {
+        return Err(format!("'{}' has no session header", source.display()));
+    }
+    if !forked.ends_with('\n') {
+        forked.push('\n');
+    }
+
+    let path = new_rollout_path(sessions_path, &new_id)?;
+    fs::write(&path, forked).map_err(|e| format!("Failed to write forked session: {}", e))?;
+
+    match load_conversation(&path) {
+        Some(conversation) => {
+            log::info!("Forked {} into {}", source.display(), path.display());
+            Ok(conversation)
+        }
+        None => {
+            let _ = fs::remove_file(&path);
+            Err("Session has no messages to fork".to_string())
         }
     }
+}
 
-    // Sort by updated_at (newest first)
-    conversations.sort_by(|a, b| b.updated_at.cmp(&a.updated_at));
+/// A rollout header is the session metadata line: either the legacy
+/// `{ "id", "timestamp", ... }` shape or a `session_meta` record with a payload id.
+fn is_session_header(record: &serde_json::Value) -> bool {
+    let has_str =
+        |value: &serde_json::Value, key: &str| value.get(key).and_then(|v| v.as_str()).is_some();
 
-    println!("Total conversations loaded: {}", conversations.len());
+    if has_str(record, "id") && has_str(record, "timestamp") {
+        return true;
+    }
+    record.get("type").and_then(|t| t.as_str()) == Some("session_meta")
+        && record
+            .get("payload")
+            .is_some_and(|payload| has_str(payload, "id"))
+}
+
+/// Read only the header line of a rollout. Handles both the legacy flat header and
+/// the newer `session_meta` record whose fields live under `payload`.
+pub async fn read_session_metadata(
+    sessions_path: &Path,
+    session_id: String,
+) -> Result<SessionMetadata, String> {
+    let rollout_path = resolve_rollout_path(sessions_path, &session_id)?;
+    let mut reader =
+        open_rollout(&rollout_path).map_err(|e| format!("Failed to open session file: {}", e))?;
 
+    let mut header_line = String::new();
+    reader
+        .read_line(&mut header_line)
+        .map_err(|e| format!("Failed to read session header: {}", e))?;
+
+    let record: serde_json::Value = serde_json::from_str(header_line.trim())
+        .map_err(|e| format!("Session header is not valid JSON: {}", e))?;
+    if !is_session_header(&record) {
+        return Err("First line of the session file is not a session header".to_string());
+    }
+
+    let header = match record.get("payload") {
+        Some(payload) if record.get("type").and_then(|t| t.as_str()) == Some("session_meta") => {
+            payload
+        }
+        _ => &record,
+    };
+    let field = |key: &str| {
+        header
+            .get(key)
+            .and_then(|v| v.as_str())
+            .map(|s| s.to_string())
+    };
+
+    Ok(SessionMetadata {
+        id: field("id"),
+        model: field("model"),
+        cwd: field("cwd"),
+        start_time: field("timestamp").or_else(|| {
+            record
+                .get("timestamp")
+                .and_then(|v| v.as_str())
+                .map(|s| s.to_string())
+        }),
+        codex_version: field("cli_version").or_else(|| field("version")),
+    })
+}
+
+/// Check a rollout without modifying it: every non-blank line must be valid JSON
+/// and the first line must be a well-formed session header.
+pub async fn validate_session_file(
+    sessions_path: &Path,
+    session_id: String,
+) -> Result<SessionValidationReport, String> {
+    let rollout_path = resolve_rollout_path(sessions_path, &session_id)?;
+    let mut bytes = Vec::new();
+    open_rollout(&rollout_path)
+        .and_then(|mut reader| reader.read_to_end(&mut bytes))
+        .map_err(|e| format!("Failed to read session file: {}", e))?;
+
+    let mut total_lines = 0;
+    let mut invalid_line_numbers = Vec::new();
+    let mut header_present = false;
+    let mut header_valid = false;
+
+    for (idx, raw_line) in bytes.split(|b| *b == b'\n').enumerate() {
+        let Ok(line) = std::str::from_utf8(raw_line) else {
+            total_lines += 1;
+            invalid_line_numbers.push(idx + 1);
+            continue;
+        };
+        if line.trim().is_empty() {
+            continue;
+        }
+        total_lines += 1;
+
+        let parsed = serde_json::from_str::<serde_json::Value>(line);
+        if total_lines == 1 {
+            header_present = true;
+            header_valid = parsed.as_ref().is_ok_and(is_session_header);
+        }
+        if parsed.is_err() {
+            invalid_line_numbers.push(idx + 1);
+        }
+    }
+
+    let invalid_lines = invalid_line_numbers.len();
+    Ok(SessionValidationReport {
+        file_path: rollout_path.to_string_lossy().to_string(),
+        total_lines,
+        valid_lines: total_lines - invalid_lines,
+        invalid_lines,
+        invalid_line_numbers,
+        header_present,
+        header_valid,
+        is_valid: header_valid && invalid_lines == 0,
+    })
+}
+
+/// Parse a rollout line by line, keeping whatever reads cleanly and reporting
+/// the rest, so a file cut short by a crash can still be opened. Only rollouts
+/// under the sessions directory are read.
+pub async fn read_session_parsed(
+    sessions_path: &Path,
+    file_path: String,
+) -> Result<ParsedSession, String> {
+    let path = resolve_session_file(&canonical_sessions_root(sessions_path)?, &file_path)?;
+    let mut bytes = Vec::new();
+    let mut reader =
+        open_rollout(&path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    // A gzip stream cut short still yields everything before the cut
+    if let Err(e) = reader.read_to_end(&mut bytes) {
+        if bytes.is_empty() {
+            return Err(format!("Failed to read session file: {}", e));
+        }
+        log::warn!("Session file {} ends early: {}", path.display(), e);
+    }
+
+    let mut total_lines = 0;
+    let mut valid = Vec::new();
+    let mut errors = Vec::new();
+    for (idx, raw_line) in bytes.split(|b| *b == b'\n').enumerate() {
+        let reason = match std::str::from_utf8(raw_line) {
+            Ok(line) if line.trim().is_empty() => continue,
+            Ok(line) => match serde_json::from_str::<serde_json::Value>(line) {
+                Ok(_) => {
+                    total_lines += 1;
+                    valid.push(line);
+                    continue;
+                }
+                Err(e) => e.to_string(),
+            },
+            Err(e) => format!("Invalid UTF-8: {}", e),
+        };
+        total_lines += 1;
+        errors.push(ParseError {
+            line_number: idx + 1,
+            raw: String::from_utf8_lossy(raw_line).trim_end().to_string(),
+            reason,
+        });
+    }
+
+    // Never prune here: a header whose every other line is corrupt is exactly what's being recovered
+    let content = valid.join("\n");
+    let conversation = parse_session_content(&content, &path, false).map(|mut c| {
+        apply_rollout_time_bounds(&mut c, &content, &path);
+        session_meta::apply_to_conversations(std::slice::from_mut(&mut c));
+        c
+    });
+
+    Ok(ParsedSession {
+        file_path: path.to_string_lossy().to_string(),
+        conversation,
+        errors,
+        total_lines,
+    })
+}
+
+/// Canonical form of a directory for comparisons, so symlinked roots still match.
+fn normalize_dir(path: &str) -> PathBuf {
+    let trimmed = path.trim_end_matches(['/', '\\']);
+    let trimmed = if trimmed.is_empty() { path } else { trimmed };
+    fs::canonicalize(trimmed).unwrap_or_else(|_| PathBuf::from(trimmed))
+}
+
+pub async fn list_sessions_for_cwd(
+    codex_home: &Path,
+    cwd: String,
+    request_id: Option<String>,
+) -> Result<Vec<Conversation>, String> {
+    let target = normalize_dir(&cwd);
+    let conversations = load_sessions_from_disk(codex_home, None, request_id).await?;
+
+    Ok(conversations
+        .into_iter()
+        .filter(|c| {
+            c.cwd
+                .as_deref()
+                .is_some_and(|dir| normalize_dir(dir) == target)
+        })
+        .collect())
+}
+
+/// Sessions tagged with any of `tags`. Matching ids come from the metadata
+/// sidecar, so only the tagged rollouts are parsed.
+pub async fn list_sessions_by_tag(
+    sessions_path: &Path,
+    tags: Vec<String>,
+) -> Result<Vec<Conversation>, String> {
+    let ids = session_meta::tagged_session_ids(&tags);
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+    let sessions_path = sessions_path.to_path_buf();
+    if !sessions_path.exists() {
+        return Ok(Vec::new());
+    }
+
+    let settings = load_settings();
+    let workers = settings.scan_worker_count();
+    let mut conversations: Vec<Conversation> = tokio::task::spawn_blocking(move || {
+        let paths: Vec<PathBuf> = scan_rollout_files(&sessions_path)
+            .map(|entry| entry.into_path())
+            .filter(|path| {
+                path.to_str()
+                    .and_then(|p| normalize_session_id(p).ok())
+                    .is_some_and(|id| ids.contains(&id))
+            })
+            .collect();
+        parallel_map(&paths, workers, |path| load_conversation(path))
+    })
+    .await
+    .map_err(|e| format!("Session scan failed: {}", e))?
+    .into_iter()
+    .flatten()
+    .map(Conversation::into_summary)
+    .collect();
+
+    session_meta::apply_to_conversations(&mut conversations);
+    conversations.retain(|c| session_meta::has_any_tag(c, &tags));
+    sort_conversations(&mut conversations, settings.session_sort);
     Ok(conversations)
 }
 
-pub async fn delete_session_file(file_path: String) -> Result<(), String> {
-    fs::remove_file(&file_path).map_err(|e| format!("Failed to delete file '{}': {}", file_path, e))
+/// Canonical path of a rollout, refusing anything outside the sessions directory
+/// or not a `.jsonl` file.
+fn resolve_session_file(sessions_root: &Path, file_path: &str) -> Result<PathBuf, String> {
+    let path = fs::canonicalize(file_path)
+        .map_err(|e| format!("Failed to resolve '{}': {}", file_path, e))?;
+    if !path.starts_with(sessions_root) {
+        return Err(format!("'{}' is outside the sessions directory", file_path));
+    }
+    if !path.is_file() || !is_rollout_file(&path) {
+        return Err(format!("'{}' is not a session file", file_path));
+    }
+    Ok(path)
 }
 
-pub async fn get_latest_session_id() -> Result<Option<String>, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    let sessions_path = home_dir.join(".codex").join("sessions");
+pub fn canonical_sessions_root(sessions_path: &Path) -> Result<PathBuf, String> {
+    fs::canonicalize(sessions_path)
+        .map_err(|e| format!("Failed to resolve sessions directory: {}", e))
+}
 
+pub async fn delete_session_file(sessions_path: &Path, file_path: String) -> Result<(), String> {
+    let path = resolve_session_file(&canonical_sessions_root(sessions_path)?, &file_path)?;
+    fs::remove_file(&path).map_err(|e| format!("Failed to delete file '{}': {}", file_path, e))
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct BulkDeleteOptions {
+    /// Move the files under `~/.codex/archive` instead of deleting them.
+    #[serde(default)]
+    pub archive: bool,
+    /// Must be `delete-<number of paths>` for a permanent delete.
+    #[serde(default, rename = "confirmToken")]
+    pub confirm_token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkDeleteItem {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    pub ok: bool,
+    pub error: Option<String>,
+    /// Where the file went when archiving.
+    #[serde(rename = "archivedTo")]
+    pub archived_to: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BulkDeleteResult {
+    pub results: Vec<BulkDeleteItem>,
+    pub succeeded: usize,
+    pub failed: usize,
+}
+
+/// Move `path` to the same relative place under `archive_root`, never overwriting.
+fn archive_session_file(
+    path: &Path,
+    sessions_root: &Path,
+    archive_root: &Path,
+) -> Result<PathBuf, String> {
+    let relative = path.strip_prefix(sessions_root).unwrap_or(path);
+    let mut dest = archive_root.join(relative);
+    if dest.exists() {
+        let stem = dest
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_default();
+        dest.set_file_name(format!(
+            "{}-{}.jsonl",
+            stem,
+            chrono::Utc::now().timestamp_millis()
+        ));
+    }
+    if let Some(parent) = dest.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create archive directory: {}", e))?;
+    }
+    if fs::rename(path, &dest).is_err() {
+        // Archive on another device
+        copy_verified(path, &dest)?;
+        fs::remove_file(path)
+            .map_err(|e| format!("Failed to remove '{}': {}", path.display(), e))?;
+    }
+    Ok(dest)
+}
+
+/// Delete or archive several rollouts, reporting each one separately so one
+/// failure doesn't stop the rest. Permanent deletes need
+/// `confirm_token == "delete-<count>"`, so a stale or wrong selection can't go through.
+pub async fn delete_sessions(
+    codex_home: &Path,
+    file_paths: Vec<String>,
+    options: Option<BulkDeleteOptions>,
+) -> Result<BulkDeleteResult, String> {
+    let options = options.unwrap_or_default();
+    if !options.archive {
+        let expected = format!("delete-{}", file_paths.len());
+        if options.confirm_token.as_deref() != Some(expected.as_str()) {
+            return Err(format!(
+                "Permanent delete needs confirmToken '{}'",
+                expected
+            ));
+        }
+    }
+
+    let sessions_root = canonical_sessions_root(&sessions_path_in(codex_home))?;
+    let archive_root = archive::archive_root(codex_home);
+
+    let results: Vec<BulkDeleteItem> = file_paths
+        .into_iter()
+        .map(|file_path| {
+            let outcome = resolve_session_file(&sessions_root, &file_path).and_then(|path| {
+                if options.archive {
+                    archive_session_file(&path, &sessions_root, &archive_root).map(Some)
+                } else {
+                    fs::remove_file(&path)
+                        .map(|_| None)
+                        .map_err(|e| format!("Failed to delete file '{}': {}", file_path, e))
+                }
+            });
+            match outcome {
+                Ok(archived_to) => BulkDeleteItem {
+                    file_path,
+                    ok: true,
+                    error: None,
+                    archived_to: archived_to.map(|p| p.to_string_lossy().to_string()),
+                },
+                Err(e) => BulkDeleteItem {
+                    file_path,
+                    ok: false,
+                    error: Some(e),
+                    archived_to: None,
+                },
+            }
+        })
+        .collect();
+
+    let succeeded = results.iter().filter(|r| r.ok).count();
+    Ok(BulkDeleteResult {
+        failed: results.len() - succeeded,
+        succeeded,
+        results,
+    })
+}
+
+pub async fn get_latest_session_id(sessions_path: &Path) -> Result<Option<String>, String> {
     if !sessions_path.exists() {
         return Ok(None);
     }
@@ -253,7 +1793,7 @@ pub async fn get_latest_session_id() -> Result<Option<String>, String> {
     let mut latest_file: Option<(std::path::PathBuf, std::time::SystemTime)> = None;
 
     // Find the most recently modified .jsonl file
-    for entry in WalkDir::new(&sessions_path)
+    for entry in WalkDir::new(sessions_path)
         .into_iter()
         .filter_map(|e| e.ok())
         .filter(|e| e.file_type().is_file())
@@ -292,3 +1832,301 @@ pub async fn get_latest_session_id() -> Result<Option<String>, String> {
 
     Ok(None)
 }
+
+/// Copy `src` to `dest` and confirm the copy has the same length before the caller removes `src`.
+fn copy_verified(src: &Path, dest: &Path) -> Result<(), String> {
+    let expected = fs::metadata(src)
+        .map_err(|e| format!("Failed to read '{}': {}", src.display(), e))?
+        .len();
+    let copied =
+        fs::copy(src, dest).map_err(|e| format!("Failed to copy '{}': {}", src.display(), e))?;
+    if copied != expected {
+        return Err(format!(
+            "Copy of '{}' is incomplete ({} of {} bytes)",
+            src.display(),
+            copied,
+            expected
+        ));
+    }
+    Ok(())
+}
+
+/// Point `default_dir` at `target` with a symlink. A link left by an earlier
+/// relocation, dangling or not, is replaced; a real directory is left alone.
+#[cfg(unix)]
+fn link_default_sessions_dir(default_dir: &Path, target: &Path) -> Result<(), String> {
+    match fs::symlink_metadata(default_dir) {
+        Ok(metadata) if metadata.file_type().is_symlink() => {
+            if fs::read_link(default_dir).is_ok_and(|current| current == target) {
+                return Ok(());
+            }
+            fs::remove_file(default_dir)
+                .map_err(|e| format!("Failed to remove old link: {}", e))?;
+        }
+        Ok(_) => {
+            return Err(format!(
+                "{} is a directory, not a link",
+                default_dir.display()
+            ))
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+        Err(e) => return Err(e.to_string()),
+    }
+    std::os::unix::fs::symlink(target, default_dir).map_err(|e| e.to_string())
+}
+
+/// `new_base` with a leading `~/` expanded, made absolute.
+fn relocation_target(new_base: &str) -> Result<PathBuf, String> {
+    let expanded = match new_base.strip_prefix("~/") {
+        Some(rest) => user_home()?.join(rest),
+        None => PathBuf::from(new_base),
+    };
+    std::path::absolute(&expanded)
+        .map_err(|e| format!("Invalid target directory '{}': {}", new_base, e))
+}
+
+/// Move the sessions tree to `new_base` and record it in settings. Uses a rename
+/// when possible and falls back to copy, verify and remove across devices.
+/// Returns the number of files moved.
+///
+/// codex always writes to `<codex home>/sessions`, so that path is replaced with
+/// a symlink to the new location. Without symlinks codex would keep writing to
+/// the old directory, so relocation is refused off unix.
+pub fn relocate_sessions_store(codex_home: &Path, new_base: &str) -> Result<usize, String> {
+    if !cfg!(unix) {
+        return Err(
+            "Relocating the sessions store is only supported on platforms with symlinks"
+                .to_string(),
+        );
+    }
+    let current = sessions_path_in(codex_home);
+    let target = relocation_target(new_base)?;
+
+    if target.exists() && fs::read_dir(&target).is_ok_and(|mut d| d.next().is_some()) {
+        return Err("Target directory must be empty".to_string());
+    }
+    if let (Ok(current_real), Some(parent)) = (current.canonicalize(), target.parent()) {
+        if parent
+            .canonicalize()
+            .is_ok_and(|p| p.starts_with(&current_real))
+        {
+            return Err("Target cannot be inside the current sessions directory".to_string());
+        }
+    }
+
+    let files: Vec<PathBuf> = if current.exists() {
+        WalkDir::new(&current)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(|e| e.file_type().is_file())
+            .map(|e| e.into_path())
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    if let Some(parent) = target.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create target directory: {}", e))?;
+    }
+    if target.exists() {
+        // Empty target left by the user; rename can't replace it on every platform
+        let _ = fs::remove_dir(&target);
+    }
+
+    let mut copied = false;
+    if current.exists() && fs::rename(&current, &target).is_err() {
+        // Likely a different device: copy everything first, only then remove the originals
+        let total_bytes: u64 = files
+            .iter()
+            .filter_map(|f| fs::metadata(f).ok())
+            .map(|m| m.len())
+            .sum();
+        ensure_space(&target, total_bytes)?;
+        for file in &files {
+            let relative = file
+                .strip_prefix(&current)
+                .map_err(|e| format!("Failed to resolve '{}': {}", file.display(), e))?;
+            let dest = target.join(relative);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)
+                    .map_err(|e| format!("Failed to create directory: {}", e))?;
+            }
+            copy_verified(file, &dest)?;
+        }
+        copied = true;
+    }
+    fs::create_dir_all(&target).map_err(|e| format!("Failed to create target directory: {}", e))?;
+
+    // Record the new location before removing anything, so a failure below
+    // never leaves settings pointing at a deleted tree
+    let mut settings = load_settings();
+    settings.sessions_dir = Some(target.to_string_lossy().to_string());
+    save_settings(&settings)?;
+
+    if copied {
+        fs::remove_dir_all(&current).map_err(|e| {
+            format!(
+                "Copied sessions but failed to remove the old directory: {}",
+                e
+            )
+        })?;
+    }
+
+    // codex itself always writes to ~/.codex/sessions; point that at the new location
+    #[cfg(unix)]
+    {
+        let default_dir = codex_home.join("sessions");
+        if default_dir != target {
+            if let Err(e) = link_default_sessions_dir(&default_dir, &target) {
+                log::warn!(
+                    "Failed to link {} to the new sessions store: {}",
+                    default_dir.display(),
+                    e
+                );
+            }
+        }
+    }
+
+    log::info!(
+        "Relocated {} session files to {}",
+        files.len(),
+        target.display()
+    );
+    Ok(files.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FIRST: &str = "0199a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5b";
+    const SECOND: &str = "0199a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5c";
+    const THIRD: &str = "0199a1b2-c3d4-7e5f-8a9b-0c1d2e3f4a5d";
+
+    fn touch(path: &Path) {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, "{}\n").unwrap();
+    }
+
+    #[test]
+    fn session_files_are_found_below_an_extra_nesting_level() {
+        let dir = tempfile::tempdir().unwrap();
+        let day = dir.path().join("2025/01/02");
+        let expected = vec![
+            day.join(format!("rollout-2025-01-02T10-00-00-{}.jsonl", FIRST)),
+            day.join(format!(
+                "host-a/rollout-2025-01-02T11-00-00-{}.jsonl",
+                SECOND
+            )),
+            day.join(format!(
+                "host-a/nested/rollout-2025-01-02T12-00-00-{}.jsonl.gz",
+                THIRD
+            )),
+        ];
+        for path in &expected {
+            touch(path);
+        }
+        touch(&day.join("host-a/notes.txt"));
+
+        for workers in [1, 4] {
+            let mut found = list_session_files(dir.path(), workers);
+            found.sort();
+            let mut expected = expected.clone();
+            expected.sort();
+            assert_eq!(found, expected, "with {} workers", workers);
+        }
+        assert_eq!(
+            find_rollout_path_for_session(dir.path(), SECOND).unwrap(),
+            Some(expected[1].clone())
+        );
+        assert_eq!(
+            find_rollout_path_for_session(dir.path(), THIRD).unwrap(),
+            Some(expected[2].clone())
+        );
+    }
+
+    #[test]
+    fn missing_sessions_directory_lists_nothing() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(list_session_files(&dir.path().join("sessions"), 2).is_empty());
+    }
+
+    #[test]
+    fn normalize_session_id_accepts_every_input_form() {
+        let rollout = format!("rollout-2025-01-02T10-00-00-{}.jsonl", FIRST);
+        let path = Path::new("/home/me/.codex/sessions/2025/01/02").join(&rollout);
+        let inputs = [
+            FIRST.to_string(),
+            FIRST.to_uppercase(),
+            format!("  {}\n", FIRST),
+            format!("codex-event-{}", FIRST),
+            rollout.clone(),
+            format!("{}.gz", rollout),
+            path.to_string_lossy().to_string(),
+        ];
+        for input in inputs {
+            assert_eq!(
+                normalize_session_id(&input).as_deref(),
+                Ok(FIRST),
+                "{:?}",
+                input
+            );
+        }
+    }
+
+    #[test]
+    fn normalize_session_id_rejects_input_without_an_id() {
+        for input in [
+            "",
+            "rollout-2025-01-02.jsonl",
+            "codex-event-123",
+            "not-a-uuid",
+        ] {
+            assert!(normalize_session_id(input).is_err(), "{:?}", input);
+        }
+    }
+
+    fn conversation_with(id: &str, turns: &[(&str, &str)]) -> Conversation {
+        Conversation {
+            id: id.to_string(),
+            title: String::new(),
+            messages: turns
+                .iter()
+                .map(|(role, content)| ChatMessage {
+                    id: String::new(),
+                    role: role.to_string(),
+                    content: content.to_string(),
+                    timestamp: 0,
+                    message_type: None,
+                    tool_call: None,
+                })
+                .collect(),
+            message_count: turns.len(),
+            preview: None,
+            mode: "agent".to_string(),
+            created_at: 0,
+            updated_at: 0,
+            is_favorite: false,
+            tags: Vec::new(),
+            file_path: None,
+            project_realpath: None,
+            cwd: None,
+        }
+    }
+
+    #[test]
+    fn a_shared_bucket_only_groups_equal_content() {
+        let bucket = vec![
+            conversation_with("a", &[("user", "hi"), ("assistant", "hello")]),
+            conversation_with("b", &[("user", "hi"), ("assistant", "bye")]),
+            conversation_with("c", &[("user", "hi"), ("assistant", "hello")]),
+            conversation_with("d", &[("assistant", "hi"), ("user", "hello")]),
+        ];
+        let groups: Vec<Vec<String>> = split_by_content(bucket)
+            .into_iter()
+            .map(|group| group.into_iter().map(|c| c.id).collect())
+            .collect();
+        assert_eq!(groups, vec![vec!["a", "c"], vec!["b"], vec!["d"]]);
+    }
+}
@@ -0,0 +1,172 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::services::session::{normalize_session_id, Conversation};
+
+/// Bump whenever `IndexEntry` changes shape; an index with another version is
+/// discarded and rebuilt.
+const INDEX_VERSION: u32 = 3;
+
+/// What the session list shows for a rollout, with the file stamp it was read
+/// at. Messages aren't kept; `load_session` parses them when a session is opened.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IndexEntry {
+    /// Unix millis of the file's modification time.
+    pub mtime: i64,
+    pub size: u64,
+    pub uuid: String,
+    pub title: String,
+    #[serde(rename = "messageCount")]
+    pub message_count: usize,
+    /// Start of the last message.
+    pub preview: Option<String>,
+    #[serde(rename = "createdAt")]
+    pub created_at: i64,
+    #[serde(rename = "updatedAt")]
+    pub updated_at: i64,
+    #[serde(rename = "projectRealpath")]
+    pub project_realpath: Option<String>,
+    pub cwd: Option<String>,
+}
+
+/// Rollout summaries keyed by file path, so unchanged files aren't parsed again
+/// on the next load.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SessionIndex {
+    version: u32,
+    entries: HashMap<String, IndexEntry>,
+}
+
+impl Default for SessionIndex {
+    fn default() -> Self {
+        Self {
+            version: INDEX_VERSION,
+            entries: HashMap::new(),
+        }
+    }
+}
+
+pub fn get_session_index_path(codex_home: &Path) -> PathBuf {
+    codex_home.join("codexia_index.json")
+}
+
+/// Modification time and size of `path`. Size is compared too, because some
+/// filesystems only keep mtimes to the second.
+pub fn file_stamp(path: &Path) -> Option<(i64, u64)> {
+    let metadata = fs::metadata(path).ok()?;
+    let modified = metadata.modified().ok()?;
+    Some((
+        chrono::DateTime::<chrono::Utc>::from(modified).timestamp_millis(),
+        metadata.len(),
+    ))
+}
+
+impl SessionIndex {
+    /// Load the index kept in `codex_home`, starting over when it is missing,
+    /// unreadable or from another version.
+    pub fn load(codex_home: &Path) -> Self {
+        let Ok(content) = fs::read_to_string(get_session_index_path(codex_home)) else {
+            return Self::default();
+        };
+        match serde_json::from_str::<SessionIndex>(&content) {
+            Ok(index) if index.version == INDEX_VERSION => index,
+            Ok(index) => {
+                log::info!(
+                    "Session index version {} is outdated, rebuilding it",
+                    index.version
+                );
+                Self::default()
+            }
+            Err(e) => {
+                log::warn!("Failed to parse session index, rebuilding it: {}", e);
+                Self::default()
+            }
+        }
+    }
+
+    /// Write the index through a temporary file so a crash never leaves it half-written.
+    pub fn save(&self, codex_home: &Path) -> Result<(), String> {
+        let path = get_session_index_path(codex_home);
+        let content = serde_json::to_string(self)
+            .map_err(|e| format!("Failed to serialize session index: {}", e))?;
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create index directory: {}", e))?;
+        }
+
+        let tmp_path = path.with_extension("json.tmp");
+        fs::write(&tmp_path, content)
+            .map_err(|e| format!("Failed to write session index: {}", e))?;
+        fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to replace session index: {}", e))
+    }
+
+    /// Summary of `path`, if the file still has `stamp`.
+    pub fn lookup(&self, path: &Path, stamp: (i64, u64)) -> Option<Conversation> {
+        let entry = self.entries.get(path.to_string_lossy().as_ref())?;
+        if (entry.mtime, entry.size) != stamp {
+            return None;
+        }
+        Some(Conversation {
+            id: format!("codex-event-{}", entry.uuid),
+            title: entry.title.clone(),
+            messages: Vec::new(),
+            message_count: entry.message_count,
+            preview: entry.preview.clone(),
+            mode: "agent".to_string(),
+            created_at: entry.created_at,
+            updated_at: entry.updated_at,
+            is_favorite: false,
+            tags: Vec::new(),
+            file_path: fs::canonicalize(path)
+                .ok()
+                .and_then(|p| p.to_str().map(|s| s.to_string())),
+            project_realpath: entry.project_realpath.clone(),
+            cwd: entry.cwd.clone(),
+        })
+    }
+
+    /// Record `conversation`, parsed from `path` before the metadata sidecar is
+    /// applied. Files without a session id in their name aren't indexed.
+    pub fn insert(&mut self, path: &Path, stamp: (i64, u64), conversation: &Conversation) {
+        let Some(uuid) = path.to_str().and_then(|p| normalize_session_id(p).ok()) else {
+            return;
+        };
+        let entry = IndexEntry {
+            mtime: stamp.0,
+            size: stamp.1,
+            uuid,
+            title: conversation.title.clone(),
+            message_count: conversation.message_count,
+            preview: conversation.preview.clone(),
+            created_at: conversation.created_at,
+            updated_at: conversation.updated_at,
+            project_realpath: conversation.project_realpath.clone(),
+            cwd: conversation.cwd.clone(),
+        };
+        self.entries
+            .insert(path.to_string_lossy().to_string(), entry);
+    }
+
+    /// Drop entries for files not in `paths`. Returns whether any were dropped.
+    pub fn retain_paths(&mut self, paths: &[PathBuf]) -> bool {
+        let before = self.entries.len();
+        let keep: HashSet<String> = paths
+            .iter()
+            .map(|p| p.to_string_lossy().to_string())
+            .collect();
+        self.entries.retain(|path, _| keep.contains(path));
+        self.entries.len() != before
+    }
+}
+
+/// Delete the index file in `codex_home` so the next load parses every rollout again.
+pub fn clear_index(codex_home: &Path) -> Result<(), String> {
+    match fs::remove_file(get_session_index_path(codex_home)) {
+        Ok(()) => Ok(()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+        Err(e) => Err(format!("Failed to remove session index: {}", e)),
+    }
+}
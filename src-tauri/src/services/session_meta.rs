@@ -0,0 +1,372 @@
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::services::session::{normalize_session_id, Conversation};
+use crate::utils::file::{default_codex_home, scan_rollout_files};
+
+/// Codexia's own per-session metadata, keyed by the session UUID. Codex never
+/// reads it, so rollout files stay untouched.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SessionMetaEntry {
+    /// Title chosen by the user, shown instead of the derived one.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    #[serde(default, rename = "isFavorite")]
+    pub is_favorite: bool,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+impl SessionMetaEntry {
+    fn is_empty(&self) -> bool {
+        self.title.is_none() && !self.is_favorite && self.tags.is_empty()
+    }
+}
+
+pub type SessionMetaStore = BTreeMap<String, SessionMetaEntry>;
+
+#[derive(Debug, Serialize)]
+pub struct MetadataRepairReport {
+    /// Session ids dropped because their rollout file no longer exists.
+    #[serde(rename = "removedMissing")]
+    pub removed_missing: Vec<String>,
+    /// Entries that couldn't be parsed and were discarded.
+    #[serde(rename = "droppedMalformed")]
+    pub dropped_malformed: usize,
+    /// The file itself wasn't valid JSON and was rebuilt from what could be salvaged.
+    pub salvaged: bool,
+    pub kept: usize,
+}
+
+pub fn get_session_meta_path() -> Result<PathBuf, String> {
+    Ok(default_codex_home()?.join("codexia_session_meta.json"))
+}
+
+/// Store key for a conversation id, which may carry the `codex-event-` prefix.
+pub fn session_key(session_id: &str) -> &str {
+    session_id
+        .strip_prefix("codex-event-")
+        .unwrap_or(session_id)
+}
+
+/// Load the store, treating a missing or unreadable file as empty.
+pub fn load_store() -> SessionMetaStore {
+    let Ok(path) = get_session_meta_path() else {
+        return SessionMetaStore::new();
+    };
+    let Ok(content) = fs::read_to_string(&path) else {
+        return SessionMetaStore::new();
+    };
+    serde_json::from_str(&content).unwrap_or_else(|e| {
+        log::warn!("Failed to parse session metadata, ignoring it: {}", e);
+        SessionMetaStore::new()
+    })
+}
+
+/// Load the store to change it. Unlike `load_store`, a file that doesn't parse is
+/// an error, so saving can't overwrite entries `repair_session_metadata` could
+/// still salvage.
+fn load_store_for_update() -> Result<SessionMetaStore, String> {
+    let path = get_session_meta_path()?;
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(SessionMetaStore::new()),
+        Err(e) => return Err(format!("Failed to read session metadata: {}", e)),
+    };
+    serde_json::from_str(&content).map_err(|e| {
+        format!(
+            "Session metadata in {} is malformed ({}); repair it before making changes",
+            path.display(),
+            e
+        )
+    })
+}
+
+/// Write the store through a temporary file so a crash never leaves it half-written.
+pub fn save_store(store: &SessionMetaStore) -> Result<(), String> {
+    let path = get_session_meta_path()?;
+    let content = serde_json::to_string_pretty(store)
+        .map_err(|e| format!("Failed to serialize session metadata: {}", e))?;
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create metadata directory: {}", e))?;
+    }
+
+    let tmp_path = path.with_extension("json.tmp");
+    fs::write(&tmp_path, content)
+        .map_err(|e| format!("Failed to write session metadata: {}", e))?;
+    fs::rename(&tmp_path, &path).map_err(|e| format!("Failed to replace session metadata: {}", e))
+}
+
+/// Overlay stored titles and favorites onto freshly parsed conversations.
+pub fn apply_to_conversations(conversations: &mut [Conversation]) {
+    let store = load_store();
+    if store.is_empty() {
+        return;
+    }
+    for conversation in conversations.iter_mut() {
+        if let Some(entry) = store.get(session_key(&conversation.id)) {
+            if let Some(title) = &entry.title {
+                conversation.title = title.clone();
+            }
+            conversation.is_favorite = entry.is_favorite;
+            conversation.tags = entry.tags.clone();
+        }
+    }
+}
+
+/// Trim tags, drop empty ones and remove case-insensitive duplicates, keeping
+/// the first spelling.
+fn normalize_tags(tags: Vec<String>) -> Vec<String> {
+    let mut seen = HashSet::new();
+    tags.into_iter()
+        .map(|tag| tag.trim().to_string())
+        .filter(|tag| !tag.is_empty() && seen.insert(tag.to_lowercase()))
+        .collect()
+}
+
+/// Replace the tags on a session and return them as stored. An empty list clears them.
+pub fn set_session_tags(session_id: &str, tags: Vec<String>) -> Result<Vec<String>, String> {
+    let tags = normalize_tags(tags);
+    let key = normalize_session_id(session_id)?;
+
+    let mut store = load_store_for_update()?;
+    let entry = store.entry(key.clone()).or_default();
+    entry.tags = tags.clone();
+    if entry.is_empty() {
+        store.remove(&key);
+    }
+    save_store(&store)?;
+    Ok(tags)
+}
+
+/// Give a session a custom title, keyed by its UUID so equal titles never collide.
+/// A blank title goes back to the one derived from the first user message.
+pub fn set_session_title(session_id: &str, title: &str) -> Result<Option<String>, String> {
+    let key = normalize_session_id(session_id)?;
+    let title = title.trim();
+    let title = (!title.is_empty()).then(|| title.to_string());
+
+    let mut store = load_store_for_update()?;
+    let entry = store.entry(key.clone()).or_default();
+    entry.title = title.clone();
+    if entry.is_empty() {
+        store.remove(&key);
+    }
+    save_store(&store)?;
+    Ok(title)
+}
+
+fn matches_any(tags: &[String], wanted: &[String]) -> bool {
+    tags.iter()
+        .any(|tag| wanted.iter().any(|w| w.trim().eq_ignore_ascii_case(tag)))
+}
+
+/// Whether `conversation` carries any of `wanted`, compared case-insensitively.
+pub fn has_any_tag(conversation: &Conversation, wanted: &[String]) -> bool {
+    matches_any(&conversation.tags, wanted)
+}
+
+/// UUIDs of the sessions carrying any of `wanted`, read from the sidecar alone.
+pub fn tagged_session_ids(wanted: &[String]) -> HashSet<String> {
+    load_store()
+        .into_iter()
+        .filter(|(_, entry)| matches_any(&entry.tags, wanted))
+        .map(|(id, _)| id)
+        .collect()
+}
+
+/// Split the body of a (possibly truncated) top-level JSON object into its
+/// `"key": value` members, respecting strings and nesting.
+fn top_level_members(text: &str) -> Vec<&str> {
+    let mut members = Vec::new();
+    let (mut depth, mut in_string, mut escaped) = (0usize, false, false);
+    let mut start = None;
+
+    for (i, c) in text.char_indices() {
+        if in_string {
+            if escaped {
+                escaped = false;
+            } else if c == '\\' {
+                escaped = true;
+            } else if c == '"' {
+                in_string = false;
+            }
+            continue;
+        }
+        match c {
+            '"' => in_string = true,
+            '{' | '[' => {
+                depth += 1;
+                if depth == 1 {
+                    start = Some(i + 1);
+                }
+            }
+            '}' | ']' => {
+                if depth == 1 {
+                    if let Some(s) = start.take() {
+                        members.push(&text[s..i]);
+                    }
+                }
+                depth = depth.saturating_sub(1);
+            }
+            ',' if depth == 1 => {
+                if let Some(s) = start {
+                    members.push(&text[s..i]);
+                }
+                start = Some(i + 1);
+            }
+            _ => {}
+        }
+    }
+    // Truncated file: the last member never got its closing brace
+    if let Some(s) = start {
+        members.push(&text[s..]);
+    }
+    members
+}
+
+/// Parse `content` entry by entry, returning the valid entries, how many were
+/// dropped, and whether the file as a whole was malformed.
+fn parse_tolerant(content: &str) -> (SessionMetaStore, usize, bool) {
+    let mut store = SessionMetaStore::new();
+    let mut dropped = 0;
+
+    let (raw_entries, salvaged) =
+        match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(content) {
+            Ok(map) => (map.into_iter().collect::<Vec<_>>(), false),
+            Err(_) => {
+                let mut entries = Vec::new();
+                for member in top_level_members(content) {
+                    let member = member.trim();
+                    if member.is_empty() {
+                        continue;
+                    }
+                    match serde_json::from_str::<serde_json::Map<String, serde_json::Value>>(
+                        &format!("{{{}}}", member),
+                    ) {
+                        Ok(map) => entries.extend(map),
+                        Err(_) => dropped += 1,
+                    }
+                }
+                (entries, true)
+            }
+        };
+
+    for (key, value) in raw_entries {
+        match serde_json::from_value::<SessionMetaEntry>(value) {
+            Ok(entry) => {
+                store.insert(session_key(&key).to_string(), entry);
+            }
+            Err(_) => dropped += 1,
+        }
+    }
+    (store, dropped, salvaged)
+}
+
+/// UUIDs of every rollout under `sessions_path`, compressed or not, taken from
+/// the `rollout-<timestamp>-<uuid>.jsonl[.gz]` file names.
+fn existing_session_ids(sessions_path: &Path) -> Option<HashSet<String>> {
+    if !sessions_path.exists() {
+        return None;
+    }
+    let ids = scan_rollout_files(sessions_path)
+        .filter_map(|entry| normalize_session_id(&entry.file_name().to_string_lossy()).ok())
+        .collect();
+    Some(ids)
+}
+
+/// Drop entries for sessions no longer in `sessions_path`, salvage what parses
+/// from a corrupted file, and rewrite the store.
+pub fn repair_session_metadata(sessions_path: &Path) -> Result<MetadataRepairReport, String> {
+    let path = get_session_meta_path()?;
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Ok(MetadataRepairReport {
+                removed_missing: Vec::new(),
+                dropped_malformed: 0,
+                salvaged: false,
+                kept: 0,
+            });
+        }
+        Err(e) => return Err(format!("Failed to read session metadata: {}", e)),
+    };
+
+    let (mut store, dropped_malformed, salvaged) = parse_tolerant(&content);
+
+    // Without a sessions directory there's nothing to compare against, so keep everything
+    let mut removed_missing = Vec::new();
+    if let Some(existing) = existing_session_ids(sessions_path) {
+        store.retain(|id, _| {
+            let keep = existing.contains(id);
+            if !keep {
+                removed_missing.push(id.clone());
+            }
+            keep
+        });
+    }
+
+    if salvaged || dropped_malformed > 0 || !removed_missing.is_empty() {
+        save_store(&store)?;
+    }
+
+    Ok(MetadataRepairReport {
+        removed_missing,
+        dropped_malformed,
+        salvaged,
+        kept: store.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn members(text: &str) -> Vec<&str> {
+        top_level_members(text).into_iter().map(str::trim).collect()
+    }
+
+    #[test]
+    fn nested_objects_and_arrays_stay_in_one_member() {
+        assert_eq!(
+            members(r#"{"a": {"x": [1, {"y": 2}]}, "b": {"tags": ["p", "q"]}}"#),
+            vec![
+                r#""a": {"x": [1, {"y": 2}]}"#,
+                r#""b": {"tags": ["p", "q"]}"#
+            ]
+        );
+    }
+
+    #[test]
+    fn escaped_quotes_and_delimiters_inside_strings_are_ignored() {
+        assert_eq!(
+            members(r#"{"a\"b": {"title": "x, \"y}] {"}, "c": {}}"#),
+            vec![r#""a\"b": {"title": "x, \"y}] {"}"#, r#""c": {}"#]
+        );
+    }
+
+    #[test]
+    fn truncated_input_keeps_the_unfinished_member() {
+        assert_eq!(
+            members(r#"{"a": {"tags": ["x"]}, "b": {"title": "unfin"#),
+            vec![r#""a": {"tags": ["x"]}"#, r#""b": {"title": "unfin"#]
+        );
+        assert!(members("").is_empty());
+    }
+
+    #[test]
+    fn truncated_file_salvages_the_complete_entries() {
+        let (store, dropped, salvaged) = parse_tolerant(
+            r#"{"a": {"tags": ["x"]}, "codex-event-b": {"isFavorite": true}, "c": {"ti"#,
+        );
+        assert!(salvaged);
+        assert_eq!(dropped, 1);
+        assert_eq!(store.keys().collect::<Vec<_>>(), vec!["a", "b"]);
+        assert_eq!(store["a"].tags, vec!["x"]);
+        assert!(store["b"].is_favorite);
+    }
+}
@@ -0,0 +1,129 @@
+use crate::filesystem::file_io::ensure_within_roots;
+use crate::protocol::SandboxMode;
+use crate::services::codex::CodexError;
+use crate::state::CodexState;
+use serde::Serialize;
+use std::path::{Path, PathBuf};
+use std::process::Stdio;
+use tauri::{AppHandle, Emitter, State};
+use tokio::io::{AsyncBufReadExt, AsyncRead, BufReader};
+use tokio::process::Command;
+
+const DEFAULT_COMMAND_TIMEOUT_SECS: u64 = 300;
+
+#[derive(Serialize, Debug, Clone)]
+pub struct CommandOutputPayload {
+    pub session_id: String,
+    pub stream: String,
+    pub line: String,
+}
+
+/// Resolve `program` to the file that will actually run: bare names through PATH,
+/// anything that looks like a path relative to the workspace.
+fn resolve_program(program: &str, workspace: &Path) -> Result<PathBuf, String> {
+    let found = if !program.contains('/') && !program.contains('\\') {
+        which::which(program).map_err(|e| format!("Cannot find program '{}': {}", program, e))?
+    } else {
+        workspace.join(program)
+    };
+    std::fs::canonicalize(&found)
+        .map_err(|e| format!("Cannot resolve program '{}': {}", program, e))
+}
+
+fn forward_lines<R>(app: AppHandle, reader: R, session_id: String, stream: &'static str)
+where
+    R: AsyncRead + Unpin + Send + 'static,
+{
+    tokio::spawn(async move {
+        let mut lines = BufReader::new(reader).lines();
+        while let Ok(Some(line)) = lines.next_line().await {
+            let payload = CommandOutputPayload {
+                session_id: session_id.clone(),
+                stream: stream.to_string(),
+                line,
+            };
+            if let Err(e) = app.emit("command-output", &payload) {
+                log::error!("Failed to emit command output: {}", e);
+            }
+        }
+    });
+}
+
+/// Run a user-initiated command in the session's working directory, streaming
+/// stdout/stderr as `command-output` events. Returns the process exit code.
+///
+/// The command runs as a plain child process with the app's own permissions;
+/// nothing confines it to the workspace. It is therefore refused unless the
+/// session runs with `danger-full-access`, where codex itself is unconfined.
+pub async fn run_command(
+    app: AppHandle,
+    state: State<'_, CodexState>,
+    session_id: String,
+    program: String,
+    args: Vec<String>,
+    timeout_secs: Option<u64>,
+) -> Result<i32, CodexError> {
+    let config = {
+        let sessions = state.sessions.lock().await;
+        match sessions.get(&session_id) {
+            Some(client) => client.config().clone(),
+            None => return Err(CodexError::not_running(&session_id)),
+        }
+    };
+
+    if config.effective_sandbox() != Some(SandboxMode::DangerFullAccess) {
+        return Err(
+            "Running commands directly is only allowed when the session's sandbox mode is danger-full-access"
+                .into(),
+        );
+    }
+
+    let cwd = config.effective_cwd();
+    if cwd.is_empty() {
+        return Err("Session has no working directory".into());
+    }
+    let workspace =
+        std::fs::canonicalize(cwd).map_err(|e| format!("Invalid working directory: {}", e))?;
+    ensure_within_roots(&workspace, &state.allowed_roots()).map_err(|e| e.to_string())?;
+
+    let resolved = resolve_program(&program, &workspace)?;
+
+    log::info!(
+        "Running command for session {}: {} {:?}",
+        session_id,
+        program,
+        args
+    );
+
+    let mut child = Command::new(&resolved)
+        .args(&args)
+        .current_dir(&workspace)
+        .stdin(Stdio::null())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true)
+        .spawn()
+        .map_err(|e| format!("Failed to start '{}': {}", program, e))?;
+
+    if let Some(stdout) = child.stdout.take() {
+        forward_lines(app.clone(), stdout, session_id.clone(), "stdout");
+    }
+    if let Some(stderr) = child.stderr.take() {
+        forward_lines(app.clone(), stderr, session_id.clone(), "stderr");
+    }
+
+    let timeout =
+        tokio::time::Duration::from_secs(timeout_secs.unwrap_or(DEFAULT_COMMAND_TIMEOUT_SECS));
+    match tokio::time::timeout(timeout, child.wait()).await {
+        Ok(Ok(status)) => status
+            .code()
+            .ok_or_else(|| "Command was terminated by a signal".into()),
+        Ok(Err(e)) => Err(format!("Failed to wait for command: {}", e).into()),
+        Err(_) => {
+            if let Err(e) = child.kill().await {
+                log::error!("Failed to kill timed out command: {}", e);
+            }
+            Err(format!("Command timed out after {}s", timeout.as_secs()).into())
+        }
+    }
+}
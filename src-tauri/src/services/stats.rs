@@ -0,0 +1,229 @@
+use serde::Serialize;
+use std::collections::BTreeMap;
+use std::io::BufRead;
+use std::path::{Path, PathBuf};
+
+use crate::services::session::{message_identity, parse_tool_call, rollout_item};
+use crate::settings::load_settings;
+use crate::utils::file::{open_rollout, scan_rollout_files};
+use crate::utils::parallel::parallel_map;
+use crate::utils::time::parse_timestamp;
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct SessionStats {
+    #[serde(rename = "filePath")]
+    pub file_path: String,
+    /// Chat messages keyed by role; instruction and environment blocks aren't counted.
+    #[serde(rename = "messagesByRole")]
+    pub messages_by_role: BTreeMap<String, usize>,
+    #[serde(rename = "commandsExecuted")]
+    pub commands_executed: usize,
+    #[serde(rename = "patchesApplied")]
+    pub patches_applied: usize,
+    /// `None` when the rollout recorded no token counts.
+    #[serde(rename = "totalTokens")]
+    pub total_tokens: Option<u64>,
+    /// Unix millis of the first and last timestamped lines.
+    #[serde(rename = "startedAt")]
+    pub started_at: Option<i64>,
+    #[serde(rename = "endedAt")]
+    pub ended_at: Option<i64>,
+    #[serde(rename = "durationMs")]
+    pub duration_ms: Option<i64>,
+}
+
+/// Totals for the sessions started on one UTC day.
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct DailyStats {
+    /// `YYYY-MM-DD`.
+    pub date: String,
+    pub sessions: usize,
+    pub messages: usize,
+    #[serde(rename = "commandsExecuted")]
+    pub commands_executed: usize,
+    #[serde(rename = "patchesApplied")]
+    pub patches_applied: usize,
+    #[serde(rename = "totalTokens")]
+    pub total_tokens: u64,
+}
+
+#[derive(Serialize, Debug, Clone, Default)]
+pub struct GlobalStats {
+    pub sessions: usize,
+    #[serde(rename = "messagesByRole")]
+    pub messages_by_role: BTreeMap<String, usize>,
+    #[serde(rename = "commandsExecuted")]
+    pub commands_executed: usize,
+    #[serde(rename = "patchesApplied")]
+    pub patches_applied: usize,
+    #[serde(rename = "totalTokens")]
+    pub total_tokens: u64,
+    #[serde(rename = "totalDurationMs")]
+    pub total_duration_ms: i64,
+    /// Oldest day first. Sessions without timestamps are left out.
+    #[serde(rename = "byDay")]
+    pub by_day: Vec<DailyStats>,
+}
+
+/// Running token total: cumulative counts from `info.total_token_usage` when
+/// codex reports them, otherwise the sum of per-turn counts.
+#[derive(Default)]
+struct TokenTally {
+    cumulative: Option<u64>,
+    summed: Option<u64>,
+}
+
+impl TokenTally {
+    fn record(&mut self, payload: &serde_json::Value) {
+        let count = |usage: &serde_json::Value| {
+            usage
+                .get("total_tokens")
+                .and_then(|t| t.as_u64())
+                .or_else(|| {
+                    let input = usage.get("input_tokens").and_then(|t| t.as_u64())?;
+                    let output = usage.get("output_tokens").and_then(|t| t.as_u64())?;
+                    Some(input + output)
+                })
+        };
+        if let Some(total) = payload
+            .get("info")
+            .and_then(|info| info.get("total_token_usage"))
+            .and_then(count)
+        {
+            self.cumulative = Some(self.cumulative.unwrap_or(0).max(total));
+        } else if let Some(turn) = count(payload) {
+            self.summed = Some(self.summed.unwrap_or(0) + turn);
+        }
+    }
+
+    fn total(&self) -> Option<u64> {
+        self.cumulative.or(self.summed)
+    }
+}
+
+/// Whether a shell call runs `apply_patch`, which codex uses to edit files.
+fn is_apply_patch(command: &[String]) -> bool {
+    command
+        .first()
+        .is_some_and(|program| program == "apply_patch")
+}
+
+/// Statistics for one rollout, read a line at a time so large files aren't held in memory.
+pub fn compute_session_stats(path: &Path) -> Result<SessionStats, String> {
+    let reader = open_rollout(path).map_err(|e| format!("Failed to open session file: {}", e))?;
+    let mut stats = SessionStats {
+        file_path: path.to_string_lossy().to_string(),
+        ..Default::default()
+    };
+    let mut tokens = TokenTally::default();
+
+    for line in reader.lines().map_while(Result::ok) {
+        if line.trim().is_empty() {
+            continue;
+        }
+        let Ok(value) = serde_json::from_str::<serde_json::Value>(&line) else {
+            continue;
+        };
+
+        if let Some(at) = value
+            .get("timestamp")
+            .and_then(|t| t.as_str())
+            .and_then(|t| parse_timestamp(t).ok())
+        {
+            stats.started_at = Some(stats.started_at.map_or(at, |first| first.min(at)));
+            stats.ended_at = Some(stats.ended_at.map_or(at, |last| last.max(at)));
+        }
+
+        if let Some((role, text)) = message_identity(&line) {
+            if !text.is_empty() {
+                *stats.messages_by_role.entry(role).or_default() += 1;
+            }
+            continue;
+        }
+
+        if value.get("type").and_then(|t| t.as_str()) == Some("event_msg") {
+            if let Some(payload) = value
+                .get("payload")
+                .filter(|p| p.get("type").and_then(|t| t.as_str()) == Some("token_count"))
+            {
+                tokens.record(payload);
+            }
+            continue;
+        }
+
+        if let Some(call) = parse_tool_call(rollout_item(&value)) {
+            match call.command.as_deref() {
+                _ if call.name == "apply_patch" => stats.patches_applied += 1,
+                Some(command) if is_apply_patch(command) => stats.patches_applied += 1,
+                Some(_) => stats.commands_executed += 1,
+                None => {}
+            }
+        }
+    }
+
+    stats.total_tokens = tokens.total();
+    stats.duration_ms = stats
+        .started_at
+        .zip(stats.ended_at)
+        .map(|(first, last)| last - first);
+    Ok(stats)
+}
+
+/// Day a session started on, as `YYYY-MM-DD` in UTC.
+fn start_day(stats: &SessionStats) -> Option<String> {
+    let started_at = chrono::DateTime::from_timestamp_millis(stats.started_at?)?;
+    Some(started_at.format("%Y-%m-%d").to_string())
+}
+
+/// Sum `sessions` into totals and per-day buckets.
+fn aggregate(sessions: &[SessionStats]) -> GlobalStats {
+    let mut global = GlobalStats {
+        sessions: sessions.len(),
+        ..Default::default()
+    };
+    let mut days: BTreeMap<String, DailyStats> = BTreeMap::new();
+
+    for stats in sessions {
+        for (role, count) in &stats.messages_by_role {
+            *global.messages_by_role.entry(role.clone()).or_default() += count;
+        }
+        global.commands_executed += stats.commands_executed;
+        global.patches_applied += stats.patches_applied;
+        global.total_tokens += stats.total_tokens.unwrap_or(0);
+        global.total_duration_ms += stats.duration_ms.unwrap_or(0);
+
+        if let Some(date) = start_day(stats) {
+            let day = days.entry(date.clone()).or_insert_with(|| DailyStats {
+                date,
+                ..Default::default()
+            });
+            day.sessions += 1;
+            day.messages += stats.messages_by_role.values().sum::<usize>();
+            day.commands_executed += stats.commands_executed;
+            day.patches_applied += stats.patches_applied;
+            day.total_tokens += stats.total_tokens.unwrap_or(0);
+        }
+    }
+
+    global.by_day = days.into_values().collect();
+    global
+}
+
+/// Statistics over every rollout in the sessions directory. Files that can't
+/// be read are skipped.
+pub fn compute_global_stats(sessions_dir: &Path) -> Result<GlobalStats, String> {
+    if !sessions_dir.exists() {
+        return Ok(GlobalStats::default());
+    }
+
+    let paths: Vec<PathBuf> = scan_rollout_files(sessions_dir)
+        .map(|entry| entry.into_path())
+        .collect();
+    let workers = load_settings().scan_worker_count();
+    let sessions: Vec<SessionStats> =
+        parallel_map(&paths, workers, |path| compute_session_stats(path).ok())
+            .into_iter()
+            .flatten()
+            .collect();
+    Ok(aggregate(&sessions))
+}
@@ -0,0 +1,197 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::PathBuf;
+use tauri::command;
+
+use crate::services::preflight::validate_approval_policy;
+use crate::services::session::SessionSort;
+use crate::utils::file::default_codex_home;
+use crate::utils::parallel::default_worker_count;
+use crate::utils::pricing::ModelRate;
+use crate::utils::watcher::WatcherBackend;
+
+pub const DEFAULT_MAX_WRITE_BYTES: u64 = 10 * 1024 * 1024;
+pub const DEFAULT_OUTPUT_BUFFER_LINES: usize = 500;
+pub const DEFAULT_HEARTBEAT_INTERVAL_SECS: u64 = 5;
+pub const DEFAULT_POLL_INTERVAL_MS: u64 = 2000;
+/// Upper bound for `max_write_bytes`, regardless of what the settings file says.
+pub const MAX_WRITE_BYTES_HARD_LIMIT: u64 = 512 * 1024 * 1024;
+
+/// Codexia's own preferences. Kept apart from codex's `config.toml` so we never
+/// write keys the codex CLI doesn't understand.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AppSettings {
+    /// Executable name used by discovery, for rebranded or forked codex builds.
+    pub codex_binary_name: String,
+    /// Largest content `write_file` accepts, in bytes.
+    pub max_write_bytes: u64,
+    /// Ask the OS for window attention when codex requests an approval.
+    pub focus_on_approval: bool,
+    /// Threads used to scan the sessions directory and search files.
+    /// `None` picks a default from the available parallelism; lower it on network shares.
+    pub scan_workers: Option<usize>,
+    /// Default order for session lists when the caller doesn't pass one.
+    pub session_sort: SessionSort,
+    /// Lines of stdout and stderr kept per running session for diagnostics.
+    pub output_buffer_lines: usize,
+    /// Watch each session's working directory and report files changed outside approved patches.
+    pub track_workspace_changes: bool,
+    /// Seconds between `session-heartbeat` events for each running session.
+    pub heartbeat_interval_secs: u64,
+    /// Sessions directory after `relocate_sessions_store`; `None` means `~/.codex/sessions`.
+    pub sessions_dir: Option<String>,
+    /// Approval policy for sessions started without one. `None` leaves it to codex.
+    pub default_approval_policy: Option<String>,
+    /// Native OS notifications, or polling for network mounts where they don't arrive.
+    /// Applies to watches started after the change.
+    pub watcher_backend: WatcherBackend,
+    /// Rescan interval for the polling backend, in milliseconds.
+    pub poll_interval_ms: u64,
+    /// Per-model token prices used for cost estimates, keyed by model name or
+    /// prefix. Overrides the built-in OpenAI list prices.
+    pub model_rates: BTreeMap<String, ModelRate>,
+    /// Gzip a session's rollout to `.jsonl.gz` when it is closed. Resuming it
+    /// decompresses it again, since codex only reads plain JSONL.
+    pub compress_rollouts_on_close: bool,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            codex_binary_name: "codex".to_string(),
+            max_write_bytes: DEFAULT_MAX_WRITE_BYTES,
+            focus_on_approval: true,
+            scan_workers: None,
+            session_sort: SessionSort::default(),
+            output_buffer_lines: DEFAULT_OUTPUT_BUFFER_LINES,
+            track_workspace_changes: false,
+            heartbeat_interval_secs: DEFAULT_HEARTBEAT_INTERVAL_SECS,
+            sessions_dir: None,
+            default_approval_policy: None,
+            watcher_backend: WatcherBackend::default(),
+            poll_interval_ms: DEFAULT_POLL_INTERVAL_MS,
+            model_rates: BTreeMap::new(),
+            compress_rollouts_on_close: false,
+        }
+    }
+}
+
+impl AppSettings {
+    /// Effective write cap, clamped to the hard limit.
+    pub fn write_limit(&self) -> u64 {
+        self.max_write_bytes.min(MAX_WRITE_BYTES_HARD_LIMIT)
+    }
+
+    pub fn scan_worker_count(&self) -> usize {
+        self.scan_workers
+            .unwrap_or_else(default_worker_count)
+            .max(1)
+    }
+}
+
+pub fn get_settings_path() -> Result<PathBuf, String> {
+    Ok(default_codex_home()?.join("codexia_settings.json"))
+}
+
+/// Load settings from disk, falling back to defaults when the file is missing or unreadable.
+pub fn load_settings() -> AppSettings {
+    let Ok(settings_path) = get_settings_path() else {
+        return AppSettings::default();
+    };
+
+    if !settings_path.exists() {
+        return AppSettings::default();
+    }
+
+    match fs::read_to_string(&settings_path) {
+        Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+            log::warn!("Failed to parse settings file, using defaults: {}", e);
+            AppSettings::default()
+        }),
+        Err(e) => {
+            log::warn!("Failed to read settings file, using defaults: {}", e);
+            AppSettings::default()
+        }
+    }
+}
+
+pub fn save_settings(settings: &AppSettings) -> Result<(), String> {
+    let settings_path = get_settings_path()?;
+
+    let content = serde_json::to_string_pretty(settings)
+        .map_err(|e| format!("Failed to serialize settings: {}", e))?;
+
+    if let Some(parent) = settings_path.parent() {
+        fs::create_dir_all(parent)
+            .map_err(|e| format!("Failed to create settings directory: {}", e))?;
+    }
+
+    fs::write(&settings_path, content).map_err(|e| format!("Failed to write settings file: {}", e))
+}
+
+#[command]
+pub async fn read_app_settings() -> Result<AppSettings, String> {
+    Ok(load_settings())
+}
+
+#[command]
+pub async fn update_app_settings(settings: AppSettings) -> Result<(), String> {
+    if settings.codex_binary_name.trim().is_empty() {
+        return Err("Codex binary name cannot be empty".to_string());
+    }
+    if settings.max_write_bytes == 0 || settings.max_write_bytes > MAX_WRITE_BYTES_HARD_LIMIT {
+        return Err(format!(
+            "Write limit must be between 1 and {} bytes",
+            MAX_WRITE_BYTES_HARD_LIMIT
+        ));
+    }
+    if settings.scan_workers == Some(0) {
+        return Err("Scan worker count must be at least 1".to_string());
+    }
+    if settings.output_buffer_lines == 0 {
+        return Err("Output buffer size must be at least 1 line".to_string());
+    }
+    if settings.heartbeat_interval_secs == 0 {
+        return Err("Heartbeat interval must be at least 1 second".to_string());
+    }
+    if settings.poll_interval_ms < 100 {
+        return Err("Poll interval must be at least 100 ms".to_string());
+    }
+    for (model, rate) in &settings.model_rates {
+        let prices = [
+            rate.input_per_million,
+            rate.output_per_million,
+            rate.cached_input_per_million.unwrap_or(0.0),
+        ];
+        if prices.iter().any(|p| !p.is_finite() || *p < 0.0) {
+            return Err(format!(
+                "Rates for '{}' must be non-negative numbers",
+                model
+            ));
+        }
+    }
+    if let Some(policy) = &settings.default_approval_policy {
+        validate_approval_policy(policy)?;
+    }
+    save_settings(&settings)
+}
+
+#[command]
+pub async fn get_default_approval_policy() -> Result<Option<String>, String> {
+    Ok(load_settings().default_approval_policy)
+}
+
+/// Set the approval policy used when a session's config leaves it empty; `None` clears it.
+/// Running sessions keep the policy they were started with.
+#[command]
+pub async fn set_default_approval_policy(policy: Option<String>) -> Result<(), String> {
+    let policy = policy.filter(|p| !p.trim().is_empty());
+    if let Some(policy) = &policy {
+        validate_approval_policy(policy)?;
+    }
+    let mut settings = load_settings();
+    settings.default_approval_policy = policy;
+    save_settings(&settings)
+}
@@ -1,20 +1,238 @@
 use crate::codex_client::CodexClient;
-use notify::RecommendedWatcher;
-use std::collections::HashMap;
-use std::sync::Arc;
-use tokio::sync::Mutex;
+use crate::utils::file::{default_codex_home, sessions_path_in, HomeDirError};
+use crate::utils::watcher::BoxedWatcher;
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::path::PathBuf;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{Mutex, Notify};
+use tokio::task::AbortHandle;
+
+pub const READ_ONLY_MESSAGE: &str = "Codexia is in read-only mode";
+
+/// Per-session counters updated from the event stream.
+#[derive(Debug, Clone)]
+pub struct SessionRuntime {
+    /// Unix millis when the codex process was started.
+    pub started_at: i64,
+    pub turns_completed: u32,
+    /// Set while a turn is generating.
+    pub turn_started_at: Option<i64>,
+    /// Unix millis of the last event received from codex.
+    pub last_activity_at: i64,
+    /// Model the session was started with, used to price its usage.
+    pub model: String,
+    pub usage: TokenUsageTotals,
+    /// Set by `pause_session` until the next turn starts or message is sent.
+    /// Queued messages stay queued meanwhile.
+    pub paused: bool,
+    /// Messages sent while a turn was running, delivered one per turn that
+    /// completes or is aborted.
+    pub queued_messages: VecDeque<String>,
+    /// A message went out and codex hasn't started its turn yet.
+    pub awaiting_turn: bool,
+}
+
+/// Token counts summed over every `TokenCount` event of a session.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct TokenUsageTotals {
+    pub input_tokens: u64,
+    pub cached_input_tokens: u64,
+    pub output_tokens: u64,
+    pub reasoning_output_tokens: u64,
+}
+
+impl SessionRuntime {
+    pub fn new(now: i64, model: String) -> Self {
+        Self {
+            started_at: now,
+            turns_completed: 0,
+            turn_started_at: None,
+            last_activity_at: now,
+            model,
+            usage: TokenUsageTotals::default(),
+            paused: false,
+            queued_messages: VecDeque::new(),
+            awaiting_turn: false,
+        }
+    }
+
+    /// Whether a new message has to wait for the current turn.
+    pub fn is_busy(&self) -> bool {
+        self.turn_started_at.is_some() || self.awaiting_turn
+    }
+
+    pub fn status(&self) -> &'static str {
+        if self.turn_started_at.is_some() {
+            "generating"
+        } else {
+            "idle"
+        }
+    }
+}
+
+/// Liveness of a session as reported by `get_session_status`.
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SessionStatus {
+    Running,
+    Paused,
+    /// The codex process exited without the session being closed.
+    Crashed,
+    Closed,
+}
+
+/// An approval codex asked for that the user hasn't answered yet, keyed by approval id.
+#[derive(Debug)]
+pub struct PendingApproval {
+    pub session_id: String,
+    pub kind: String,
+    pub summary: String,
+    /// Unix millis when codex asked.
+    pub requested_at: i64,
+    /// Task answering the request once `approval_timeout_secs` passes.
+    pub timeout: Option<AbortHandle>,
+    /// What a patch approval would change, for `get_pending_patch`.
+    pub patch: Option<PendingPatch>,
+}
+
+/// Changes a patch approval asks for, as codex sent them.
+#[derive(Debug, Clone)]
+pub enum PendingPatch {
+    /// Per-file changes from `apply_patch_approval_request`, keyed by path.
+    Changes(serde_json::Value),
+    /// Patch text and files from the older `patch_approval_request`.
+    Text { patch: String, files: Vec<String> },
+}
+
+// Removing the entry means it was answered (or the session went away), so the timeout is moot
+impl Drop for PendingApproval {
+    fn drop(&mut self) {
+        if let Some(timeout) = self.timeout.take() {
+            timeout.abort();
+        }
+    }
+}
+
+/// Watcher on the codex sessions directory. While paused the watch stays
+/// registered but events are swallowed, and `missed_events` records that a
+/// refresh is owed on resume.
+#[derive(Default)]
+pub struct SessionsWatch {
+    pub watcher: Option<BoxedWatcher>,
+    pub paused: Arc<AtomicBool>,
+    pub missed_events: Arc<AtomicBool>,
+}
+
+/// Tail of a rollout file opened with `watch_session_file`. Dropping it stops the watch.
+pub struct SessionFileWatch {
+    pub _watcher: BoxedWatcher,
+    /// UUID of the rollout, so the watch can be dropped when that session closes.
+    pub session_id: Option<String>,
+}
 
 pub struct CodexState {
     pub sessions: Arc<Mutex<HashMap<String, CodexClient>>>,
+    pub runtimes: Arc<Mutex<HashMap<String, SessionRuntime>>>,
+    pub pending_approvals: Arc<Mutex<HashMap<String, PendingApproval>>>,
     // Active filesystem watchers keyed by absolute folder path with ref-count
-    pub watchers: Arc<Mutex<HashMap<String, (RecommendedWatcher, usize)>>>,
+    pub watchers: Arc<Mutex<HashMap<String, (BoxedWatcher, usize)>>>,
+    pub sessions_watch: Arc<Mutex<SessionsWatch>>,
+    // Live tails of rollout files keyed by canonical path
+    pub session_file_watchers: Arc<Mutex<HashMap<String, SessionFileWatch>>>,
+    /// How sessions that are no longer running ended: `Crashed` or `Closed`.
+    pub ended_sessions: Arc<Mutex<HashMap<String, SessionStatus>>>,
+    /// Browse-only mode: nothing may spawn codex, answer approvals or write files.
+    pub read_only: Arc<AtomicBool>,
+    /// Canonical directories the file commands are confined to; empty allows any path.
+    pub allowed_roots: Arc<RwLock<Vec<PathBuf>>>,
+    /// Most codex processes allowed at once; 0 means no limit.
+    pub max_concurrent_sessions: Arc<AtomicUsize>,
+    /// Starts over the limit wait for a free slot instead of failing.
+    pub queue_over_limit: Arc<AtomicBool>,
+    // Held by a start from its limit check until the client is stored
+    pub session_start: Arc<Mutex<()>>,
+    /// Woken whenever a session stops running.
+    pub session_slot_freed: Arc<Notify>,
+    /// Codex home chosen with `set_codex_home`, until restart.
+    pub codex_home_override: Arc<RwLock<Option<PathBuf>>>,
 }
 
 impl CodexState {
     pub fn new() -> Self {
         Self {
             sessions: Arc::new(Mutex::new(HashMap::new())),
+            runtimes: Arc::new(Mutex::new(HashMap::new())),
+            pending_approvals: Arc::new(Mutex::new(HashMap::new())),
             watchers: Arc::new(Mutex::new(HashMap::new())),
+            sessions_watch: Arc::new(Mutex::new(SessionsWatch::default())),
+            session_file_watchers: Arc::new(Mutex::new(HashMap::new())),
+            ended_sessions: Arc::new(Mutex::new(HashMap::new())),
+            read_only: Arc::new(AtomicBool::new(false)),
+            allowed_roots: Arc::new(RwLock::new(Vec::new())),
+            max_concurrent_sessions: Arc::new(AtomicUsize::new(0)),
+            queue_over_limit: Arc::new(AtomicBool::new(false)),
+            session_start: Arc::new(Mutex::new(())),
+            session_slot_freed: Arc::new(Notify::new()),
+            codex_home_override: Arc::new(RwLock::new(None)),
+        }
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only.load(Ordering::SeqCst)
+    }
+
+    pub fn session_limit(&self) -> Option<usize> {
+        match self.max_concurrent_sessions.load(Ordering::SeqCst) {
+            0 => None,
+            limit => Some(limit),
+        }
+    }
+
+    /// Wake starts queued on the session limit after a session stopped running.
+    pub fn release_session_slot(&self) {
+        self.session_slot_freed.notify_waiters();
+    }
+
+    /// The codex data directory: the `set_codex_home` override, else `CODEX_HOME`
+    /// when set, otherwise `~/.codex`.
+    pub fn codex_home(&self) -> Result<PathBuf, HomeDirError> {
+        match self.codex_home_override() {
+            Some(codex_home) => Ok(codex_home),
+            None => default_codex_home(),
+        }
+    }
+
+    pub fn codex_home_override(&self) -> Option<PathBuf> {
+        self.codex_home_override.read().ok()?.clone()
+    }
+
+    /// Use `path` as the codex home, or go back to the default with `None`.
+    pub fn set_codex_home_override(&self, path: Option<PathBuf>) {
+        if let Ok(mut current) = self.codex_home_override.write() {
+            *current = path;
+        }
+    }
+
+    /// Sessions directory under the current codex home.
+    pub fn sessions_path(&self) -> Result<PathBuf, String> {
+        Ok(sessions_path_in(&self.codex_home()?))
+    }
+
+    pub fn allowed_roots(&self) -> Vec<PathBuf> {
+        self.allowed_roots
+            .read()
+            .map(|roots| roots.clone())
+            .unwrap_or_default()
+    }
+
+    /// Err with `READ_ONLY_MESSAGE` while read-only mode is on.
+    pub fn ensure_writable(&self) -> Result<(), String> {
+        if self.is_read_only() {
+            Err(READ_ONLY_MESSAGE.to_string())
+        } else {
+            Ok(())
         }
     }
 }
@@ -1,142 +1,835 @@
-use std::path::PathBuf;
+use serde::Serialize;
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
 
-fn get_platform_binary_name() -> &'static str {
+use crate::settings::load_settings;
+use crate::utils::file::user_home;
+
+fn get_platform_binary_name(name: &str) -> String {
     let os = std::env::consts::OS;
     let arch = std::env::consts::ARCH;
     match (os, arch) {
-        ("macos", "aarch64") => "codex-aarch64-apple-darwin",
-        ("macos", "x86_64") => "codex-x86_64-apple-darwin",
-        ("linux", "x86_64") => "codex-x86_64-unknown-linux-musl",
-        ("linux", "aarch64") => "codex-aarch64-unknown-linux-musl",
-        ("windows", "x86_64") => "codex-x86_64-pc-windows-msvc.exe",
-        _ => "codex", // fallback
+        ("macos", "aarch64") => format!("{}-aarch64-apple-darwin", name),
+        ("macos", "x86_64") => format!("{}-x86_64-apple-darwin", name),
+        ("linux", "x86_64") => format!("{}-x86_64-unknown-linux-musl", name),
+        ("linux", "aarch64") => format!("{}-aarch64-unknown-linux-musl", name),
+        ("windows", "x86_64") => format!("{}-x86_64-pc-windows-msvc.exe", name),
+        _ => name.to_string(), // fallback
     }
 }
 
+/// Last discovery result, keyed by the binary name it was resolved for.
+type PathCache = Mutex<Option<(String, Option<PathBuf>)>>;
+
+static CODEX_PATH_CACHE: PathCache = Mutex::new(None);
+
+/// Resolve the codex binary, scanning the filesystem only on the first call per
+/// binary name. A cached path that has since been removed triggers a fresh scan.
 pub fn discover_codex_command() -> Option<PathBuf> {
-    let home = if cfg!(windows) {
-        std::env::var("USERPROFILE")
-            .or_else(|_| std::env::var("HOME"))
+    let name = load_settings().codex_binary_name;
+    cached_discovery(&CODEX_PATH_CACHE, &name, &RealFs, || {
+        discover_codex_command_named(&name)
+    })
+}
+
+fn cached_discovery(
+    cache: &PathCache,
+    name: &str,
+    fs: &dyn DiscoveryFs,
+    discover: impl FnOnce() -> Option<PathBuf>,
+) -> Option<PathBuf> {
+    let mut cache = cache.lock().unwrap_or_else(|e| e.into_inner());
+    if let Some((cached_name, cached)) = cache.as_ref() {
+        if cached_name == name && cached.as_ref().is_none_or(|path| fs.exists(path)) {
+            return cached.clone();
+        }
+    }
+
+    let discovered = discover();
+    *cache = Some((name.to_string(), discovered.clone()));
+    discovered
+}
+
+/// Forget the cached codex path, e.g. after codex was installed or moved.
+pub fn invalidate_codex_path_cache() {
+    *CODEX_PATH_CACHE.lock().unwrap_or_else(|e| e.into_inner()) = None;
+}
+
+/// The parts of the environment discovery depends on, read once up front.
+struct DiscoveryEnv {
+    /// `std::env::consts::OS` of the machine being searched.
+    os: &'static str,
+    home: Option<PathBuf>,
+    codex_path: Option<OsString>,
+    path: Option<OsString>,
+    appdata: Option<PathBuf>,
+    local_appdata: Option<PathBuf>,
+    scoop: Option<PathBuf>,
+}
+
+impl DiscoveryEnv {
+    fn current() -> Self {
+        // Without a home directory, skip home-relative locations rather than probing the cwd
+        let home = match user_home() {
+            Ok(home) => Some(home),
+            Err(e) => {
+                log::warn!("{}; skipping home-relative codex locations", e);
+                None
+            }
+        };
+        let dir = |key: &str| {
+            std::env::var_os(key)
+                .filter(|value| !value.is_empty())
+                .map(PathBuf::from)
+        };
+        Self {
+            os: std::env::consts::OS,
+            home,
+            codex_path: std::env::var_os("CODEX_PATH"),
+            path: std::env::var_os("PATH"),
+            appdata: dir("APPDATA"),
+            local_appdata: dir("LOCALAPPDATA"),
+            scoop: dir("SCOOP"),
+        }
+    }
+
+    fn is_windows(&self) -> bool {
+        self.os == "windows"
+    }
+}
+
+/// The filesystem queries discovery makes.
+trait DiscoveryFs {
+    fn exists(&self, path: &Path) -> bool;
+    /// Text content of `path`; `None` for compiled binaries and unreadable files.
+    fn read_to_string(&self, path: &Path) -> Option<String>;
+    /// Entries of `dir`; empty when it can't be read.
+    fn list_dir(&self, dir: &Path) -> Vec<PathBuf>;
+}
+
+struct RealFs;
+
+impl DiscoveryFs for RealFs {
+    fn exists(&self, path: &Path) -> bool {
+        path.exists()
+    }
+
+    fn read_to_string(&self, path: &Path) -> Option<String> {
+        std::fs::read_to_string(path).ok()
+    }
+
+    fn list_dir(&self, dir: &Path) -> Vec<PathBuf> {
+        std::fs::read_dir(dir)
+            .map(|entries| entries.filter_map(|e| e.ok()).map(|e| e.path()).collect())
             .unwrap_or_default()
-    } else {
-        std::env::var("HOME").unwrap_or_default()
-    };
-    let binary_name = get_platform_binary_name();
+    }
+}
+
+/// Discover a codex executable called `name` (`codex` unless overridden in settings).
+pub fn discover_codex_command_named(name: &str) -> Option<PathBuf> {
+    discover_in(&DiscoveryEnv::current(), &RealFs, name)
+}
+
+fn discover_in(env: &DiscoveryEnv, fs: &dyn DiscoveryFs, name: &str) -> Option<PathBuf> {
+    let binary_name = get_platform_binary_name(name);
+    let js_entry = format!("{}.js", name);
 
     // 0) Optional override via environment variable
-    if let Ok(explicit) = std::env::var("CODEX_PATH") {
-        let p = PathBuf::from(&explicit);
-        if p.exists() {
+    if let Some(explicit) = &env.codex_path {
+        let p = PathBuf::from(explicit);
+        if fs.exists(&p) {
             log::debug!("Using CODEX_PATH override at {}", p.display());
             return Some(p);
         } else {
-            log::warn!("CODEX_PATH provided but not found: {}", explicit);
+            log::warn!("CODEX_PATH provided but not found: {}", p.display());
         }
     }
 
     // First priority: Check actual binary locations in node_modules
-    let binary_locations = [
-        // Bun global installation
-        PathBuf::from(&home)
-            .join(".bun/install/global/node_modules/@openai/codex/bin")
-            .join(binary_name),
-        // NPM rootless (user) global installation
-        PathBuf::from(&home)
-            .join(".local/share/npm/lib/node_modules/@openai/codex/bin")
-            .join(binary_name),
-        // NPM global installations
-        PathBuf::from("/usr/local/lib/node_modules/@openai/codex/bin").join(binary_name),
-        PathBuf::from("/opt/homebrew/lib/node_modules/@openai/codex/bin").join(binary_name),
-    ];
-
-    for path_buf in &binary_locations {
-        if path_buf.exists() {
+    for path_buf in package_binary_locations(env, name, &binary_name) {
+        if fs.exists(&path_buf) {
             log::debug!("Found codex binary at {}", path_buf.display());
-            return Some(path_buf.clone());
-        }
-    }
-
-    // Windows npm global installation paths
-    if cfg!(windows) {
-        if let Ok(appdata) = std::env::var("APPDATA") {
-            let npm_paths = [
-                PathBuf::from(&appdata).join("npm").join("codex.cmd"),
-                PathBuf::from(&appdata).join("npm").join("codex.ps1"),
-                PathBuf::from(&appdata).join("npm").join("codex"),
-            ];
-            for path_buf in &npm_paths {
-                if path_buf.exists() {
-                    log::debug!("Found npm codex at {}", path_buf.display());
-                    return Some(path_buf.clone());
-                }
+            return Some(path_buf);
+        }
+    }
+
+    // Windows npm global installation paths. These are usually node shims, which
+    // only win if no native binary turns up below
+    let mut wrapper_candidate: Option<PathBuf> = None;
+    for path_buf in npm_shim_locations(env, name) {
+        if fs.exists(&path_buf) {
+            if wrapper_reason(fs, &path_buf, &js_entry).is_some() {
+                log::debug!("Found npm codex shim at {}", path_buf.display());
+                wrapper_candidate.get_or_insert(path_buf);
+                continue;
             }
+            log::debug!("Found npm codex at {}", path_buf.display());
+            return Some(path_buf);
         }
     }
 
     // Second priority: Check if there are native rust/cargo installations
-    let native_paths = [
-        PathBuf::from(&home).join(".cargo/bin/codex"),
-        PathBuf::from(&home).join(".cargo/bin/codex.exe"),
-        PathBuf::from("/usr/local/bin/codex"),
-        PathBuf::from("/opt/homebrew/bin/codex"),
-    ];
-
-    for path_buf in &native_paths {
-        if path_buf.exists() {
+    for path_buf in native_install_locations(env, fs, name) {
+        if fs.exists(&path_buf) {
             // Check if it's a real binary (not a js wrapper)
-            if let Ok(content) = std::fs::read_to_string(path_buf) {
-                if content.contains("codex.js") || content.starts_with("#!/usr/bin/env node") {
-                    // This is a wrapper script, skip it
-                    log::debug!("Skipping wrapper script at {}", path_buf.display());
-                    continue;
-                }
+            if wrapper_reason(fs, &path_buf, &js_entry).is_some() {
+                // This is a wrapper script, skip it
+                log::debug!("Skipping wrapper script at {}", path_buf.display());
+                continue;
             }
             log::debug!("Found native codex binary at {}", path_buf.display());
-            return Some(path_buf.clone());
+            return Some(path_buf);
         }
     }
 
-    if let Ok(path_env) = std::env::var("PATH") {
-        let separator = if cfg!(windows) { ';' } else { ':' };
-        let mut wrapper_candidate: Option<PathBuf> = None;
-        let candidate_names: &[&str] = if cfg!(windows) {
-            &["codex.exe", "codex.cmd", "codex.ps1", "codex"]
-        } else {
-            &["codex"]
-        };
-        for dir in path_env.split(separator) {
-            if dir.is_empty() {
-                continue;
+    for candidate in path_candidates(env, fs, name) {
+        if wrapper_reason(fs, &candidate, &js_entry).is_some() {
+            if wrapper_candidate.is_none() {
+                log::debug!("Found wrapper script candidate at {} (will use only if no native binary is found)", candidate.display());
+                wrapper_candidate = Some(candidate);
             }
-            for name in candidate_names {
-                let candidate = PathBuf::from(dir).join(name);
-                if candidate.exists() {
-                    if let Ok(content) = std::fs::read_to_string(&candidate) {
-                        let is_wrapper = content.contains("codex.js")
-                            || content.starts_with("#!/usr/bin/env node")
-                            || content.contains("import");
-                        if is_wrapper {
-                            if wrapper_candidate.is_none() {
-                                wrapper_candidate = Some(candidate.clone());
-                                log::debug!("Found wrapper script candidate at {} (will use only if no native binary is found)", candidate.display());
-                            }
-                            continue;
-                        }
-                    }
-                    log::debug!("Found codex in PATH at {}", candidate.display());
-                    return Some(candidate);
-                }
+            continue;
+        }
+        log::debug!("Found codex in PATH at {}", candidate.display());
+        return Some(candidate);
+    }
+    if let Some(wrapper) = wrapper_candidate {
+        log::debug!(
+            "Using wrapper codex from PATH at {} as fallback",
+            wrapper.display()
+        );
+        return Some(wrapper);
+    }
+
+    log::warn!("No {} binary found in common locations or PATH", name);
+    None
+}
+
+/// Whether a discovered codex would run natively or through node.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CodexBinaryKind {
+    Native,
+    Wrapper,
+}
+
+/// A codex executable found on this machine, as reported by `locate_codex`.
+#[derive(Debug, Clone, Serialize)]
+pub struct CodexCandidate {
+    pub path: String,
+    pub kind: CodexBinaryKind,
+    /// Why the candidate was classified as `kind`.
+    pub reason: String,
+    /// Whether discovery would pick this candidate.
+    pub selected: bool,
+}
+
+/// Platform binaries shipped inside the `@openai/<name>` npm package.
+fn package_binary_locations(env: &DiscoveryEnv, name: &str, binary_name: &str) -> Vec<PathBuf> {
+    let package = format!("@openai/{}", name);
+    let mut roots = Vec::new();
+    if let Some(h) = &env.home {
+        // Bun global installation
+        roots.push(h.join(".bun/install/global/node_modules"));
+        // NPM rootless (user) global installation
+        roots.push(h.join(".local/share/npm/lib/node_modules"));
+    }
+    // NPM global installations
+    roots.push(PathBuf::from("/usr/local/lib/node_modules"));
+    roots.push(PathBuf::from("/opt/homebrew/lib/node_modules"));
+
+    roots
+        .into_iter()
+        .map(|root| root.join(&package).join("bin").join(binary_name))
+        .collect()
+}
+
+/// Shims npm creates for global installs on Windows.
+fn npm_shim_locations(env: &DiscoveryEnv, name: &str) -> Vec<PathBuf> {
+    if !env.is_windows() {
+        return Vec::new();
+    }
+    match &env.appdata {
+        Some(appdata) => {
+            let npm_dir = appdata.join("npm");
+            vec![
+                npm_dir.join(format!("{}.cmd", name)),
+                npm_dir.join(format!("{}.ps1", name)),
+                npm_dir.join(name),
+            ]
+        }
+        None => Vec::new(),
+    }
+}
+
+/// Common install locations for native builds: cargo, Windows package managers,
+/// Flatpak and Snap exports, and the user's `~/.local/bin`.
+fn native_install_locations(env: &DiscoveryEnv, fs: &dyn DiscoveryFs, name: &str) -> Vec<PathBuf> {
+    let mut paths = Vec::new();
+    if let Some(h) = &env.home {
+        paths.push(h.join(".cargo/bin").join(name));
+        paths.push(h.join(".cargo/bin").join(format!("{}.exe", name)));
+    }
+    paths.extend(windows_package_locations(env, name));
+    paths.extend(linux_sandbox_locations(env, fs, name));
+    if let Some(h) = &env.home {
+        paths.push(h.join(".local/bin").join(name));
+        paths.push(h.join(".local/bin").join(format!("{}.exe", name)));
+    }
+    paths.push(PathBuf::from("/usr/local/bin").join(name));
+    paths.push(PathBuf::from("/opt/homebrew/bin").join(name));
+    paths
+}
+
+/// Shims created by Scoop (`SCOOP`, default `~/scoop`) and links created by WinGet.
+fn windows_package_locations(env: &DiscoveryEnv, name: &str) -> Vec<PathBuf> {
+    if !env.is_windows() {
+        return Vec::new();
+    }
+    let exe = format!("{}.exe", name);
+    let mut paths = Vec::new();
+    let scoop_root = env
+        .scoop
+        .clone()
+        .or_else(|| env.home.as_ref().map(|h| h.join("scoop")));
+    if let Some(root) = scoop_root {
+        paths.push(root.join("shims").join(&exe));
+    }
+    if let Some(local_appdata) = &env.local_appdata {
+        paths.push(
+            local_appdata
+                .join("Microsoft")
+                .join("WinGet")
+                .join("Links")
+                .join(&exe),
+        );
+    }
+    paths
+}
+
+/// Launchers exported by Flatpak (system-wide and per user) and Snap.
+fn linux_sandbox_locations(env: &DiscoveryEnv, fs: &dyn DiscoveryFs, name: &str) -> Vec<PathBuf> {
+    if env.os != "linux" {
+        return Vec::new();
+    }
+    let mut export_dirs = vec![PathBuf::from("/var/lib/flatpak/exports/bin")];
+    if let Some(h) = &env.home {
+        export_dirs.push(h.join(".local/share/flatpak/exports/bin"));
+    }
+    let mut paths: Vec<PathBuf> = export_dirs
+        .iter()
+        .flat_map(|dir| flatpak_exports(fs, dir, name))
+        .collect();
+    paths.push(PathBuf::from("/snap/bin").join(name));
+    paths
+}
+
+/// Flatpak names exports after the app id (e.g. `com.openai.Codex`), so match
+/// entries of `dir` whose last id segment is `name`, in name order.
+fn flatpak_exports(fs: &dyn DiscoveryFs, dir: &Path, name: &str) -> Vec<PathBuf> {
+    let mut exports: Vec<PathBuf> = fs
+        .list_dir(dir)
+        .into_iter()
+        .filter(|path| {
+            path.file_name()
+                .and_then(|file_name| file_name.to_str())
+                .and_then(|app_id| app_id.rsplit_once('.'))
+                .is_some_and(|(_, last)| last.eq_ignore_ascii_case(name))
+        })
+        .collect();
+    exports.sort();
+    exports
+}
+
+/// Kind of sandbox launcher `path` is, if any. Flatpak exports a shell script
+/// that runs `flatpak run`, Snap a symlink to `/usr/bin/snap`; both start the
+/// real binary inside the sandbox, so neither is a node wrapper.
+fn sandbox_launcher(fs: &dyn DiscoveryFs, path: &Path) -> Option<&'static str> {
+    if path.starts_with("/snap/bin") {
+        return Some("Snap launcher");
+    }
+    let content = fs.read_to_string(path)?;
+    content
+        .contains("flatpak run")
+        .then_some("Flatpak launcher")
+}
+
+/// Existing files named like codex in each PATH directory, in PATH order.
+fn path_candidates(env: &DiscoveryEnv, fs: &dyn DiscoveryFs, name: &str) -> Vec<PathBuf> {
+    let Some(path_env) = env.path.as_ref().and_then(|p| p.to_str()) else {
+        return Vec::new();
+    };
+    let separator = if env.is_windows() { ';' } else { ':' };
+    let candidate_names: Vec<String> = if env.is_windows() {
+        vec![
+            format!("{}.exe", name),
+            format!("{}.cmd", name),
+            format!("{}.ps1", name),
+            name.to_string(),
+        ]
+    } else {
+        vec![name.to_string()]
+    };
+
+    path_env
+        .split(separator)
+        .filter(|dir| !dir.is_empty())
+        .flat_map(|dir| {
+            candidate_names
+                .iter()
+                .map(move |n| PathBuf::from(dir).join(n))
+        })
+        .filter(|candidate| fs.exists(candidate))
+        .collect()
+}
+
+/// Why `path` looks like a node/JS wrapper script, or `None` if it doesn't.
+/// Compiled binaries aren't valid UTF-8, so they never match.
+fn wrapper_reason(fs: &dyn DiscoveryFs, path: &Path, js_entry: &str) -> Option<String> {
+    if sandbox_launcher(fs, path).is_some() {
+        return None;
+    }
+    let content = fs.read_to_string(path)?;
+    if content.starts_with("#!/usr/bin/env node") {
+        Some("Script with a node shebang".to_string())
+    } else if content.contains(js_entry) {
+        Some(format!("Script that launches {}", js_entry))
+    } else if content.contains("import") {
+        Some("Script containing JS imports".to_string())
+    } else {
+        None
+    }
+}
+
+fn classify_candidate(
+    fs: &dyn DiscoveryFs,
+    path: &Path,
+    js_entry: &str,
+) -> (CodexBinaryKind, String) {
+    if let Some(reason) = wrapper_reason(fs, path, js_entry) {
+        return (CodexBinaryKind::Wrapper, reason);
+    }
+    if let Some(launcher) = sandbox_launcher(fs, path) {
+        return (CodexBinaryKind::Native, launcher.to_string());
+    }
+    let reason = if fs.read_to_string(path).is_none() {
+        "Compiled executable"
+    } else {
+        "Text file without node/JS wrapper markers"
+    };
+    (CodexBinaryKind::Native, reason.to_string())
+}
+
+/// Classify `path` as a native binary or node wrapper for the configured binary name.
+pub fn classify_codex_binary(path: &Path) -> (CodexBinaryKind, String) {
+    let js_entry = format!("{}.js", load_settings().codex_binary_name);
+    classify_candidate(&RealFs, path, &js_entry)
+}
+
+/// Every codex candidate discovery considers, classified as native or wrapper,
+/// with the one discovery would use marked as selected.
+pub fn locate_codex_candidates() -> Vec<CodexCandidate> {
+    let name = load_settings().codex_binary_name;
+    let env = DiscoveryEnv::current();
+    let fs = RealFs;
+    let binary_name = get_platform_binary_name(&name);
+    let js_entry = format!("{}.js", name);
+    let selected = discover_in(&env, &fs, &name);
+
+    let mut paths: Vec<PathBuf> = Vec::new();
+    if let Some(explicit) = &env.codex_path {
+        paths.push(PathBuf::from(explicit));
+    }
+    paths.extend(package_binary_locations(&env, &name, &binary_name));
+    paths.extend(npm_shim_locations(&env, &name));
+    paths.extend(native_install_locations(&env, &fs, &name));
+    paths.extend(path_candidates(&env, &fs, &name));
+
+    let mut seen = HashSet::new();
+    paths
+        .into_iter()
+        .filter(|p| fs.exists(p) && seen.insert(p.clone()))
+        .map(|p| {
+            let (kind, reason) = classify_candidate(&fs, &p, &js_entry);
+            CodexCandidate {
+                selected: selected.as_ref() == Some(&p),
+                path: p.to_string_lossy().to_string(),
+                kind,
+                reason,
             }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    /// In-memory tree; a file without text content stands for a compiled binary.
+    #[derive(Default)]
+    struct FakeFs {
+        files: HashMap<PathBuf, Option<String>>,
+    }
+
+    impl FakeFs {
+        fn binary(mut self, path: &str) -> Self {
+            self.files.insert(PathBuf::from(path), None);
+            self
+        }
+
+        fn script(mut self, path: &str, content: &str) -> Self {
+            self.files
+                .insert(PathBuf::from(path), Some(content.to_string()));
+            self
+        }
+    }
+
+    impl DiscoveryFs for FakeFs {
+        fn exists(&self, path: &Path) -> bool {
+            self.files.contains_key(path)
+        }
+
+        fn read_to_string(&self, path: &Path) -> Option<String> {
+            self.files.get(path).cloned().flatten()
+        }
+
+        fn list_dir(&self, dir: &Path) -> Vec<PathBuf> {
+            self.files
+                .keys()
+                .filter(|path| path.parent() == Some(dir))
+                .cloned()
+                .collect()
         }
-        if let Some(wrapper) = wrapper_candidate {
-            log::debug!(
-                "Using wrapper codex from PATH at {} as fallback",
-                wrapper.display()
+    }
+
+    /// Counts `exists` checks, to tell a full scan from a cache hit.
+    struct CountingFs {
+        inner: Mutex<FakeFs>,
+        checks: AtomicUsize,
+    }
+
+    impl CountingFs {
+        fn new(inner: FakeFs) -> Self {
+            Self {
+                inner: Mutex::new(inner),
+                checks: AtomicUsize::new(0),
+            }
+        }
+
+        fn checks(&self) -> usize {
+            self.checks.load(Ordering::SeqCst)
+        }
+    }
+
+    impl DiscoveryFs for CountingFs {
+        fn exists(&self, path: &Path) -> bool {
+            self.checks.fetch_add(1, Ordering::SeqCst);
+            self.inner.lock().unwrap().exists(path)
+        }
+
+        fn read_to_string(&self, path: &Path) -> Option<String> {
+            self.inner.lock().unwrap().read_to_string(path)
+        }
+
+        fn list_dir(&self, dir: &Path) -> Vec<PathBuf> {
+            self.inner.lock().unwrap().list_dir(dir)
+        }
+    }
+
+    fn linux_env(path: &str) -> DiscoveryEnv {
+        DiscoveryEnv {
+            os: "linux",
+            home: Some(PathBuf::from("/home/me")),
+            codex_path: None,
+            path: Some(path.into()),
+            appdata: None,
+            local_appdata: None,
+            scoop: None,
+        }
+    }
+
+    fn windows_env() -> DiscoveryEnv {
+        DiscoveryEnv {
+            os: "windows",
+            home: Some(PathBuf::from("/users/me")),
+            codex_path: None,
+            path: Some("".into()),
+            appdata: Some(PathBuf::from("/users/me/AppData/Roaming")),
+            local_appdata: Some(PathBuf::from("/users/me/AppData/Local")),
+            scoop: None,
+        }
+    }
+
+    const NPM_SHIM: &str = "/users/me/AppData/Roaming/npm/codex.cmd";
+    const SCOOP_SHIM: &str = "/users/me/scoop/shims/codex.exe";
+    const WINGET_LINK: &str = "/users/me/AppData/Local/Microsoft/WinGet/Links/codex.exe";
+    const LOCAL_BIN: &str = "/users/me/.local/bin/codex.exe";
+
+    #[test]
+    fn windows_native_locations_are_ordered_cargo_scoop_winget_local_bin() {
+        let locations = native_install_locations(&windows_env(), &FakeFs::default(), "codex");
+        let position = |path: &str| {
+            locations
+                .iter()
+                .position(|p| p == Path::new(path))
+                .unwrap_or_else(|| panic!("{} not searched", path))
+        };
+
+        let cargo = position("/users/me/.cargo/bin/codex.exe");
+        assert!(cargo < position(SCOOP_SHIM));
+        assert!(position(SCOOP_SHIM) < position(WINGET_LINK));
+        assert!(position(WINGET_LINK) < position(LOCAL_BIN));
+    }
+
+    #[test]
+    fn windows_discovery_picks_the_first_native_install() {
+        let env = windows_env();
+        let installs = [SCOOP_SHIM, WINGET_LINK, LOCAL_BIN];
+        for (i, expected) in installs.iter().enumerate() {
+            let fs = installs[i..]
+                .iter()
+                .fold(FakeFs::default(), |fs, path| fs.binary(path));
+            assert_eq!(
+                discover_in(&env, &fs, "codex"),
+                Some(PathBuf::from(expected))
             );
-            return Some(wrapper);
         }
     }
 
-    log::warn!("No codex binary found in common locations or PATH");
-    None
+    #[test]
+    fn npm_shim_does_not_win_over_a_native_scoop_install() {
+        let fs = FakeFs::default()
+            .script(
+                NPM_SHIM,
+                "@node \"%~dp0\\node_modules\\@openai\\codex\\bin\\codex.js\" %*\r\n",
+            )
+            .binary(SCOOP_SHIM);
+        assert_eq!(
+            discover_in(&windows_env(), &fs, "codex"),
+            Some(PathBuf::from(SCOOP_SHIM))
+        );
+
+        // With nothing native installed the shim is still used
+        let fs = FakeFs::default().script(NPM_SHIM, "node codex.js %*\r\n");
+        assert_eq!(
+            discover_in(&windows_env(), &fs, "codex"),
+            Some(PathBuf::from(NPM_SHIM))
+        );
+    }
+
+    #[test]
+    fn wrapper_in_scoop_shims_is_skipped_for_a_native_winget_link() {
+        let fs = FakeFs::default()
+            .script(SCOOP_SHIM, "#!/usr/bin/env node\nimport './codex.js'\n")
+            .binary(WINGET_LINK);
+        assert_eq!(
+            discover_in(&windows_env(), &fs, "codex"),
+            Some(PathBuf::from(WINGET_LINK))
+        );
+    }
+
+    #[test]
+    fn scoop_root_comes_from_the_scoop_variable() {
+        let env = DiscoveryEnv {
+            scoop: Some(PathBuf::from("/tools/scoop")),
+            ..windows_env()
+        };
+        let fs = FakeFs::default()
+            .binary(SCOOP_SHIM)
+            .binary("/tools/scoop/shims/codex.exe");
+        assert_eq!(
+            discover_in(&env, &fs, "codex"),
+            Some(PathBuf::from("/tools/scoop/shims/codex.exe"))
+        );
+    }
+
+    #[test]
+    fn windows_locations_are_not_searched_elsewhere() {
+        let env = DiscoveryEnv {
+            os: "macos",
+            ..windows_env()
+        };
+        assert!(windows_package_locations(&env, "codex").is_empty());
+        assert!(npm_shim_locations(&env, "codex").is_empty());
+    }
+
+    #[test]
+    fn discovers_a_binary_under_a_custom_name() {
+        let fs = FakeFs::default()
+            .binary("/usr/bin/codex")
+            .binary("/opt/fork/bin/mycodex");
+        let env = linux_env("/usr/bin:/opt/fork/bin");
+
+        assert_eq!(
+            discover_in(&env, &fs, "mycodex"),
+            Some(PathBuf::from("/opt/fork/bin/mycodex"))
+        );
+        assert_eq!(
+            discover_in(&env, &fs, "codex"),
+            Some(PathBuf::from("/usr/bin/codex"))
+        );
+    }
+
+    #[test]
+    fn custom_name_looks_in_its_own_npm_package() {
+        let package_binary = format!(
+            "/home/me/.bun/install/global/node_modules/@openai/mycodex/bin/{}",
+            get_platform_binary_name("mycodex")
+        );
+        let fs = FakeFs::default()
+            .binary(&package_binary)
+            .binary("/home/me/.cargo/bin/mycodex");
+
+        assert_eq!(
+            discover_in(&linux_env(""), &fs, "mycodex"),
+            Some(PathBuf::from(package_binary))
+        );
+    }
+
+    #[test]
+    fn custom_name_wrapper_is_recognized_by_its_js_entry() {
+        let fs = FakeFs::default()
+            .script("/a/bin/mycodex", "require('../lib/mycodex.js')\n")
+            .binary("/b/bin/mycodex");
+
+        assert_eq!(
+            discover_in(&linux_env("/a/bin:/b/bin"), &fs, "mycodex"),
+            Some(PathBuf::from("/b/bin/mycodex"))
+        );
+    }
+
+    #[test]
+    fn without_a_home_no_relative_locations_are_probed() {
+        let env = DiscoveryEnv {
+            home: None,
+            ..linux_env("")
+        };
+        let binary_name = get_platform_binary_name("codex");
+        let locations: Vec<PathBuf> = package_binary_locations(&env, "codex", &binary_name)
+            .into_iter()
+            .chain(native_install_locations(&env, &FakeFs::default(), "codex"))
+            .collect();
+
+        assert!(!locations.is_empty());
+        assert!(locations.iter().all(|p| p.has_root()), "{:?}", locations);
+    }
+
+    #[test]
+    fn second_discovery_reuses_the_cached_path() {
+        let cache = PathCache::new(None);
+        let env = linux_env("/a/bin:/b/bin:/c/bin");
+        let fs = CountingFs::new(FakeFs::default().binary("/c/bin/codex"));
+        let discover =
+            || cached_discovery(&cache, "codex", &fs, || discover_in(&env, &fs, "codex"));
+
+        assert_eq!(discover(), Some(PathBuf::from("/c/bin/codex")));
+        let after_scan = fs.checks();
+        assert!(after_scan > 1);
+
+        // Only the cached path is checked again, no directory is searched
+        assert_eq!(discover(), Some(PathBuf::from("/c/bin/codex")));
+        assert_eq!(fs.checks(), after_scan + 1);
+    }
+
+    #[test]
+    fn removed_binary_or_new_name_triggers_a_fresh_scan() {
+        let cache = PathCache::new(None);
+        let env = linux_env("/a/bin:/b/bin");
+        let fs = CountingFs::new(
+            FakeFs::default()
+                .binary("/a/bin/codex")
+                .binary("/b/bin/codex")
+                .binary("/b/bin/mycodex"),
+        );
+        let discover =
+            |name: &str| cached_discovery(&cache, name, &fs, || discover_in(&env, &fs, name));
+
+        assert_eq!(discover("codex"), Some(PathBuf::from("/a/bin/codex")));
+        fs.inner
+            .lock()
+            .unwrap()
+            .files
+            .remove(Path::new("/a/bin/codex"));
+        assert_eq!(discover("codex"), Some(PathBuf::from("/b/bin/codex")));
+        assert_eq!(discover("mycodex"), Some(PathBuf::from("/b/bin/mycodex")));
+    }
+
+    #[test]
+    fn invalidated_cache_scans_again() {
+        let cache = PathCache::new(Some(("codex".to_string(), None)));
+        let fs = FakeFs::default().binary("/a/bin/codex");
+        let env = linux_env("/a/bin");
+
+        assert_eq!(
+            cached_discovery(&cache, "codex", &fs, || discover_in(&env, &fs, "codex")),
+            None
+        );
+        *cache.lock().unwrap() = None;
+        assert_eq!(
+            cached_discovery(&cache, "codex", &fs, || discover_in(&env, &fs, "codex")),
+            Some(PathBuf::from("/a/bin/codex"))
+        );
+    }
+
+    const FLATPAK_LAUNCHER: &str = "#!/bin/sh\nexec /usr/bin/flatpak run --branch=stable --arch=x86_64 com.openai.Codex \"$@\"\n";
+
+    #[test]
+    fn flatpak_exports_are_matched_on_the_app_id() {
+        let fs = FakeFs::default()
+            .script(
+                "/var/lib/flatpak/exports/bin/org.example.Editor",
+                FLATPAK_LAUNCHER,
+            )
+            .script(
+                "/var/lib/flatpak/exports/bin/com.openai.CodexBeta",
+                FLATPAK_LAUNCHER,
+            )
+            .script(
+                "/var/lib/flatpak/exports/bin/com.openai.Codex",
+                FLATPAK_LAUNCHER,
+            );
+
+        let found = discover_in(&linux_env(""), &fs, "codex");
+        assert_eq!(
+            found,
+            Some(PathBuf::from(
+                "/var/lib/flatpak/exports/bin/com.openai.Codex"
+            ))
+        );
+        assert_eq!(
+            classify_candidate(&fs, &found.unwrap(), "codex.js"),
+            (CodexBinaryKind::Native, "Flatpak launcher".to_string())
+        );
+    }
+
+    #[test]
+    fn user_flatpak_export_is_found_after_the_system_one() {
+        let user_export = "/home/me/.local/share/flatpak/exports/bin/io.github.someone.codex";
+        let fs = FakeFs::default().script(user_export, FLATPAK_LAUNCHER);
+        assert_eq!(
+            discover_in(&linux_env(""), &fs, "codex"),
+            Some(PathBuf::from(user_export))
+        );
+
+        let fs = fs.script(
+            "/var/lib/flatpak/exports/bin/com.openai.Codex",
+            FLATPAK_LAUNCHER,
+        );
+        assert_eq!(
+            discover_in(&linux_env(""), &fs, "codex"),
+            Some(PathBuf::from(
+                "/var/lib/flatpak/exports/bin/com.openai.Codex"
+            ))
+        );
+    }
+
+    #[test]
+    fn flatpak_export_named_like_the_binary_is_not_an_app_id() {
+        let fs = FakeFs::default().script("/var/lib/flatpak/exports/bin/codex", FLATPAK_LAUNCHER);
+        assert_eq!(discover_in(&linux_env(""), &fs, "codex"), None);
+    }
 }
@@ -0,0 +1,247 @@
+//! Line-based unified diff (Myers' O(ND) algorithm), used to preview edits before they're written.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Edit {
+    Equal(usize, usize),
+    Delete(usize),
+    Insert(usize),
+}
+
+/// Edit distance past which `myers` gives up. Time grows as O((N+M)·D) and the
+/// backtracking trace as O(D²), so this bounds both at a few megabytes.
+const MAX_EDIT_DISTANCE: usize = 1_000;
+
+/// Shortest edit script from `a` to `b`, or `None` when it needs more than
+/// `MAX_EDIT_DISTANCE` edits. Only the diagonals reachable at each step are kept
+/// for backtracking, so memory is O(D²) rather than O(D·(N+M)).
+fn myers<T: PartialEq>(a: &[T], b: &[T]) -> Option<Vec<Edit>> {
+    let n = a.len() as isize;
+    let m = b.len() as isize;
+    let max = (n + m) as usize;
+    let offset = max as isize + 1;
+    let mut v = vec![0isize; 2 * max + 3];
+    let mut trace: Vec<Vec<isize>> = Vec::new();
+
+    'search: for d in 0..=max as isize {
+        if d as usize > MAX_EDIT_DISTANCE {
+            return None;
+        }
+        // v[k] for k in [-(d+1), d+1] is everything backtracking needs at this step
+        trace.push(v[(offset - d - 1) as usize..=(offset + d + 1) as usize].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let idx = (offset + k) as usize;
+            let mut x = if k == -d || (k != d && v[idx - 1] < v[idx + 1]) {
+                v[idx + 1]
+            } else {
+                v[idx - 1] + 1
+            };
+            let mut y = x - k;
+            while x < n && y < m && a[x as usize] == b[y as usize] {
+                x += 1;
+                y += 1;
+            }
+            v[idx] = x;
+            if x >= n && y >= m {
+                break 'search;
+            }
+        }
+    }
+
+    let mut edits = Vec::new();
+    let (mut x, mut y) = (n, m);
+    for (d, v) in trace.iter().enumerate().rev() {
+        let d = d as isize;
+        let at = |k: isize| v[(k + d + 1) as usize];
+        let k = x - y;
+        let prev_k = if k == -d || (k != d && at(k - 1) < at(k + 1)) {
+            k + 1
+        } else {
+            k - 1
+        };
+        let prev_x = at(prev_k);
+        let prev_y = prev_x - prev_k;
+
+        while x > prev_x && y > prev_y {
+            edits.push(Edit::Equal((x - 1) as usize, (y - 1) as usize));
+            x -= 1;
+            y -= 1;
+        }
+        if d > 0 {
+            if x == prev_x {
+                edits.push(Edit::Insert((y - 1) as usize));
+            } else {
+                edits.push(Edit::Delete((x - 1) as usize));
+            }
+        }
+        x = prev_x;
+        y = prev_y;
+    }
+
+    edits.reverse();
+    Some(edits)
+}
+
+/// Edit script that deletes all of `a` and inserts all of `b`, for inputs too
+/// different to search.
+fn replace_all(a_len: usize, b_len: usize) -> Vec<Edit> {
+    (0..a_len)
+        .map(Edit::Delete)
+        .chain((0..b_len).map(Edit::Insert))
+        .collect()
+}
+
+fn push_line(out: &mut String, prefix: char, line: &str) {
+    out.push(prefix);
+    out.push_str(line);
+    if !line.ends_with('\n') {
+        out.push_str("\n\\ No newline at end of file\n");
+    }
+}
+
+/// Hunk range in unified format: 1-based start, or the preceding line when empty.
+fn hunk_range(start: usize, len: usize) -> String {
+    if len == 0 {
+        format!("{},0", start)
+    } else {
+        format!("{},{}", start + 1, len)
+    }
+}
+
+/// Render a unified diff of `old` → `new` with `context` lines around each change.
+/// Returns an empty string when the inputs are identical. Inputs that differ in
+/// more than `MAX_EDIT_DISTANCE` lines are shown as one hunk replacing the file.
+pub fn unified_diff(
+    old: &str,
+    new: &str,
+    old_label: &str,
+    new_label: &str,
+    context: usize,
+) -> String {
+    let a: Vec<&str> = old.split_inclusive('\n').collect();
+    let b: Vec<&str> = new.split_inclusive('\n').collect();
+    let edits = myers(&a, &b).unwrap_or_else(|| replace_all(a.len(), b.len()));
+
+    let changes: Vec<usize> = edits
+        .iter()
+        .enumerate()
+        .filter(|(_, e)| !matches!(e, Edit::Equal(..)))
+        .map(|(i, _)| i)
+        .collect();
+    if changes.is_empty() {
+        return String::new();
+    }
+
+    let mut out = format!("--- {}\n+++ {}\n", old_label, new_label);
+
+    // Group changes whose context windows touch into one hunk
+    let mut i = 0;
+    while i < changes.len() {
+        let first = changes[i];
+        let mut last = first;
+        while i + 1 < changes.len() && changes[i + 1] - last <= 2 * context + 1 {
+            i += 1;
+            last = changes[i];
+        }
+        i += 1;
+
+        let start = first.saturating_sub(context);
+        let end = (last + context + 1).min(edits.len());
+        let hunk = &edits[start..end];
+
+        // Position in each file where the hunk begins
+        let (old_start, new_start) = edits[..start].iter().fold((0, 0), |(o, n), e| match e {
+            Edit::Equal(..) => (o + 1, n + 1),
+            Edit::Delete(_) => (o + 1, n),
+            Edit::Insert(_) => (o, n + 1),
+        });
+        let old_len = hunk
+            .iter()
+            .filter(|e| !matches!(e, Edit::Insert(_)))
+            .count();
+        let new_len = hunk
+            .iter()
+            .filter(|e| !matches!(e, Edit::Delete(_)))
+            .count();
+
+        out.push_str(&format!(
+            "@@ -{} +{} @@\n",
+            hunk_range(old_start, old_len),
+            hunk_range(new_start, new_len)
+        ));
+        for edit in hunk {
+            match *edit {
+                Edit::Equal(x, _) => push_line(&mut out, ' ', a[x]),
+                Edit::Delete(x) => push_line(&mut out, '-', a[x]),
+                Edit::Insert(y) => push_line(&mut out, '+', b[y]),
+            }
+        }
+    }
+
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn empty_and_identical_inputs_have_no_diff() {
+        assert_eq!(unified_diff("", "", "a", "b", 3), "");
+        assert_eq!(unified_diff("x\ny\n", "x\ny\n", "a", "b", 3), "");
+    }
+
+    #[test]
+    fn new_and_deleted_files_are_one_hunk() {
+        assert_eq!(
+            unified_diff("", "x\ny\n", "/dev/null", "b", 3),
+            "--- /dev/null\n+++ b\n@@ -0,0 +1,2 @@\n+x\n+y\n"
+        );
+        assert_eq!(
+            unified_diff("x\n", "", "a", "/dev/null", 3),
+            "--- a\n+++ /dev/null\n@@ -1,1 +0,0 @@\n-x\n"
+        );
+    }
+
+    #[test]
+    fn every_line_changed() {
+        assert_eq!(
+            unified_diff("a\nb\n", "c\nd\n", "old", "new", 3),
+            "--- old\n+++ new\n@@ -1,2 +1,2 @@\n-a\n-b\n+c\n+d\n"
+        );
+    }
+
+    #[test]
+    fn missing_trailing_newline_is_marked() {
+        assert_eq!(
+            unified_diff("a\nb\n", "a\nb", "old", "new", 3),
+            "--- old\n+++ new\n@@ -1,2 +1,2 @@\n a\n-b\n+b\n\\ No newline at end of file\n"
+        );
+    }
+
+    #[test]
+    fn distant_changes_get_separate_hunks() {
+        let old: String = (1..=20).map(|i| format!("{}\n", i)).collect();
+        let new = old.replace("2\n", "two\n").replace("19\n", "nineteen\n");
+        let diff = unified_diff(&old, &new, "old", "new", 1);
+        assert!(diff.contains("@@ -1,3 +1,3 @@\n 1\n-2\n+two\n 3\n"));
+        assert!(diff.contains("@@ -18,3 +18,3 @@\n 18\n-19\n+nineteen\n 20\n"));
+    }
+
+    #[test]
+    fn too_many_edits_fall_back_to_replacing_everything() {
+        let old: String = (0..=MAX_EDIT_DISTANCE)
+            .map(|i| format!("a{}\n", i))
+            .collect();
+        let new: String = (0..=MAX_EDIT_DISTANCE)
+            .map(|i| format!("b{}\n", i))
+            .collect();
+        let a: Vec<&str> = old.split_inclusive('\n').collect();
+        let b: Vec<&str> = new.split_inclusive('\n').collect();
+        assert!(myers(&a, &b).is_none());
+
+        let diff = unified_diff(&old, &new, "old", "new", 3);
+        let header = format!("@@ -1,{0} +1,{0} @@\n", MAX_EDIT_DISTANCE + 1);
+        assert!(diff.starts_with(&format!("--- old\n+++ new\n{}", header)));
+        assert_eq!(diff.matches("@@ -").count(), 1);
+    }
+}
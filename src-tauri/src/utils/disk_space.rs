@@ -0,0 +1,85 @@
+use std::path::Path;
+
+/// Nearest ancestor of `path` that exists, so space can be queried for files
+/// and directories that are about to be created.
+fn existing_ancestor(path: &Path) -> Option<&Path> {
+    path.ancestors().find(|p| p.exists())
+}
+
+#[cfg(unix)]
+fn free_bytes(path: &Path) -> Result<u64, String> {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+
+    let c_path = CString::new(path.as_os_str().as_bytes())
+        .map_err(|_| format!("Invalid path: {}", path.display()))?;
+    let mut stat: libc::statvfs = unsafe { std::mem::zeroed() };
+    // SAFETY: c_path is NUL-terminated and stat is a valid out pointer
+    if unsafe { libc::statvfs(c_path.as_ptr(), &mut stat) } != 0 {
+        return Err(format!(
+            "Failed to query disk space for {}: {}",
+            path.display(),
+            std::io::Error::last_os_error()
+        ));
+    }
+    // Field widths differ between platforms (u32 on macOS, u64 on Linux)
+    #[allow(clippy::unnecessary_cast)]
+    Ok(stat.f_bavail as u64 * stat.f_frsize as u64)
+}
+
+#[cfg(windows)]
+fn free_bytes(path: &Path) -> Result<u64, String> {
+    use std::os::windows::ffi::OsStrExt;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetDiskFreeSpaceExW(
+            directory: *const u16,
+            free_to_caller: *mut u64,
+            total: *mut u64,
+            total_free: *mut u64,
+        ) -> i32;
+    }
+
+    let wide: Vec<u16> = path.as_os_str().encode_wide().chain(Some(0)).collect();
+    let mut free_to_caller = 0u64;
+    // SAFETY: wide is NUL-terminated; null pointers are allowed for the unused outputs
+    let ok = unsafe {
+        GetDiskFreeSpaceExW(
+            wide.as_ptr(),
+            &mut free_to_caller,
+            std::ptr::null_mut(),
+            std::ptr::null_mut(),
+        )
+    };
+    if ok == 0 {
+        return Err(format!(
+            "Failed to query disk space for {}: {}",
+            path.display(),
+            std::io::Error::last_os_error()
+        ));
+    }
+    Ok(free_to_caller)
+}
+
+/// Bytes available to this user on the filesystem holding `path`.
+pub fn available_space(path: &Path) -> Result<u64, String> {
+    let existing = existing_ancestor(path)
+        .ok_or_else(|| format!("No existing parent for {}", path.display()))?;
+    free_bytes(existing)
+}
+
+/// Fail early with a readable message when writing `needed` bytes under `path`
+/// would run the disk out of space.
+pub fn ensure_space(path: &Path, needed: u64) -> Result<(), String> {
+    let available = available_space(path)?;
+    if needed > available {
+        return Err(format!(
+            "Not enough disk space to write to {} ({} bytes needed, {} available)",
+            path.display(),
+            needed,
+            available
+        ));
+    }
+    Ok(())
+}
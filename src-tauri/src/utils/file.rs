@@ -1,11 +1,94 @@
-use std::fs;
+use flate2::read::GzDecoder;
+use serde::Serialize;
+use std::fmt;
+use std::fs::{self, File, OpenOptions};
+use std::io::{BufRead, BufReader, ErrorKind, Read, Seek, SeekFrom};
 use std::path::{Path, PathBuf};
+use std::time::Duration;
 use walkdir::WalkDir;
 
-#[allow(dead_code)]
-pub fn get_sessions_path() -> Result<PathBuf, String> {
-    let home_dir = dirs::home_dir().ok_or("Could not find home directory")?;
-    Ok(home_dir.join(".codex").join("sessions"))
+use crate::settings::load_settings;
+use crate::utils::parallel::parallel_map;
+
+const READ_RETRY_ATTEMPTS: u32 = 3;
+const READ_RETRY_BASE_DELAY_MS: u64 = 50;
+const TAIL_CHUNK_SIZE: u64 = 4096;
+/// Suffix of rollouts gzipped by `compress_rollouts_on_close`.
+pub const COMPRESSED_ROLLOUT_SUFFIX: &str = ".jsonl.gz";
+
+/// Returned when neither the environment nor the OS can tell us where home is.
+#[derive(Debug, Clone, Serialize)]
+pub struct HomeDirError {
+    /// Environment variables that were consulted, in order
+    pub checked: Vec<&'static str>,
+}
+
+impl fmt::Display for HomeDirError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "Could not determine home directory (checked {})",
+            self.checked.join(", ")
+        )
+    }
+}
+
+impl From<HomeDirError> for String {
+    fn from(err: HomeDirError) -> Self {
+        err.to_string()
+    }
+}
+
+fn non_empty_env(key: &str) -> Option<PathBuf> {
+    std::env::var_os(key)
+        .filter(|value| !value.is_empty())
+        .map(PathBuf::from)
+}
+
+/// The user's home directory, honoring `HOME` then `USERPROFILE` before asking the OS.
+pub fn user_home() -> Result<PathBuf, HomeDirError> {
+    resolve_user_home(non_empty_env, dirs::home_dir)
+}
+
+fn resolve_user_home(
+    env: impl Fn(&str) -> Option<PathBuf>,
+    os_home: impl FnOnce() -> Option<PathBuf>,
+) -> Result<PathBuf, HomeDirError> {
+    env("HOME")
+        .or_else(|| env("USERPROFILE"))
+        .or_else(os_home)
+        .ok_or(HomeDirError {
+            checked: vec!["HOME", "USERPROFILE"],
+        })
+}
+
+/// `CODEX_HOME` when set, otherwise `~/.codex`. Codexia keeps its own files
+/// (settings, session metadata, index, approval log) here; codex data is found
+/// through `CodexState::codex_home`, which also honors `set_codex_home`.
+pub fn default_codex_home() -> Result<PathBuf, HomeDirError> {
+    resolve_codex_home(non_empty_env, user_home)
+}
+
+fn resolve_codex_home(
+    env: impl Fn(&str) -> Option<PathBuf>,
+    user_home: impl FnOnce() -> Result<PathBuf, HomeDirError>,
+) -> Result<PathBuf, HomeDirError> {
+    if let Some(codex_home) = env("CODEX_HOME") {
+        return Ok(codex_home);
+    }
+    user_home()
+        .map(|home| home.join(".codex"))
+        .map_err(|_| HomeDirError {
+            checked: vec!["CODEX_HOME", "HOME", "USERPROFILE"],
+        })
+}
+
+/// Where codex rollouts live: `<codex_home>/sessions` unless relocated in settings.
+pub fn sessions_path_in(codex_home: &Path) -> PathBuf {
+    match load_settings().sessions_dir.filter(|d| !d.is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => codex_home.join("sessions"),
+    }
 }
 
 #[allow(dead_code)]
@@ -17,6 +100,90 @@ pub fn scan_jsonl_files<P: AsRef<Path>>(dir_path: P) -> impl Iterator<Item = wal
         .filter(|e| e.path().extension().and_then(|s| s.to_str()) == Some("jsonl"))
 }
 
+pub fn is_compressed_rollout(path: &Path) -> bool {
+    path.file_name()
+        .is_some_and(|name| name.to_string_lossy().ends_with(COMPRESSED_ROLLOUT_SUFFIX))
+}
+
+/// Plain `.jsonl` or gzipped `.jsonl.gz` rollout.
+pub fn is_rollout_file(path: &Path) -> bool {
+    path.extension().and_then(|s| s.to_str()) == Some("jsonl") || is_compressed_rollout(path)
+}
+
+/// Create and remove a probe file in `dir`, since permission bits don't tell
+/// the whole story on network shares and with ACLs.
+pub fn probe_writable(dir: &Path) -> Result<(), String> {
+    let probe = dir.join(format!(".codexia-write-test-{}", std::process::id()));
+    OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .open(&probe)
+        .map_err(|e| e.to_string())?;
+    let _ = fs::remove_file(&probe);
+    Ok(())
+}
+
+/// Like `scan_jsonl_files`, but also finds gzipped rollouts. Read what it
+/// returns with `open_rollout` or `read_to_string_with_retry`.
+pub fn scan_rollout_files<P: AsRef<Path>>(dir_path: P) -> impl Iterator<Item = walkdir::DirEntry> {
+    WalkDir::new(dir_path)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file() && is_rollout_file(e.path()))
+}
+
+/// Open a rollout for reading line by line, decompressing `.jsonl.gz` on the fly.
+pub fn open_rollout(path: &Path) -> std::io::Result<Box<dyn BufRead + Send>> {
+    let file = File::open(path)?;
+    if is_compressed_rollout(path) {
+        Ok(Box::new(BufReader::new(GzDecoder::new(file))))
+    } else {
+        Ok(Box::new(BufReader::new(file)))
+    }
+}
+
+fn read_rollout_to_string(path: &Path) -> std::io::Result<String> {
+    let mut content = String::new();
+    open_rollout(path)?.read_to_string(&mut content)?;
+    Ok(content)
+}
+
+/// Depth of the directories handed to separate workers; with codex's `YYYY/MM/DD`
+/// layout these are the day directories.
+const PARALLEL_SPLIT_DEPTH: usize = 3;
+
+/// The same rollouts `scan_rollout_files` finds, in no particular order, with
+/// each directory at `PARALLEL_SPLIT_DEPTH` walked on one of `workers` threads.
+pub fn scan_jsonl_files_parallel(dir_path: &Path, workers: usize) -> Vec<PathBuf> {
+    let is_jsonl = |e: &walkdir::DirEntry| e.file_type().is_file() && is_rollout_file(e.path());
+
+    let mut files = Vec::new();
+    let mut split_dirs = Vec::new();
+    for entry in WalkDir::new(dir_path)
+        .max_depth(PARALLEL_SPLIT_DEPTH)
+        .into_iter()
+        .filter_map(|e| e.ok())
+    {
+        if is_jsonl(&entry) {
+            files.push(entry.into_path());
+        } else if entry.depth() == PARALLEL_SPLIT_DEPTH && entry.file_type().is_dir() {
+            split_dirs.push(entry.into_path());
+        }
+    }
+
+    let nested = parallel_map(&split_dirs, workers, |dir| {
+        WalkDir::new(dir)
+            .min_depth(1)
+            .into_iter()
+            .filter_map(|e| e.ok())
+            .filter(is_jsonl)
+            .map(|e| e.into_path())
+            .collect::<Vec<_>>()
+    });
+    files.extend(nested.into_iter().flatten());
+    files
+}
+
 #[allow(dead_code)]
 pub fn read_file_content<P: AsRef<Path>>(file_path: P) -> Result<String, String> {
     fs::read_to_string(&file_path)
@@ -33,3 +200,203 @@ pub fn remove_file<P: AsRef<Path>>(file_path: P) -> Result<(), String> {
 pub fn get_file_modification_time<P: AsRef<Path>>(file_path: P) -> Option<std::time::SystemTime> {
     file_path.as_ref().metadata().ok()?.modified().ok()
 }
+
+fn is_transient_io_error(err: &std::io::Error) -> bool {
+    matches!(
+        err.kind(),
+        ErrorKind::Interrupted
+            | ErrorKind::WouldBlock
+            | ErrorKind::TimedOut
+            | ErrorKind::ResourceBusy
+    )
+}
+
+/// Read a file to string, retrying with exponential backoff on transient errors
+/// (EINTR/EBUSY and friends, which show up on NFS/SMB mounted homes).
+/// Permanent errors such as not-found are returned immediately. Gzipped
+/// rollouts (`.jsonl.gz`) are decompressed.
+pub fn read_to_string_with_retry_blocking<P: AsRef<Path>>(file_path: P) -> std::io::Result<String> {
+    let mut attempt = 0;
+    loop {
+        match read_rollout_to_string(file_path.as_ref()) {
+            Err(e) if is_transient_io_error(&e) && attempt < READ_RETRY_ATTEMPTS => {
+                let delay = READ_RETRY_BASE_DELAY_MS << attempt;
+                attempt += 1;
+                log::debug!(
+                    "Transient error reading {:?} ({}), retry {}/{} in {}ms",
+                    file_path.as_ref(),
+                    e,
+                    attempt,
+                    READ_RETRY_ATTEMPTS,
+                    delay
+                );
+                std::thread::sleep(Duration::from_millis(delay));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Async wrapper around [`read_to_string_with_retry_blocking`] that keeps the
+/// blocking IO and backoff sleeps off the async runtime threads.
+pub async fn read_to_string_with_retry<P: AsRef<Path>>(file_path: P) -> std::io::Result<String> {
+    let file_path = file_path.as_ref().to_path_buf();
+    tokio::task::spawn_blocking(move || read_to_string_with_retry_blocking(file_path))
+        .await
+        .map_err(std::io::Error::other)?
+}
+
+/// Read the first and last non-empty lines of a file without loading it whole.
+/// The last line is found by reading backwards from the end in small chunks.
+/// Returns `None` for an empty file; for single-line files both lines are the same.
+/// Gzipped rollouts can't be read backwards and are decompressed whole.
+pub fn read_first_and_last_lines<P: AsRef<Path>>(
+    file_path: P,
+) -> std::io::Result<Option<(String, String)>> {
+    if is_compressed_rollout(file_path.as_ref()) {
+        let content = read_rollout_to_string(file_path.as_ref())?;
+        let first_line = content.lines().next().unwrap_or_default().trim_end();
+        if first_line.is_empty() {
+            return Ok(None);
+        }
+        let last_line = content
+            .lines()
+            .rev()
+            .find(|line| !line.trim().is_empty())
+            .unwrap_or(first_line)
+            .trim_end();
+        return Ok(Some((first_line.to_string(), last_line.to_string())));
+    }
+
+    let mut file = File::open(file_path)?;
+
+    let mut first_line = String::new();
+    BufReader::new(&file).read_line(&mut first_line)?;
+    let first_line = first_line.trim_end().to_string();
+    if first_line.is_empty() {
+        return Ok(None);
+    }
+
+    let mut end = file.metadata()?.len();
+    let mut tail: Vec<u8> = Vec::new();
+    let last_line = loop {
+        let start = end.saturating_sub(TAIL_CHUNK_SIZE);
+        file.seek(SeekFrom::Start(start))?;
+        let mut chunk = vec![0u8; (end - start) as usize];
+        file.read_exact(&mut chunk)?;
+        chunk.extend_from_slice(&tail);
+        tail = chunk;
+
+        // Ignore trailing newlines, then look for the line break before the last line
+        let content_end = tail
+            .iter()
+            .rposition(|b| *b != b'\n' && *b != b'\r')
+            .map_or(0, |pos| pos + 1);
+        if let Some(newline) = tail[..content_end].iter().rposition(|b| *b == b'\n') {
+            break String::from_utf8_lossy(&tail[newline + 1..content_end]).into_owned();
+        }
+        if start == 0 {
+            break String::from_utf8_lossy(&tail[..content_end]).into_owned();
+        }
+        end = start;
+    };
+
+    Ok(Some((first_line, last_line)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn env_with(vars: &'static [(&'static str, &'static str)]) -> impl Fn(&str) -> Option<PathBuf> {
+        move |key| {
+            vars.iter()
+                .find(|(name, _)| *name == key)
+                .map(|(_, value)| PathBuf::from(value))
+        }
+    }
+
+    #[test]
+    fn missing_home_reports_the_variables_checked() {
+        let err = resolve_user_home(env_with(&[]), || None).unwrap_err();
+        assert_eq!(err.checked, vec!["HOME", "USERPROFILE"]);
+        assert_eq!(
+            String::from(err),
+            "Could not determine home directory (checked HOME, USERPROFILE)"
+        );
+    }
+
+    #[test]
+    fn home_falls_back_from_home_to_userprofile_to_the_os() {
+        let home = resolve_user_home(env_with(&[("USERPROFILE", "/profile")]), || {
+            Some(PathBuf::from("/os"))
+        });
+        assert_eq!(home.unwrap(), PathBuf::from("/profile"));
+
+        let home = resolve_user_home(env_with(&[]), || Some(PathBuf::from("/os")));
+        assert_eq!(home.unwrap(), PathBuf::from("/os"));
+    }
+
+    #[test]
+    fn missing_home_without_codex_home_is_an_error() {
+        let err = resolve_codex_home(env_with(&[]), || resolve_user_home(env_with(&[]), || None))
+            .unwrap_err();
+        assert_eq!(err.checked, vec!["CODEX_HOME", "HOME", "USERPROFILE"]);
+    }
+
+    #[test]
+    fn codex_home_does_not_need_a_home_directory() {
+        let codex_home = resolve_codex_home(env_with(&[("CODEX_HOME", "/data/codex")]), || {
+            resolve_user_home(env_with(&[]), || None)
+        });
+        assert_eq!(codex_home.unwrap(), PathBuf::from("/data/codex"));
+
+        let codex_home = resolve_codex_home(env_with(&[]), || Ok(PathBuf::from("/home/me")));
+        assert_eq!(codex_home.unwrap(), PathBuf::from("/home/me/.codex"));
+    }
+
+    /// `YYYY/MM/DD` tree of `days * per_day` rollouts, plus a few files at
+    /// other depths and some that aren't rollouts.
+    fn synthetic_sessions_tree(root: &Path, days: usize, per_day: usize) {
+        for day in 0..days {
+            let dir = root.join(format!(
+                "{}/{:02}/{:02}",
+                2024 + day / 336,
+                day / 28 % 12 + 1,
+                day % 28 + 1
+            ));
+            fs::create_dir_all(&dir).unwrap();
+            for n in 0..per_day {
+                let suffix = if n % 7 == 0 { ".jsonl.gz" } else { ".jsonl" };
+                fs::write(dir.join(format!("rollout-{}-{}{}", day, n, suffix)), "{}\n").unwrap();
+            }
+            fs::write(dir.join("notes.txt"), "").unwrap();
+        }
+        fs::write(root.join("rollout-top.jsonl"), "{}\n").unwrap();
+        fs::create_dir_all(root.join("2024/01/01/extra/deeper")).unwrap();
+        fs::write(
+            root.join("2024/01/01/extra/deeper/rollout-deep.jsonl"),
+            "{}\n",
+        )
+        .unwrap();
+    }
+
+    #[test]
+    fn parallel_scan_finds_the_same_rollouts_as_the_sequential_walk() {
+        let dir = tempfile::tempdir().unwrap();
+        synthetic_sessions_tree(dir.path(), 400, 8);
+
+        let mut expected: Vec<PathBuf> = scan_rollout_files(dir.path())
+            .map(|entry| entry.into_path())
+            .collect();
+        expected.sort();
+        assert_eq!(expected.len(), 400 * 8 + 2);
+
+        for workers in [1, 2, 8, 32] {
+            let mut found = scan_jsonl_files_parallel(dir.path(), workers);
+            assert_eq!(found.len(), expected.len(), "with {} workers", workers);
+            found.sort();
+            assert_eq!(found, expected, "with {} workers", workers);
+        }
+    }
+}
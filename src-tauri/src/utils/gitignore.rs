@@ -0,0 +1,37 @@
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Absolute paths under `root` that git considers ignored. Ignored directories
+/// are listed once rather than per file. Returns `None` when `root` isn't in a
+/// git work tree or git can't be run, so callers can fall back to walking everything.
+pub fn git_ignored_paths(root: &Path) -> Option<HashSet<PathBuf>> {
+    let output = Command::new("git")
+        .args([
+            "ls-files",
+            "--others",
+            "--ignored",
+            "--exclude-standard",
+            "--directory",
+            "-z",
+        ])
+        .current_dir(root)
+        .output()
+        .ok()?;
+
+    if !output.status.success() {
+        return None;
+    }
+
+    let ignored = output
+        .stdout
+        .split(|b| *b == 0)
+        .filter(|entry| !entry.is_empty())
+        .map(|entry| {
+            let relative = String::from_utf8_lossy(entry);
+            root.join(relative.trim_end_matches('/'))
+        })
+        .collect();
+
+    Some(ignored)
+}
@@ -1,3 +1,9 @@
 pub mod codex_discovery;
+pub mod diff;
+pub mod disk_space;
 pub mod file;
+pub mod gitignore;
+pub mod parallel;
+pub mod pricing;
 pub mod time;
+pub mod watcher;
@@ -0,0 +1,75 @@
+use serde::{Deserialize, Serialize};
+
+use crate::settings::load_settings;
+
+/// USD per million tokens for one model.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ModelRate {
+    pub input_per_million: f64,
+    /// Rate for prompt tokens served from cache; falls back to `input_per_million`.
+    #[serde(default)]
+    pub cached_input_per_million: Option<f64>,
+    pub output_per_million: f64,
+}
+
+const fn rate(input: f64, cached: f64, output: f64) -> ModelRate {
+    ModelRate {
+        input_per_million: input,
+        cached_input_per_million: Some(cached),
+        output_per_million: output,
+    }
+}
+
+/// Published OpenAI list prices, matched by model-name prefix. More specific
+/// prefixes come first. `model_rates` in settings takes precedence.
+const BUILTIN_RATES: &[(&str, ModelRate)] = &[
+    ("gpt-5-nano", rate(0.05, 0.005, 0.40)),
+    ("gpt-5-mini", rate(0.25, 0.025, 2.00)),
+    ("gpt-5", rate(1.25, 0.125, 10.00)),
+    ("gpt-4.1-nano", rate(0.10, 0.025, 0.40)),
+    ("gpt-4.1-mini", rate(0.40, 0.10, 1.60)),
+    ("gpt-4.1", rate(2.00, 0.50, 8.00)),
+    ("o4-mini", rate(1.10, 0.275, 4.40)),
+    ("o3", rate(2.00, 0.50, 8.00)),
+    ("codex-mini", rate(1.50, 0.375, 6.00)),
+];
+
+/// Rate for `model`: an exact or longest-prefix match in settings, then the built-in table.
+pub fn rate_for_model(model: &str) -> Option<ModelRate> {
+    let model = model.trim().to_lowercase();
+    let configured = load_settings().model_rates;
+    if let Some(rate) = configured
+        .iter()
+        .filter(|(name, _)| model.starts_with(&name.to_lowercase()))
+        .max_by_key(|(name, _)| name.len())
+        .map(|(_, rate)| *rate)
+    {
+        return Some(rate);
+    }
+    BUILTIN_RATES
+        .iter()
+        .find(|(prefix, _)| model.starts_with(prefix))
+        .map(|(_, rate)| *rate)
+}
+
+/// Estimated USD cost, or `None` when no rate is known for `model`.
+/// `input_tokens` includes the cached ones, as codex reports them.
+pub fn estimate_cost_usd(
+    model: &str,
+    input_tokens: u64,
+    cached_input_tokens: u64,
+    output_tokens: u64,
+) -> Option<f64> {
+    let rate = rate_for_model(model)?;
+    let cached = cached_input_tokens.min(input_tokens);
+    let uncached = input_tokens - cached;
+    let cached_rate = rate
+        .cached_input_per_million
+        .unwrap_or(rate.input_per_million);
+    Some(
+        (uncached as f64 * rate.input_per_million
+            + cached as f64 * cached_rate
+            + output_tokens as f64 * rate.output_per_million)
+            / 1_000_000.0,
+    )
+}
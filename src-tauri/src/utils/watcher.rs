@@ -0,0 +1,38 @@
+use notify::{Config, EventHandler, PollWatcher, RecommendedWatcher, Watcher};
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+
+use crate::settings::load_settings;
+
+/// Filesystem watcher implementation used for every watch the app starts.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum WatcherBackend {
+    /// OS notifications (inotify, FSEvents, ReadDirectoryChangesW). Cheap and
+    /// immediate, but events are often missing on SMB/NFS mounts.
+    #[default]
+    Native,
+    /// Rescan watched trees every `poll_interval_ms`. Works on any filesystem,
+    /// at the cost of periodic IO proportional to the tree size and delayed events.
+    Polling,
+}
+
+pub type BoxedWatcher = Box<dyn Watcher + Send + Sync>;
+
+/// Create a watcher with the backend chosen in settings.
+pub fn create_watcher<F: EventHandler>(handler: F) -> notify::Result<BoxedWatcher> {
+    let settings = load_settings();
+    match settings.watcher_backend {
+        WatcherBackend::Native => Ok(Box::new(RecommendedWatcher::new(
+            handler,
+            Config::default(),
+        )?)),
+        WatcherBackend::Polling => {
+            let interval = Duration::from_millis(settings.poll_interval_ms.max(100));
+            Ok(Box::new(PollWatcher::new(
+                handler,
+                Config::default().with_poll_interval(interval),
+            )?))
+        }
+    }
+}
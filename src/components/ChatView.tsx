@@ -50,8 +50,18 @@ export const ChatView: React.FC<ChatViewProps> = ({ selectedConversation, showCh
     return statuses;
   }, [activeConversations]);
 
-  const handleConversationSelect = (conversation: Conversation) => {
-    const conversationCopy = { ...conversation };
+  const handleConversationSelect = async (conversation: Conversation) => {
+    let conversationCopy = { ...conversation };
+    // Sessions served from the index come without messages
+    if (conversation.messages.length === 0 && conversation.messageCount && conversation.filePath) {
+      try {
+        conversationCopy = await invoke<Conversation>("load_session", {
+          filePath: conversation.filePath,
+        });
+      } catch (e) {
+        console.error("Failed to load session messages:", e);
+      }
+    }
     setInternalSelectedConversation(conversationCopy);
     console.log("🔄 ChatView: Calling selectHistoryConversation", conversation.id);
     selectHistoryConversation(conversationCopy);
@@ -17,6 +17,7 @@ import { ForkOriginBanner } from './ForkOriginBanner';
 import { useEphemeralStore } from '@/stores/EphemeralStore';
 import { ChangesSummary } from './ChangesSummary';
 import { ModelSelector } from "./ModelSelector";
+import { errorMessage, isApprovalNotPending, sessionStartErrorMessage } from "@/utils/codexError";
 
 interface ChatInterfaceProps {
   sessionId: string;
@@ -191,13 +192,13 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
                 setSessionLoading(sessionId, false);
               } catch (error) {
                 console.error("Failed to auto-start session:", error);
-                const errorMessage = {
+                const systemMessage = {
                   id: `${sessionId}-auto-start-error-${generateUniqueId()}`,
                   role: "system" as const,
-                  content: `Failed to start Codex session: ${error}`,
+                  content: sessionStartErrorMessage(error),
                   timestamp: Date.now(),
                 };
-                addMessage(sessionId, errorMessage);
+                addMessage(sessionId, systemMessage);
                 setSessionLoading(sessionId, false);
               }
             };
@@ -304,13 +305,13 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
     } catch (error) {
       console.error("Failed to start session:", error);
       setSessionStarting(false);
-      const errorMessage = {
+      const systemMessage = {
         id: `${actualSessionId}-startup-error-${generateUniqueId()}`,
         role: "system" as const,
-        content: `Failed to start Codex session: ${error}`,
+        content: sessionStartErrorMessage(error),
         timestamp: Date.now(),
       };
-      addMessage(actualSessionId, errorMessage);
+      addMessage(actualSessionId, systemMessage);
       setSessionLoading(actualSessionId, false);
       return;
     }
@@ -372,13 +373,13 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
       }
     } catch (error) {
       console.error("Failed to send message:", error);
-      const errorMessage = {
+      const systemMessage = {
         id: `${actualSessionId}-send-error-${generateUniqueId()}`,
         role: "system" as const,
-        content: `Failed to send message: ${error}`,
+        content: `Failed to send message: ${errorMessage(error)}`,
         timestamp: Date.now(),
       };
-      addMessage(actualSessionId, errorMessage);
+      addMessage(actualSessionId, systemMessage);
       setSessionLoading(actualSessionId, false);
     }
   };
@@ -434,13 +435,15 @@ export const ChatInterface: React.FC<ChatInterfaceProps> = ({
       console.error("Failed to send approval:", error);
       
       // Add error message to conversation
-      const errorMessage = {
+      const systemMessage = {
         id: `${sessionId}-approval-error-${generateUniqueId()}`,
         role: "system" as const,
-        content: `Failed to process approval: ${error}`,
+        content: isApprovalNotPending(error)
+          ? "This approval was already answered or has expired; nothing was sent to Codex."
+          : `Failed to process approval: ${errorMessage(error)}`,
         timestamp: Date.now(),
       };
-      addMessage(sessionId, errorMessage);
+      addMessage(sessionId, systemMessage);
     }
   };
 
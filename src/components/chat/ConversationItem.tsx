@@ -14,6 +14,7 @@ import {
 } from "@/components/ui/dropdown-menu";
 import type { Conversation } from "@/types/chat";
 import { useConversationStore } from "@/stores/ConversationStore";
+import { getMessageCount, getPreviewText } from "@/utils/conversationSummary";
 import { DropdownMenuSeparator } from "@/components/ui/dropdown-menu";
 import { Tag } from "lucide-react";
 
@@ -56,16 +57,6 @@ export function ConversationItem({
     }
   };
 
-  const getPreviewText = (conversation: Conversation) => {
-    const lastMessage = conversation.messages[conversation.messages.length - 1];
-    if (lastMessage) {
-      return lastMessage.content.length > 100
-        ? lastMessage.content.substring(0, 100) + "..."
-        : lastMessage.content;
-    }
-    return "No messages yet";
-  };
-
   return (
     <div
       key={`${tabPrefix}-${conversation.id}-${index}`}
@@ -95,7 +86,7 @@ export function ConversationItem({
             )}
           </div>
           <p className="text-xs text-muted-foreground mt-1 line-clamp-2">
-            {getPreviewText(conversation)}
+            {getPreviewText(conversation, 100) ?? "No messages yet"}
           </p>
 
           <div className="flex items-center justify-between mt-2">
@@ -103,7 +94,7 @@ export function ConversationItem({
               {formatDate(conversation.updatedAt)}
             </span>
             <span className="text-xs text-muted-foreground/70">
-              {conversation.messages.length} messages
+              {getMessageCount(conversation)} messages
             </span>
           </div>
         </div>
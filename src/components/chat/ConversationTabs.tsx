@@ -3,6 +3,7 @@ import { ConversationList } from "./ConversationList";
 import { SearchInput } from "@/components/common/SearchInput";
 import { useLayoutStore } from "@/stores/layoutStore";
 import type { Conversation } from "@/types/chat";
+import { matchesSearch } from "@/utils/conversationSummary";
 import React, { useMemo } from "react";
 
 interface ChatTabsProps {
@@ -50,12 +51,8 @@ export function ConversationTabs({
 
     if (searchQuery.trim()) {
       const query = searchQuery.toLowerCase();
-      conversations = conversations.filter(
-        (conversation) =>
-          conversation.title.toLowerCase().includes(query) ||
-          conversation.messages.some((msg) =>
-            msg.content.toLowerCase().includes(query),
-          ),
+      conversations = conversations.filter((conversation) =>
+        matchesSearch(conversation, query),
       );
     }
 
@@ -4,6 +4,7 @@ import { Badge } from '../ui/badge';
 import { MessageSquare, X, SquarePen } from 'lucide-react';
 import type { Conversation } from '@/types/chat';
 import { sessionManager } from '@/services/sessionManager';
+import { getMessageCount, getPreviewText } from '@/utils/conversationSummary';
 
 interface SessionManagerProps {
   conversations: Conversation[];
@@ -73,12 +74,11 @@ export const SessionManager: React.FC<SessionManagerProps> = ({
                       {conversation.title}
                     </h3>
                     <p className="text-xs text-gray-500 mt-1">
-                      {conversation.messages.length} messages
+                      {getMessageCount(conversation)} messages
                     </p>
-                    {conversation.messages.length > 0 && (
+                    {getMessageCount(conversation) > 0 && (
                       <p className="text-xs text-gray-400 mt-1 truncate">
-                        {conversation.messages[conversation.messages.length - 1].content.slice(0, 50)}
-                        {conversation.messages[conversation.messages.length - 1].content.length > 50 ? '...' : ''}
+                        {getPreviewText(conversation, 50)}
                       </p>
                     )}
                   </div>
@@ -89,7 +89,7 @@ export const SessionManager: React.FC<SessionManagerProps> = ({
                         Running
                       </Badge>
                     ) : (
-                      conversation.messages.length > 0 && (
+                      getMessageCount(conversation) > 0 && (
                         <Badge variant="outline" className="text-xs py-0 text-gray-500">
                           View Only
                         </Badge>
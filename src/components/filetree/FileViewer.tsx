@@ -9,6 +9,7 @@ import { useThemeStore } from "@/stores/ThemeStore";
 import { useConversationStore } from "@/stores/ConversationStore";
 import { useLayoutStore } from "@/stores/layoutStore";
 import { useChatInputStore } from "@/stores/chatInputStore";
+import { errorMessage } from "@/utils/codexError";
 
 interface FileViewerProps {
   filePath: string | null;
@@ -81,7 +82,11 @@ export function FileViewer({ filePath, onClose, addToNotepad }: FileViewerProps)
           });
           break;
         default:
-          fileContent = await invoke<string>("read_file", { filePath });
+          fileContent = (
+            await invoke<{ content: string; encoding: string }>("read_file", {
+              filePath,
+            })
+          ).content;
           break;
       }
 
@@ -89,7 +94,7 @@ export function FileViewer({ filePath, onClose, addToNotepad }: FileViewerProps)
       setCurrentContent(fileContent);
       setDiskChanged(false);
     } catch (err) {
-      setError(err as string);
+      setError(errorMessage(err));
     } finally {
       setLoading(false);
     }
@@ -167,7 +172,7 @@ export function FileViewer({ filePath, onClose, addToNotepad }: FileViewerProps)
       setCurrentContent(newContent);
     } catch (err) {
       console.error("Failed to save file:", err);
-      throw new Error(`Failed to save file: ${err}`);
+      throw new Error(`Failed to save file: ${errorMessage(err)}`);
     }
   };
 
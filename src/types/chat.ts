@@ -29,6 +29,16 @@ export interface ChatMessage {
     status: 'running' | 'completed' | 'failed';
     duration?: number;
   };
+  // Structured tool call parsed from a saved session (messageType 'tool_call')
+  toolCall?: {
+    callId: string;
+    name: string;
+    arguments: unknown;
+    command: string[] | null;
+    exitCode: number | null;
+    output: string | null;
+    status: 'completed' | 'interrupted';
+  };
   // Optional structured plan payload for plan_update messages
   plan?: {
     explanation?: string | null;
@@ -40,9 +50,14 @@ export interface Conversation {
   id: string;
   title: string;
   messages: ChatMessage[];
+  // Set for sessions loaded from disk; messages stay empty until load_session is called
+  messageCount?: number;
+  // Start of the last message, for sessions loaded from disk
+  preview?: string;
   createdAt: number;
   updatedAt: number;
   isFavorite?: boolean;
+  tags?: string[];
   filePath?: string;
   isLoading?: boolean;
   projectRealpath?: string;
@@ -0,0 +1,48 @@
+// Shape of errors returned by the codex session commands (see CodexError in services/codex.rs).
+export type CodexError =
+  | { kind: "sessionNotRunning"; sessionId: string; message: string }
+  | { kind: "readOnlyMode"; message: string }
+  | { kind: "noActiveTurn"; sessionId: string; message: string }
+  | { kind: "tooManySessions"; limit: number; running: number; message: string }
+  | { kind: "approvalNotPending"; approvalId: string; message: string }
+  | { kind: "patchSplitUnsupported"; approvalId: string; message: string }
+  | { kind: "failed"; message: string };
+
+export const isSessionNotRunning = (
+  error: unknown,
+): error is Extract<CodexError, { kind: "sessionNotRunning" }> =>
+  typeof error === "object" && error !== null && (error as CodexError).kind === "sessionNotRunning";
+
+export const isApprovalNotPending = (
+  error: unknown,
+): error is Extract<CodexError, { kind: "approvalNotPending" }> =>
+  typeof error === "object" && error !== null && (error as CodexError).kind === "approvalNotPending";
+
+export const isTooManySessions = (
+  error: unknown,
+): error is Extract<CodexError, { kind: "tooManySessions" }> =>
+  typeof error === "object" && error !== null && (error as CodexError).kind === "tooManySessions";
+
+// Text for a failed start_codex_session, pointing at the session limit when that was hit.
+export const sessionStartErrorMessage = (error: unknown): string =>
+  isTooManySessions(error)
+    ? `Too many Codex sessions are running (${error.running} of ${error.limit}). Close one and try again.`
+    : `Failed to start Codex session: ${errorMessage(error)}`;
+
+// Shape of errors returned by read_file, read_file_lossy, write_file and append_file
+// (see FileAccessError in filesystem/file_types.rs).
+export type FileAccessError =
+  | { kind: "notFound"; path: string; message: string }
+  | { kind: "outsideAllowedRoots"; path: string; message: string }
+  | { kind: "failed"; message: string };
+
+// Human-readable text for an invoke error: a CodexError, FileAccessError or plain string.
+export const errorMessage = (error: unknown): string => {
+  if (typeof error === "object" && error !== null && (error as CodexError).kind === "patchSplitUnsupported") {
+    return "Codex can only apply this patch as a whole. Approve or deny all of its files instead.";
+  }
+  if (typeof error === "object" && error !== null && "message" in error) {
+    return String((error as { message: unknown }).message);
+  }
+  return String(error);
+};
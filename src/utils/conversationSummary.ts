@@ -0,0 +1,31 @@
+import type { Conversation } from "@/types/chat";
+
+/**
+ * Number of messages, also for list entries whose messages aren't loaded yet
+ */
+export function getMessageCount(conversation: Conversation): number {
+  return conversation.messages.length || conversation.messageCount || 0;
+}
+
+/**
+ * Start of the last message, falling back to the preview stored with list entries
+ */
+export function getPreviewText(conversation: Conversation, maxLength: number): string | undefined {
+  const lastMessage = conversation.messages[conversation.messages.length - 1];
+  const text = lastMessage?.content ?? conversation.preview;
+  if (!text) {
+    return undefined;
+  }
+  return text.length > maxLength ? text.substring(0, maxLength) + "..." : text;
+}
+
+/**
+ * Whether the title, the preview or any loaded message contains `query` (already lowercased)
+ */
+export function matchesSearch(conversation: Conversation, query: string): boolean {
+  return (
+    conversation.title.toLowerCase().includes(query) ||
+    (conversation.preview?.toLowerCase().includes(query) ?? false) ||
+    conversation.messages.some((msg) => msg.content.toLowerCase().includes(query))
+  );
+}
@@ -1,4 +1,5 @@
 import { invoke } from '@tauri-apps/api/core';
+import { errorMessage } from '@/utils/codexError';
 
 export interface WebFrameworkInfo {
   framework: 'nextjs' | 'react' | 'vite' | 'astro' | 'nuxt' | 'vue' | 'svelte' | 'angular' | 'unknown';
@@ -11,7 +12,7 @@ export async function detectWebFramework(projectPath: string): Promise<WebFramew
   try {
     // Read package.json to detect framework
     const packageJsonPath = `${projectPath}/package.json`;
-    const packageJsonContent = await invoke<string>('read_file', { path: packageJsonPath });
+    const { content: packageJsonContent } = await invoke<{ content: string }>('read_file', { filePath: packageJsonPath });
     const packageJson = JSON.parse(packageJsonContent);
     
     const dependencies = {
@@ -103,7 +104,7 @@ export async function detectWebFramework(projectPath: string): Promise<WebFramew
     
     return null;
   } catch (error) {
-    console.error('Failed to detect web framework:', error);
+    console.error('Failed to detect web framework:', errorMessage(error));
     return null;
   }
 }